@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use serde::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -12,6 +13,105 @@ use crate::template_xml;
 const VERBALIZED_SAMPLING_PROMPT: &str =
     include_str!("../../templates/verbalized_sampling_prompt.xml");
 
+/// Environment variable carrying the approach a speculative worker must follow.
+pub const APPROACH_ENV: &str = "FORGER_APPROACH";
+
+/// One alternative approach from the generation output.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Approach {
+    pub approach: String,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub why_different: String,
+    #[serde(default)]
+    pub trade_off: String,
+    #[serde(default)]
+    pub avoids_error: String,
+}
+
+impl Approach {
+    /// @returns The approach with its rationale, as handed to a speculative worker.
+    pub fn instructions(&self) -> String {
+        let mut text = self.approach.trim().to_string();
+        if !self.why_different.trim().is_empty() {
+            text.push_str(&format!(
+                "\n\nWhy it differs: {}",
+                self.why_different.trim()
+            ));
+        }
+        if !self.trade_off.trim().is_empty() {
+            text.push_str(&format!("\nAccepted trade-off: {}", self.trade_off.trim()));
+        }
+        text
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Recommendation {
+    index: usize,
+}
+
+/// Parsed alternative approach generation output.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApproachSet {
+    pub alternative_approaches: Vec<Approach>,
+    #[serde(default)]
+    recommended: Option<Recommendation>,
+}
+
+impl ApproachSet {
+    /// @param k Maximum number of approaches to return.
+    /// @returns The recommended approach first, then the rest by likelihood of avoiding the error.
+    pub fn top(&self, k: usize) -> Vec<&Approach> {
+        let recommended = self
+            .recommended
+            .as_ref()
+            .map(|r| r.index)
+            .filter(|&i| i < self.alternative_approaches.len());
+
+        let mut ranked: Vec<(usize, &Approach)> =
+            self.alternative_approaches.iter().enumerate().collect();
+        ranked.sort_by_key(|(i, a)| {
+            let likelihood = match a.avoids_error.trim().to_ascii_lowercase().as_str() {
+                "high" => 0,
+                "medium" => 1,
+                "low" => 2,
+                _ => 3,
+            };
+            (Some(*i) != recommended, likelihood, *i)
+        });
+        ranked.into_iter().take(k).map(|(_, a)| a).collect()
+    }
+}
+
+/// @param path Cached generation output.
+/// @returns The approaches, tolerating prose or code fences around the JSON.
+pub fn load_approaches(path: &Path) -> Result<ApproachSet> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read alternative approaches: {}", path.display()))?;
+    parse_approaches(&raw)
+}
+
+fn parse_approaches(raw: &str) -> Result<ApproachSet> {
+    let start = raw.find('{').context("No JSON object in approach output")?;
+    let end = raw
+        .rfind('}')
+        .context("No JSON object in approach output")?;
+    serde_json::from_str(&raw[start..=end]).context("Failed to parse alternative approaches")
+}
+
+/// @returns A prompt section pinning the session to an assigned approach, if any.
+pub fn assigned_approach_section() -> String {
+    match std::env::var(APPROACH_ENV) {
+        Ok(approach) if !approach.trim().is_empty() => format!(
+            "\n## 🧭 Assigned Approach\nThis is a speculative attempt. Implement the feature using this approach, even if another seems easier:\n\n{}\n",
+            approach.trim()
+        ),
+        _ => String::new(),
+    }
+}
+
 /// @param config Loaded configuration.
 /// @param feature Active feature to recover.
 /// @param error_context Optional error context for the stuck session.
@@ -29,7 +129,7 @@ pub fn generate_alternative_approaches(
         )
     })?;
 
-    let cache_file = cache_dir.join(format!("{}-approaches.json", slugify(&feature.description)));
+    let cache_file = cache_dir.join(format!("{}-approaches.json", feature.key));
     if config.alternative_approaches.cache_results && cache_file.exists() {
         return Ok(cache_file);
     }
@@ -42,8 +142,7 @@ pub fn generate_alternative_approaches(
             prompt_cache_dir.display()
         )
     })?;
-    let prompt_cache_path =
-        prompt_cache_dir.join(format!("{}-prompt.txt", slugify(&feature.description)));
+    let prompt_cache_path = prompt_cache_dir.join(format!("{}-prompt.txt", feature.key));
     fs::write(&prompt_cache_path, &prompt).with_context(|| {
         format!(
             "Failed to write alternative approach prompt: {}",
@@ -51,13 +150,13 @@ pub fn generate_alternative_approaches(
         )
     })?;
 
-    let cache_file = cache_dir.join(format!("{}-approaches.json", slugify(&feature.description)));
+    let cache_file = cache_dir.join(format!("{}-approaches.json", feature.key));
     if config.alternative_approaches.cache_results && cache_file.exists() {
         return Ok(cache_file);
     }
 
     let prompt = build_prompt(config, feature, error_context)?;
-    let prompt_cache_path = cache_dir.join(format!("{}-prompt.txt", slugify(&feature.description)));
+    let prompt_cache_path = cache_dir.join(format!("{}-prompt.txt", feature.key));
     fs::write(&prompt_cache_path, &prompt).with_context(|| {
         format!(
             "Failed to write alternative approach prompt: {}",
@@ -153,21 +252,31 @@ fn load_app_spec_excerpt(app_spec_path: &str) -> Result<String> {
     Ok(content.chars().take(1200).collect())
 }
 
-/// @param input Raw feature description.
-/// @returns A filesystem-safe slug.
-fn slugify(input: &str) -> String {
-    let mut slug = String::new();
-    for ch in input.chars() {
-        if ch.is_ascii_alphanumeric() {
-            slug.push(ch.to_ascii_lowercase());
-        } else if (ch.is_whitespace() || ch == '-' || ch == '_') && !slug.ends_with('_') {
-            slug.push('_');
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_approaches_prefers_recommended_then_likelihood() {
+        let raw = r#"Here you go:
+```json
+{
+  "alternative_approaches": [
+    {"approach": "A", "avoids_error": "low"},
+    {"approach": "B", "avoids_error": "high"},
+    {"approach": "C", "avoids_error": "medium"},
+    {"approach": "D", "avoids_error": "high"}
+  ],
+  "recommended": {"index": 2, "reasoning": "simplest"}
+}
+```"#;
+        let set = parse_approaches(raw).unwrap();
+        let top: Vec<&str> = set.top(3).iter().map(|a| a.approach.as_str()).collect();
+        assert_eq!(top, vec!["C", "B", "D"]);
     }
 
-    if slug.is_empty() {
-        "feature".to_string()
-    } else {
-        slug.trim_matches('_').to_string()
+    #[test]
+    fn test_parse_approaches_rejects_non_json() {
+        assert!(parse_approaches("no approaches today").is_err());
     }
 }
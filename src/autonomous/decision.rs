@@ -8,10 +8,15 @@ use std::path::Path;
 
 use crate::conductor;
 use crate::config::Config;
+use crate::db::features::FeatureStatus;
+use crate::db::history::ChangeSource;
 use crate::regression;
 
 use super::features::FeatureProgress;
-use super::verification::{classify_verification_failure, VerificationFailure};
+use super::git;
+use super::verification::{
+    self, classify_verification_failure, VerificationCache, VerificationFailure,
+};
 use crate::common::logging as debug_logger;
 
 /// Actions determined by the Supervisor
@@ -21,7 +26,7 @@ pub enum SupervisorAction {
     Command(&'static str),
     /// Fix a regression
     Fix {
-        feature: crate::db::features::Feature,
+        feature: Box<crate::db::features::Feature>,
         error: String,
     },
     /// All features complete, exit the loop (normal mode)
@@ -40,7 +45,7 @@ pub enum SupervisorAction {
 /// 3. **Context Setup**: If conductor requires context generation.
 /// 4. **Active Track**: If there's an active plan with remaining tasks.
 /// 5. **Continue**: If there are pending features.
-/// 6. **Stop**: All features are passing.
+/// 6. **Stop**: All features are passing, or the rest are quarantined/manual.
 pub fn determine_action(
     db_path: &Path,
     config: &Config,
@@ -131,15 +136,27 @@ pub fn determine_action(
 
     // --- Phase 4: DB Progress ---
     let progress = FeatureProgress::load_from_db(db_path)?;
-    println!(
-        "→ Progress: {} passing, {} remaining",
-        progress.passing, progress.remaining
-    );
+    let breakdown = progress.breakdown();
+    if breakdown.is_empty() {
+        println!("→ Progress: {} passing", progress.passing);
+    } else {
+        println!("→ Progress: {} passing, {}", progress.passing, breakdown);
+    }
 
     if progress.all_passing() {
         return Ok(SupervisorAction::Complete);
     }
 
+    // Remaining features are all blocked or quarantined (manual features count as done)
+    if progress.actionable() == 0 && progress.total() > 0 {
+        println!(
+            "→ No actionable features left ({} blocked, {} quarantined)",
+            progress.count(FeatureStatus::Blocked),
+            progress.count(FeatureStatus::Quarantined)
+        );
+        return Ok(SupervisorAction::Complete);
+    }
+
     // --- Phase 5: Auto-continue ---
     Ok(SupervisorAction::Command("auto-continue"))
 }
@@ -154,10 +171,18 @@ fn determine_action_for_target(
     let features = db.features().list_all()?;
 
     if let Some(feature) = features.iter().find(|f| f.id == Some(id)) {
-        if feature.passes {
+        if feature.status.is_done() {
             logger.info(&format!("Target feature {} already passes", id));
             return Ok(SupervisorAction::Complete);
         }
+        if !feature.status.is_actionable() {
+            println!(
+                "⏸ Target Feature #{} is {}, skipping",
+                id,
+                feature.status.as_str()
+            );
+            return Ok(SupervisorAction::Complete);
+        }
 
         // If feature has a stored error, trigger Fix mode to give agent context
         if let Some(ref error) = feature.last_error {
@@ -166,7 +191,7 @@ fn determine_action_for_target(
                 id
             );
             return Ok(SupervisorAction::Fix {
-                feature: feature.clone(),
+                feature: Box::new(feature.clone()),
                 error: error.clone(),
             });
         }
@@ -191,18 +216,37 @@ fn check_for_regressions(
     let db = crate::db::Database::open(db_path)?;
     let features = db.features().list_all()?;
 
-    let sample_size = if config.agent.verification_sample_size == 0 {
-        None
+    // Sample the riskiest features: least recently verified, touched by the
+    // last commit or flaky
+    let sample = if config.agent.verification_sample_size == 0 {
+        features.clone()
     } else {
-        Some(config.agent.verification_sample_size as usize)
+        let sampled = regression::sample_features(
+            &db,
+            &features,
+            config.agent.verification_sample_size as usize,
+        )?;
+        for (feature, risk) in &sampled {
+            logger.info(&format!(
+                "Regression sample: #{} {} ({})",
+                feature.id.unwrap_or(0),
+                feature.description,
+                risk.describe()
+            ));
+        }
+        sampled.into_iter().map(|(feature, _)| feature).collect()
+    };
+    let cache = if config.agent.verification_cache {
+        VerificationCache::open(&db, &verification::project_root(), &config.paths)
+    } else {
+        None
     };
-    let summary = regression::run_regression_check(
-        &features,
-        None,
-        sample_size,
-        false,
-        Some(&config.security),
-    )?;
+    let options = regression::CheckOptions {
+        cache: cache.as_ref(),
+        ..regression::CheckOptions::new(config)
+    };
+    let summary = regression::run_regression_check(&sample, &options)?;
+    regression::record_runs(&db, &summary)?;
 
     if summary.automated_failed == 0 {
         return Ok(None);
@@ -220,6 +264,15 @@ fn check_for_regressions(
             {
                 let error_msg = result.error_message.unwrap_or_default();
 
+                // A blocked command can't be fixed by changing code
+                if error_msg.starts_with("Security blocked") {
+                    println!("🚫 Verification command blocked: {}", feature.description);
+                    db.features()
+                        .with_source(ChangeSource::Regression)
+                        .mark_blocked(feature.db_id()?, &error_msg)?;
+                    continue;
+                }
+
                 // Classify the failure to avoid looping on broken verification commands
                 let failure_type = classify_verification_failure(&error_msg);
 
@@ -241,7 +294,9 @@ fn check_for_regressions(
                         ));
 
                         // Mark as failing so it goes back to pending queue
-                        db.features().mark_failing(&feature.description)?;
+                        db.features()
+                            .with_source(ChangeSource::Regression)
+                            .mark_failing(feature.db_id()?)?;
 
                         // If multiple features have broken verification, it's a systemic issue
                         if broken_verification_count >= 3 {
@@ -255,9 +310,15 @@ fn check_for_regressions(
                         continue;
                     }
                     VerificationFailure::AssertionFailure => {
-                        // Real regression - proceed with fix
+                        // Real regression - record it against the current commit, then fix
+                        db.features()
+                            .with_source(ChangeSource::Regression)
+                            .mark_failing_with_error(feature.db_id()?, Some(&error_msg))?;
+                        if let (Some(id), Some(commit)) = (feature.id, git::head_commit()?) {
+                            db.history().attach_commit(id, &commit)?;
+                        }
                         return Ok(Some(SupervisorAction::Fix {
-                            feature: feature.clone(),
+                            feature: Box::new(feature.clone()),
                             error: error_msg,
                         }));
                     }
@@ -5,11 +5,14 @@ use anyhow::Result;
 use std::path::Path;
 
 use crate::db;
+use crate::db::features::FeatureStatus;
 
 /// Feature progress status
 pub struct FeatureProgress {
     pub passing: usize,
     pub remaining: usize,
+    /// Feature count per lifecycle state
+    pub statuses: Vec<(FeatureStatus, usize)>,
 }
 
 impl FeatureProgress {
@@ -18,7 +21,12 @@ impl FeatureProgress {
         let database = db::Database::open(db_path)?;
         let repo = database.features();
         let (passing, remaining) = repo.count()?;
-        Ok(Self { passing, remaining })
+        let statuses = repo.count_by_status()?;
+        Ok(Self {
+            passing,
+            remaining,
+            statuses,
+        })
     }
 
     /// Check if the database has any features (determines if init has run)
@@ -51,33 +59,60 @@ impl FeatureProgress {
     pub fn all_passing(&self) -> bool {
         self.remaining == 0 && self.passing > 0
     }
+
+    /// Number of features in a lifecycle state
+    pub fn count(&self, status: FeatureStatus) -> usize {
+        self.statuses
+            .iter()
+            .find(|(s, _)| *s == status)
+            .map(|(_, n)| *n)
+            .unwrap_or(0)
+    }
+
+    /// Number of features the supervisor can still work on
+    pub fn actionable(&self) -> usize {
+        self.statuses
+            .iter()
+            .filter(|(s, _)| s.is_actionable())
+            .map(|(_, n)| n)
+            .sum()
+    }
+
+    /// Non-zero states other than passing, e.g. `3 failing, 1 quarantined`
+    pub fn breakdown(&self) -> String {
+        self.statuses
+            .iter()
+            .filter(|(s, n)| *n > 0 && *s != FeatureStatus::Passing)
+            .map(|(s, n)| format!("{} {}", n, s.as_str().replace('_', " ")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 }
-/// Get the first pending (not passing) feature from the database
+/// Get the first feature the supervisor can work on from the database
 pub fn get_first_pending_feature(db_path: &Path) -> Result<Option<db::features::Feature>> {
     if !db_path.exists() {
         return Ok(None);
     }
 
     let database = db::Database::open(db_path)?;
-    let features = database.features().list_all()?;
 
-    // Find first feature where passes = false (i.e., passes = 0)
-    Ok(features.into_iter().find(|f| !f.passes))
+    // Blocked and quarantined features wait for a human; manual ones are done
+    Ok(database.features().list_actionable()?.into_iter().next())
 }
 
-/// Get up to N pending (not passing) features for parallel processing
+/// Get up to N features that still need work, for parallel processing
 pub fn get_pending_features(db_path: &Path, limit: usize) -> Result<Vec<db::features::Feature>> {
     if !db_path.exists() {
         return Ok(Vec::new());
     }
 
     let database = db::Database::open(db_path)?;
-    let features = database.features().list_all()?;
 
-    // Get first N non-passing features
-    Ok(features
+    // Get first N features that still need work
+    Ok(database
+        .features()
+        .list_actionable()?
         .into_iter()
-        .filter(|f| !f.passes)
         .take(limit)
         .collect())
 }
@@ -93,3 +128,73 @@ pub fn get_feature_by_id(db_path: &Path, id: i64) -> Result<Option<db::features:
 
     Ok(features.into_iter().find(|f| f.id == Some(id)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::features::Feature;
+    use crate::spec::Priority;
+
+    #[test]
+    fn test_selection_skips_features_waiting_for_a_human() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db_path = temp.path().join("progress.db");
+        let database = db::Database::open(&db_path).unwrap();
+        let repo = database.features();
+        for (description, status) in [
+            ("Blocked by policy", FeatureStatus::Blocked),
+            ("Quarantined", FeatureStatus::Quarantined),
+            ("Failing", FeatureStatus::Failing),
+            ("Pending", FeatureStatus::Pending),
+        ] {
+            let id = repo
+                .insert(&Feature {
+                    verification_command: Some("true".to_string()),
+                    ..Feature::new("functional", description)
+                })
+                .unwrap();
+            repo.set_status(id, status).unwrap();
+        }
+
+        let first = get_first_pending_feature(&db_path).unwrap().unwrap();
+        assert_eq!(first.description, "Failing");
+        let pending: Vec<String> = get_pending_features(&db_path, 10)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.description)
+            .collect();
+        assert_eq!(pending, vec!["Failing", "Pending"]);
+
+        let progress = FeatureProgress::load_from_db(&db_path).unwrap();
+        assert_eq!(progress.actionable(), 2);
+    }
+
+    #[test]
+    fn test_selection_prefers_higher_priority() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db_path = temp.path().join("progress.db");
+        let database = db::Database::open(&db_path).unwrap();
+        let repo = database.features();
+        for (description, priority) in [
+            ("Low", Priority::Low),
+            ("Medium", Priority::Medium),
+            ("Critical", Priority::Critical),
+            ("Also medium", Priority::Medium),
+        ] {
+            repo.insert(&Feature {
+                priority,
+                ..Feature::new("functional", description)
+            })
+            .unwrap();
+        }
+
+        let pending: Vec<String> = get_pending_features(&db_path, 10)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.description)
+            .collect();
+        assert_eq!(pending, vec!["Critical", "Medium", "Also medium", "Low"]);
+        let first = get_first_pending_feature(&db_path).unwrap().unwrap();
+        assert_eq!(first.description, "Critical");
+    }
+}
@@ -11,10 +11,19 @@ use std::process::Command;
 // ─────────────────────────────────────────────────────────────────────────────
 
 /// Auto-commit a completed feature to git
-pub fn commit_completed_feature(feature_description: &str, verbose: bool) -> Result<()> {
+///
+/// Returns the new commit hash, or `None` if there was nothing to commit.
+pub fn commit_completed_feature(
+    feature_key: &str,
+    feature_description: &str,
+    verbose: bool,
+) -> Result<Option<String>> {
     stage_all_changes()?;
-    create_feature_commit(feature_description, verbose)?;
-    Ok(())
+    if create_feature_commit(feature_key, feature_description, verbose)? {
+        head_commit()
+    } else {
+        Ok(None)
+    }
 }
 
 fn stage_all_changes() -> Result<()> {
@@ -33,11 +42,23 @@ fn stage_all_changes() -> Result<()> {
     Ok(())
 }
 
-fn create_feature_commit(feature_description: &str, verbose: bool) -> Result<()> {
+/// Trailer identifying the feature a commit belongs to, stable across description edits
+const FEATURE_KEY_TRAILER: &str = "Feature-Key";
+
+fn create_feature_commit(
+    feature_key: &str,
+    feature_description: &str,
+    verbose: bool,
+) -> Result<bool> {
     let commit_msg = format!("feat: {}", feature_description);
+    let mut args = vec!["commit".to_string(), "-m".to_string(), commit_msg.clone()];
+    if !feature_key.is_empty() {
+        args.push("-m".to_string());
+        args.push(format!("{}: {}", FEATURE_KEY_TRAILER, feature_key));
+    }
 
     let status = Command::new("git")
-        .args(["commit", "-m", &commit_msg])
+        .args(&args)
         .status()
         .context("Failed to run git commit")?;
 
@@ -52,7 +73,7 @@ fn create_feature_commit(feature_description: &str, verbose: bool) -> Result<()>
         }
     }
 
-    Ok(())
+    Ok(status.success())
 }
 
 /// Discard all uncommitted changes to reset the working directory
@@ -145,7 +166,7 @@ pub fn stash_drop() -> Result<()> {
     Ok(())
 }
 
-/// Rebase a branch onto target (usually "main"), return true if successful
+/// Rebase a branch onto target (the configured base branch), return true if successful
 pub fn rebase(branch: &str, target: &str) -> Result<bool> {
     let status = Command::new("git")
         .args(["rebase", target, branch])
@@ -170,6 +191,172 @@ pub fn merge_ff_only(branch: &str) -> Result<bool> {
     Ok(status.success())
 }
 
+/// Merge a branch into the current branch with a merge commit
+///
+/// Aborts the merge on conflict so the working tree is left clean.
+pub fn merge_no_ff(branch: &str, message: &str) -> Result<bool> {
+    let status = Command::new("git")
+        .args(["merge", "--no-ff", "-m", message, branch])
+        .status()
+        .with_context(|| format!("Failed to merge branch: {}", branch))?;
+
+    if !status.success() {
+        let _ = Command::new("git").args(["merge", "--abort"]).status();
+    }
+
+    Ok(status.success())
+}
+
+/// Squash a branch into a single commit on the current branch
+///
+/// Resets the index and working tree if the squash conflicts or the commit fails.
+pub fn merge_squash(branch: &str, message: &str) -> Result<bool> {
+    let status = Command::new("git")
+        .args(["merge", "--squash", branch])
+        .status()
+        .with_context(|| format!("Failed to squash branch: {}", branch))?;
+
+    if !status.success() {
+        let _ = Command::new("git").args(["reset", "--merge"]).status();
+        return Ok(false);
+    }
+
+    let commit = Command::new("git")
+        .args(["commit", "-m", message])
+        .status()
+        .context("Failed to run git commit")?;
+
+    if !commit.success() {
+        let _ = Command::new("git").args(["reset", "--merge"]).status();
+    }
+
+    Ok(commit.success())
+}
+
+/// Push a branch to a remote (name, URL or local bare repository path)
+pub fn push(remote: &str, branch: &str) -> Result<bool> {
+    let status = Command::new("git")
+        .args(["push", remote, branch])
+        .status()
+        .with_context(|| format!("Failed to push {} to {}", branch, remote))?;
+
+    Ok(status.success())
+}
+
+/// Check whether a local branch exists
+pub fn branch_exists(branch: &str) -> Result<bool> {
+    let status = Command::new("git")
+        .args([
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", branch),
+        ])
+        .stdout(std::process::Stdio::null())
+        .status()
+        .context("Failed to run git rev-parse")?;
+
+    Ok(status.success())
+}
+
+/// Hash of the commit currently checked out, or `None` outside a repository
+pub fn head_commit() -> Result<Option<String>> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .context("Failed to run git rev-parse")?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
+/// Name of the currently checked-out branch, or `None` on a detached HEAD
+pub fn current_branch() -> Result<Option<String>> {
+    let output = Command::new("git")
+        .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .output()
+        .context("Failed to run git symbolic-ref")?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
+/// Rename a local branch, returning true if successful
+pub fn rename_branch(old: &str, new: &str) -> Result<bool> {
+    let status = Command::new("git")
+        .args(["branch", "-m", old, new])
+        .status()
+        .with_context(|| format!("Failed to rename branch {} to {}", old, new))?;
+
+    Ok(status.success())
+}
+
+/// Count commits on `branch` that are not on `base`
+pub fn commits_ahead(base: &str, branch: &str) -> Result<usize> {
+    let output = Command::new("git")
+        .args(["rev-list", "--count", &format!("{}..{}", base, branch)])
+        .output()
+        .context("Failed to run git rev-list")?;
+
+    if !output.status.success() {
+        anyhow::bail!("git rev-list failed for {}..{}", base, branch);
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .context("Unexpected git rev-list output")
+}
+
+/// List local branch names starting with a prefix
+pub fn list_branches(prefix: &str) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["for-each-ref", "--format=%(refname:short)", "refs/heads/"])
+        .output()
+        .context("Failed to run git for-each-ref")?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|name| name.starts_with(prefix))
+        .map(str::to_string)
+        .collect())
+}
+
+/// List worktrees as (path, branch) pairs; the branch is `None` for detached worktrees
+pub fn list_worktrees() -> Result<Vec<(std::path::PathBuf, Option<String>)>> {
+    let output = Command::new("git")
+        .args(["worktree", "list", "--porcelain"])
+        .output()
+        .context("Failed to run git worktree list")?;
+
+    let mut worktrees = Vec::new();
+    let mut current: Option<std::path::PathBuf> = None;
+    let mut branch: Option<String> = None;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            if let Some(prev) = current.take() {
+                worktrees.push((prev, branch.take()));
+            }
+            current = Some(std::path::PathBuf::from(path));
+        } else if let Some(name) = line.strip_prefix("branch refs/heads/") {
+            branch = Some(name.to_string());
+        }
+    }
+    if let Some(prev) = current {
+        worktrees.push((prev, branch));
+    }
+    Ok(worktrees)
+}
+
 /// Delete a branch (force)
 pub fn delete_branch_force(branch: &str) -> Result<()> {
     let _ = Command::new("git").args(["branch", "-D", branch]).output();
@@ -185,3 +372,344 @@ pub fn delete_branch(branch: &str) -> Result<bool> {
 
     Ok(status.success())
 }
+
+/// List files changed by the commits `commit_completed_feature` created for
+/// each of `features` (`(key, description)` pairs), in a single `git log`
+///
+/// Matches on the `Feature-Key: <key>` trailer, so it finds both the original
+/// implementation and any later fix commits even after the description was
+/// edited. Commits made before the trailer existed are found by their exact
+/// `feat: <description>` subject when no commit carries the key. Returns one
+/// file list per feature, in order.
+pub fn files_changed_by_feature_commits(features: &[(&str, &str)]) -> Result<Vec<Vec<String>>> {
+    if features.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Anchored so "Add login" doesn't also pick up "feat: Add login rate limiting"
+    let mut args = vec![
+        "log".to_string(),
+        "--format=%x1e%H%x1f%B%x1f".to_string(),
+        "--name-only".to_string(),
+    ];
+    for (key, description) in features {
+        if !key.is_empty() {
+            args.push(format!(
+                "--grep=^{}: {}$",
+                FEATURE_KEY_TRAILER,
+                escape_basic_regex(key)
+            ));
+        }
+        args.push(format!(
+            "--grep=^feat: {}$",
+            escape_basic_regex(description)
+        ));
+    }
+
+    let output = Command::new("git")
+        .args(&args)
+        .output()
+        .context("Failed to run git log")?;
+    if !output.status.success() {
+        return Ok(vec![Vec::new(); features.len()]);
+    }
+
+    let commits: Vec<(CommitMessage, Vec<String>)> = String::from_utf8_lossy(&output.stdout)
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut parts = record.splitn(3, '\x1f');
+            let hash = parts.next()?.trim();
+            let message = parts.next()?.trim();
+            let files = name_list(parts.next().unwrap_or_default().as_bytes());
+            Some((
+                CommitMessage {
+                    hash: hash.to_string(),
+                    message: message.to_string(),
+                },
+                files,
+            ))
+        })
+        .collect();
+
+    Ok(features
+        .iter()
+        .map(|(key, description)| files_for_feature(&commits, key, description))
+        .collect())
+}
+
+/// Files changed by a feature's commits: those carrying its key, or failing
+/// that, those with its exact `feat:` subject
+fn files_for_feature(
+    commits: &[(CommitMessage, Vec<String>)],
+    feature_key: &str,
+    feature_description: &str,
+) -> Vec<String> {
+    let collect = |matches: &dyn Fn(&CommitMessage) -> bool| {
+        let mut files: Vec<String> = commits
+            .iter()
+            .filter(|(commit, _)| matches(commit))
+            .flat_map(|(_, files)| files.iter().cloned())
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    };
+
+    let keyed = collect(&|commit| commit.has_feature_key(feature_key));
+    if !keyed.is_empty() {
+        return keyed;
+    }
+    collect(&|commit| commit.has_feature_subject(feature_description))
+}
+
+/// A commit's full hash and message
+#[derive(Debug, Clone)]
+pub struct CommitMessage {
+    pub hash: String,
+    pub message: String,
+}
+
+impl CommitMessage {
+    /// Whether `commit_completed_feature` made this commit for the feature
+    ///
+    /// Matches the `Feature-Key` trailer, or the exact `feat: <description>`
+    /// subject of commits made before the trailer existed.
+    pub fn is_for_feature(&self, feature_key: &str, feature_description: &str) -> bool {
+        self.has_feature_subject(feature_description) || self.has_feature_key(feature_key)
+    }
+
+    /// Whether the commit carries the `Feature-Key` trailer for `feature_key`
+    fn has_feature_key(&self, feature_key: &str) -> bool {
+        let trailer = format!("{}: {}", FEATURE_KEY_TRAILER, feature_key);
+        !feature_key.is_empty() && self.message.lines().any(|line| line == trailer)
+    }
+
+    /// Whether the commit's subject is exactly `feat: <description>`
+    fn has_feature_subject(&self, feature_description: &str) -> bool {
+        let subject = format!("feat: {}", feature_description);
+        self.message.lines().next() == Some(subject.as_str())
+    }
+}
+
+/// Commits that changed any of `paths`, newest first, in a single `git log`
+pub fn commits_touching(paths: &[String]) -> Result<Vec<CommitMessage>> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    let output = Command::new("git")
+        .args(["--literal-pathspecs", "log", "--format=%H%x1f%B%x1e", "--"])
+        .args(paths)
+        .output()
+        .context("Failed to run git log")?;
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\x1e')
+        .filter_map(|record| record.trim_start().split_once('\x1f'))
+        .map(|(hash, message)| CommitMessage {
+            hash: hash.to_string(),
+            message: message.trim().to_string(),
+        })
+        .collect())
+}
+
+/// Escape a literal for git's default (POSIX basic) regex syntax
+fn escape_basic_regex(literal: &str) -> String {
+    let mut escaped = String::with_capacity(literal.len());
+    for c in literal.chars() {
+        if matches!(c, '\\' | '.' | '[' | ']' | '*' | '^' | '$') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Whether tracked files match HEAD; `false` outside a repository
+///
+/// Untracked files are ignored, as they don't end up in a worktree of HEAD.
+pub fn is_clean() -> Result<bool> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain", "--untracked-files=no"])
+        .output()
+        .context("Failed to run git status")?;
+
+    Ok(output.status.success() && output.stdout.is_empty())
+}
+
+/// Files changed since `commit`, including uncommitted changes
+pub fn files_changed_since(commit: &str) -> Result<Vec<String>> {
+    let verify = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("{}^{{commit}}", commit))
+        .output()
+        .context("Failed to run git rev-parse")?;
+    if !verify.status.success() {
+        anyhow::bail!("Unknown commit: {}", commit);
+    }
+
+    let output = Command::new("git")
+        .args(["diff", "--name-only", commit])
+        .output()
+        .context("Failed to run git diff")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git diff failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(name_list(&output.stdout))
+}
+
+/// Files changed by the given commits; unknown commits are skipped
+pub fn files_in_commits(commits: &[String]) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    for commit in commits {
+        let output = Command::new("git")
+            .args(["show", "--name-only", "--format=", commit])
+            .output()
+            .context("Failed to run git show")?;
+        if output.status.success() {
+            files.extend(name_list(&output.stdout));
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Non-empty lines of a `--name-only` listing
+fn name_list(stdout: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Hash of the tree the working directory would commit as, or `None` outside a repository
+///
+/// Covers uncommitted and untracked (but not ignored) files; paths matching
+/// `excludes` are left out. Uses a throwaway index, so the real index and
+/// HEAD are untouched.
+pub fn working_tree_hash(root: &std::path::Path, excludes: &[&str]) -> Result<Option<String>> {
+    let git_path = |name: &str| -> Result<Option<std::path::PathBuf>> {
+        let output = Command::new("git")
+            .args(["rev-parse", "--git-path", name])
+            .current_dir(root)
+            .output()
+            .context("Failed to run git rev-parse")?;
+        Ok(output
+            .status
+            .success()
+            .then(|| root.join(String::from_utf8_lossy(&output.stdout).trim())))
+    };
+    let Some(index) = git_path("index")? else {
+        return Ok(None);
+    };
+
+    let scratch = std::env::temp_dir().join(format!(
+        "forger-index-{}-{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    // Starting from the real index lets git reuse its cached file stats
+    if index.exists() {
+        std::fs::copy(&index, &scratch).context("Failed to copy git index")?;
+    }
+
+    let result = (|| -> Result<Option<String>> {
+        let status = Command::new("git")
+            .args(["add", "--all", "--", "."])
+            .current_dir(root)
+            .env("GIT_INDEX_FILE", &scratch)
+            .output()
+            .context("Failed to run git add")?
+            .status;
+        if !status.success() {
+            return Ok(None);
+        }
+        // Removed afterwards: excluding ignored paths would make `git add` fail
+        if !excludes.is_empty() {
+            Command::new("git")
+                .args(["rm", "-r", "--cached", "--ignore-unmatch", "--quiet", "--"])
+                .args(excludes)
+                .current_dir(root)
+                .env("GIT_INDEX_FILE", &scratch)
+                .output()
+                .context("Failed to run git rm")?;
+        }
+
+        let output = Command::new("git")
+            .arg("write-tree")
+            .current_dir(root)
+            .env("GIT_INDEX_FILE", &scratch)
+            .output()
+            .context("Failed to run git write-tree")?;
+        Ok(output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+    })();
+    let _ = std::fs::remove_file(&scratch);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_basic_regex() {
+        assert_eq!(escape_basic_regex("login-form"), "login-form");
+        assert_eq!(escape_basic_regex("v1.2 [beta]*"), r"v1\.2 \[beta\]\*");
+        assert_eq!(escape_basic_regex(r"^a\b$"), r"\^a\\b\$");
+    }
+
+    #[test]
+    fn test_feature_commits_match_key_or_exact_subject() {
+        let commit = |message: &str| CommitMessage {
+            hash: "abc".to_string(),
+            message: message.to_string(),
+        };
+        let keyed = commit("feat: Add login\n\nFeature-Key: login");
+        assert!(keyed.is_for_feature("login", "Let users log in"));
+        assert!(!keyed.is_for_feature("login-2", "Add login rate limiting"));
+
+        let legacy = commit("feat: Add login");
+        assert!(legacy.is_for_feature("login", "Add login"));
+        assert!(!legacy.is_for_feature("login-limits", "Add login rate limiting"));
+    }
+
+    #[test]
+    fn test_feature_files_prefer_keyed_commits() {
+        let commit = |message: &str, files: &[&str]| {
+            (
+                CommitMessage {
+                    hash: "abc".to_string(),
+                    message: message.to_string(),
+                },
+                files.iter().map(|f| f.to_string()).collect::<Vec<_>>(),
+            )
+        };
+        let commits = vec![
+            commit("fix: login\n\nFeature-Key: login", &["src/login.rs"]),
+            commit("feat: Add login", &["src/old.rs"]),
+            commit("feat: Add signup", &["src/signup.rs", "src/db.rs"]),
+        ];
+
+        assert_eq!(
+            files_for_feature(&commits, "login", "Add login"),
+            vec!["src/login.rs"]
+        );
+        // No keyed commits yet: fall back to the exact subject
+        assert_eq!(
+            files_for_feature(&commits, "signup", "Add signup"),
+            vec!["src/db.rs", "src/signup.rs"]
+        );
+        assert!(files_for_feature(&commits, "search", "Add search").is_empty());
+    }
+}
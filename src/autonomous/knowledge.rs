@@ -0,0 +1,166 @@
+//! Knowledge injection into session prompts
+//!
+//! Facts relevant to the feature being worked on are rendered into the
+//! continue and reasoning prompts, so the agent doesn't have to remember to
+//! query the knowledge base. The section is capped at
+//! `[agent] knowledge_token_budget` and every injection is logged.
+//!
+//! Features have no dependency model, so facts from other passing features
+//! in the same category are included as an approximation of the features
+//! this one builds on.
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::common::logging::DebugLogger;
+use crate::config::Config;
+use crate::db::features::Feature;
+use crate::db::knowledge::RelevantFact;
+use crate::db::Database;
+
+/// Rough token estimate used for the budget (about four characters per token)
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Prompt section with the facts relevant to `feature`, or an empty string
+///
+/// Failures to read the knowledge base are logged and never block a session.
+pub fn knowledge_section(
+    db_path: &Path,
+    feature: &Feature,
+    config: &Config,
+    logger: &DebugLogger,
+) -> String {
+    let budget = config.agent.knowledge_token_budget as usize;
+    if budget == 0 {
+        return String::new();
+    }
+
+    match relevant_facts(db_path, feature) {
+        Ok(facts) => {
+            let (section, injected, skipped) = render(&facts, budget);
+            log_injection(feature, &section, &injected, &skipped, logger);
+            section
+        }
+        Err(e) => {
+            logger.warning(&format!("Failed to load knowledge for prompt: {}", e));
+            String::new()
+        }
+    }
+}
+
+fn relevant_facts(db_path: &Path, feature: &Feature) -> Result<Vec<RelevantFact>> {
+    if feature.id.is_none() || !db_path.exists() {
+        return Ok(Vec::new());
+    }
+    Database::open(db_path)?.knowledge().relevant_to(feature)
+}
+
+/// Render facts in relevance order until the budget is spent
+///
+/// Returns the section and the keys that were injected and left out.
+fn render(facts: &[RelevantFact], budget: usize) -> (String, Vec<String>, Vec<String>) {
+    let header = "## Known Facts\nFrom the knowledge base (`opencode-forger db knowledge list`):\n";
+    let mut lines = Vec::new();
+    let mut injected = Vec::new();
+    let mut skipped = Vec::new();
+    // The trailing blank line counts against the budget too
+    let mut used = estimate_tokens(header) + 1;
+
+    for relevant in facts {
+        let fact = &relevant.fact;
+        let mut line = format!("- `{}` = {}", fact.key, fact.value);
+        if let Some(description) = fact.description.as_deref().filter(|d| !d.is_empty()) {
+            line.push_str(&format!(" ({})", description));
+        }
+        line.push_str(&format!(" [{}]", relevant.relevance.as_str()));
+
+        let cost = estimate_tokens(&line) + 1;
+        if used + cost > budget {
+            skipped.push(fact.key.clone());
+            continue;
+        }
+        used += cost;
+        lines.push(line);
+        injected.push(fact.key.clone());
+    }
+
+    if lines.is_empty() {
+        return (String::new(), injected, skipped);
+    }
+    let section = format!("{}{}\n\n", header, lines.join("\n"));
+    (section, injected, skipped)
+}
+
+fn log_injection(
+    feature: &Feature,
+    section: &str,
+    injected: &[String],
+    skipped: &[String],
+    logger: &DebugLogger,
+) {
+    if injected.is_empty() && skipped.is_empty() {
+        return;
+    }
+    let tokens = estimate_tokens(section);
+    println!(
+        "📚 Injected {} knowledge fact(s) (~{} tokens)",
+        injected.len(),
+        tokens
+    );
+    logger.info(&format!(
+        "Knowledge injected for feature #{} (~{} tokens; same-category passing features \
+         stand in for dependencies): {}",
+        feature.id.unwrap_or(0),
+        tokens,
+        injected.join(", ")
+    ));
+    if !skipped.is_empty() {
+        logger.info(&format!(
+            "Knowledge left out (over budget): {}",
+            skipped.join(", ")
+        ));
+    }
+    logger.debug(&format!("Knowledge section:\n{}", section));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::knowledge::{Knowledge, Relevance};
+
+    fn fact(key: &str, value: &str, relevance: Relevance) -> RelevantFact {
+        RelevantFact {
+            fact: Knowledge {
+                key: key.to_string(),
+                value: value.to_string(),
+                category: "general".to_string(),
+                description: None,
+                feature_id: None,
+                session_id: None,
+                expires_at: None,
+                pinned: relevance == Relevance::Pinned,
+            },
+            relevance,
+        }
+    }
+
+    #[test]
+    fn test_render_respects_budget_in_relevance_order() {
+        let facts = vec![
+            fact("package_manager", "bun", Relevance::Pinned),
+            fact("schema_dump", &"x".repeat(400), Relevance::ThisFeature),
+            fact("dev_port", "3001", Relevance::Category),
+        ];
+
+        let (section, injected, skipped) = render(&facts, 60);
+        assert_eq!(injected, vec!["package_manager", "dev_port"]);
+        assert_eq!(skipped, vec!["schema_dump"]);
+        assert!(section.contains("- `package_manager` = bun [pinned]"));
+        assert!(estimate_tokens(&section) <= 60);
+
+        let (section, injected, _) = render(&facts, 5);
+        assert!(section.is_empty() && injected.is_empty());
+    }
+}
@@ -7,15 +7,17 @@ mod alternative;
 mod decision;
 mod display;
 mod features;
-mod git;
+pub mod git;
+mod knowledge;
 pub mod parallel;
 pub mod runner;
 pub mod security;
 mod session;
 pub mod settings;
-mod stats;
+pub mod stats;
 pub mod supervisor;
 pub mod templates;
+pub mod test_report;
 pub mod verification;
 mod verifier;
 mod webhook;
@@ -54,7 +56,12 @@ pub fn run(
 
     // Register instance globally
     let instance_repo = crate::db::InstanceRepository::open()?;
-    let instance_id = instance_repo.register(pid, "supervisor", settings.log_path.as_deref())?;
+    let instance_id = register_instance(
+        &instance_repo,
+        pid,
+        settings.log_path.as_deref(),
+        target_feature_id,
+    )?;
     logger.info(&format!("Process registered as instance #{}", instance_id));
 
     // Register Ctrl+C handler to create stop signal file AND update DB status
@@ -99,6 +106,7 @@ pub fn run(
         enhancement_mode,
         target_feature_id,
         banner_width,
+        instance_id,
     );
 
     // Final status display and cleanup
@@ -108,6 +116,36 @@ pub fn run(
     result
 }
 
+/// Register this process in the global instance registry
+///
+/// Parallel workers are spawned with their coordinator's instance id in the
+/// environment and register as its children; everything else is a supervisor.
+fn register_instance(
+    repo: &crate::db::InstanceRepository,
+    pid: u32,
+    log_path: Option<&str>,
+    target_feature_id: Option<i64>,
+) -> Result<i64> {
+    let parent_id = std::env::var(parallel::isolation::PARENT_INSTANCE_ENV)
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok());
+    let Some(parent_id) = parent_id else {
+        return repo.register(pid, "supervisor", log_path);
+    };
+
+    let worktree = std::env::current_dir()?.display().to_string();
+    let project_path =
+        std::env::var(parallel::isolation::PROJECT_PATH_ENV).unwrap_or_else(|_| worktree.clone());
+    repo.register_worker(
+        pid,
+        Some(parent_id),
+        &project_path,
+        &worktree,
+        target_feature_id,
+        log_path,
+    )
+}
+
 fn log_final_status(settings: &settings::LoopSettings, developer_mode: bool) {
     let logger = crate::common::logging::get();
     let db_path = Path::new(&settings.database_file);
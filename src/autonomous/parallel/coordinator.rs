@@ -1,20 +1,32 @@
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use super::merge::rebase_and_merge;
+use super::merge::merge_into_base;
 use super::types::WorkerResult;
 use super::worktree::remove_worktree;
 use crate::autonomous::git;
+use crate::config::{MergeStrategy, ParallelConfig};
+use crate::db::reconcile::{reconcile_worker_db, BranchOutcome};
+use crate::db::Database;
 
 /// Coordinator for parallel workers
 pub struct Coordinator {
     merge_queue: Vec<WorkerResult>,
+    config: ParallelConfig,
+    database_path: PathBuf,
 }
 
 impl Coordinator {
-    pub fn new(_worker_count: usize, _base_path: PathBuf) -> Self {
+    pub fn new(
+        _worker_count: usize,
+        _base_path: PathBuf,
+        config: ParallelConfig,
+        database_path: PathBuf,
+    ) -> Self {
         Self {
             merge_queue: Vec::new(),
+            config,
+            database_path,
         }
     }
 
@@ -25,33 +37,126 @@ impl Coordinator {
     }
 
     /// Process the merge queue - clean up all, merge successful ones
+    ///
+    /// Each worker's database is reconciled into the main database after its
+    /// branch is handled, so a feature only counts as passing once merged.
     pub fn process_merge_queue(&mut self) -> Result<usize> {
         let mut merged_count = 0;
+        let main_db = Database::open(&self.database_path)?;
 
-        for result in self.merge_queue.drain(..) {
+        for result in std::mem::take(&mut self.merge_queue) {
             println!("📦 Processing result for: {}", result.branch_name);
 
-            // ALWAYS remove worktree first
+            // Copy the worker database out before the worktree disappears
+            let staged_db = self.stage_worker_db(&result);
+
+            // ALWAYS remove worktree before merging (git can't rebase a checked-out branch)
             println!("  → Removing worktree...");
             remove_worktree(&result.worktree_path, &result.branch_name)?;
 
-            if result.success {
-                println!("  → Merging feature...");
-                if rebase_and_merge(&result.branch_name)? {
+            let outcome = if result.success {
+                println!(
+                    "  → Merging feature into {} ({})...",
+                    self.config.base_branch,
+                    self.config.strategy.as_str()
+                );
+                if merge_into_base(
+                    &result.branch_name,
+                    &result.feature_description,
+                    &self.config,
+                )? {
                     println!("  ✅ Merged successfully");
-                    // Delete the merged branch
-                    git::delete_branch(&result.branch_name).ok();
+                    // Delete the merged branch (squashed branches never look merged to git)
+                    if self.config.strategy == MergeStrategy::Squash {
+                        git::delete_branch_force(&result.branch_name).ok();
+                    } else {
+                        git::delete_branch(&result.branch_name).ok();
+                    }
                     merged_count += 1;
+                    BranchOutcome::Merged
                 } else {
-                    println!("  ⚠️ Rebase failed, branch left for manual review");
+                    println!(
+                        "  ⚠️ {} failed, branch left for manual review",
+                        self.config.strategy.as_str()
+                    );
+                    BranchOutcome::MergeFailed(format!(
+                        "Parallel merge failed: could not {} branch {} into {}; branch left for manual review",
+                        self.config.strategy.as_str(),
+                        result.branch_name,
+                        self.config.base_branch
+                    ))
                 }
             } else {
                 println!("  ❌ Worker failed, skipping merge (branch preserved for debugging)");
                 // We do NOT delete the branch here, so user can debug why it failed.
                 // But we DID remove the worktree so we don't hog the filesystem.
+                BranchOutcome::NotAttempted
+            };
+
+            if let Some(staged) = staged_db {
+                match reconcile_worker_db(&main_db, &staged, result.feature_id, &outcome) {
+                    Ok(summary) => println!(
+                        "  → Synced progress: feature {}, {} knowledge update(s), {} session(s)",
+                        if summary.feature_passes {
+                            "passing"
+                        } else {
+                            "failing"
+                        },
+                        summary.knowledge_updated + summary.knowledge_deleted,
+                        summary.sessions_copied
+                    ),
+                    Err(e) => println!("  ⚠️ Could not sync worker database: {:#}", e),
+                }
+                std::fs::remove_file(&staged).ok();
+            }
+
+            // The merge commit is what brought the feature's change into the base branch
+            if outcome == BranchOutcome::Merged {
+                if let Some(commit) = git::head_commit()? {
+                    main_db
+                        .history()
+                        .attach_commit(result.feature_id, &commit)?;
+                }
             }
         }
 
         Ok(merged_count)
     }
+
+    /// Snapshot a worker's database next to the main database
+    fn stage_worker_db(&self, result: &WorkerResult) -> Option<PathBuf> {
+        stage_worker_db(
+            &self.database_path,
+            &result.database_path,
+            &format!("feature-{}.db", result.feature_id),
+        )
+    }
+}
+
+/// Copy a worker database into `<main db dir>/workers/<file_name>`
+///
+/// Returns `None` (after printing why) if the worker database can't be read.
+pub(super) fn stage_worker_db(
+    main_database: &Path,
+    worker_database: &Path,
+    file_name: &str,
+) -> Option<PathBuf> {
+    if !worker_database.exists() {
+        println!("  ⚠️ Worker database missing, progress not synced");
+        return None;
+    }
+
+    let staged = main_database
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("workers")
+        .join(file_name);
+
+    match Database::open(worker_database).and_then(|db| db.snapshot_to(&staged)) {
+        Ok(()) => Some(staged),
+        Err(e) => {
+            println!("  ⚠️ Could not read worker database: {:#}", e);
+            None
+        }
+    }
 }
@@ -0,0 +1,443 @@
+//! Per-worker isolation for parallel mode
+//!
+//! Each worker slot gets its own port range and environment so dev servers
+//! started by concurrent workers don't collide. Each worker runs in its own
+//! process group, optionally under a CPU/memory budget for the whole group.
+
+use anyhow::{bail, Result};
+use chrono::NaiveDateTime;
+use std::process::{Child, Command};
+use std::time::Duration;
+
+use crate::config::ParallelConfig;
+use crate::db::instances::REGISTRY_TIME_FORMAT;
+
+/// Environment variable holding the worker slot id
+pub const WORKER_ID_ENV: &str = "FORGER_WORKER_ID";
+
+/// Environment variable holding the coordinator's registry instance id
+pub const PARENT_INSTANCE_ENV: &str = "FORGER_PARENT_INSTANCE_ID";
+
+/// Environment variable holding the main project path (workers run in a worktree)
+pub const PROJECT_PATH_ENV: &str = "FORGER_PROJECT_PATH";
+
+/// A worker slot: stable id plus the ports reserved for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkerSlot {
+    pub id: usize,
+    pub port_start: u16,
+    pub port_end: u16,
+}
+
+impl WorkerSlot {
+    /// Reserve the port range for slot `id`
+    pub fn new(id: usize, config: &ParallelConfig) -> Result<Self> {
+        if config.ports_per_worker == 0 {
+            bail!("[parallel] ports_per_worker must be at least 1");
+        }
+
+        let start = config.port_range_start as usize + id * config.ports_per_worker as usize;
+        let end = start + config.ports_per_worker as usize - 1;
+        if end > u16::MAX as usize {
+            bail!(
+                "Port range for worker {} ({}-{}) exceeds 65535; lower [parallel] port_range_start or ports_per_worker",
+                id,
+                start,
+                end
+            );
+        }
+
+        Ok(Self {
+            id,
+            port_start: start as u16,
+            port_end: end as u16,
+        })
+    }
+
+    /// Environment variables passed to the worker process
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (WORKER_ID_ENV, self.id.to_string()),
+            ("PORT", self.port_start.to_string()),
+            ("FORGER_PORT_RANGE_START", self.port_start.to_string()),
+            ("FORGER_PORT_RANGE_END", self.port_end.to_string()),
+        ]
+    }
+
+    /// Apply environment, process group and resource limits to a worker command
+    ///
+    /// On Unix the worker leads its own process group so the coordinator can
+    /// clean up everything it started. On Linux the CPU and memory limits are a
+    /// budget for that whole group, enforced by [`wait_for_worker`]; other Unix
+    /// systems fall back to rlimits set before exec, which every process the
+    /// worker spawns inherits as its own limit.
+    pub fn configure(&self, command: &mut Command, config: &ParallelConfig) {
+        command.envs(self.env());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            command.process_group(0);
+        }
+
+        #[cfg(all(unix, not(target_os = "linux")))]
+        {
+            use std::os::unix::process::CommandExt;
+
+            let cpu_seconds = config.worker_cpu_seconds;
+            let memory_bytes = config
+                .worker_memory_mb
+                .map(|mb| mb.saturating_mul(1024 * 1024));
+            if cpu_seconds.is_some() || memory_bytes.is_some() {
+                // SAFETY: the closure only calls setrlimit, which is async-signal-safe.
+                unsafe {
+                    command.pre_exec(move || {
+                        if let Some(seconds) = cpu_seconds {
+                            let limit = libc::rlimit {
+                                rlim_cur: seconds as libc::rlim_t,
+                                rlim_max: seconds as libc::rlim_t,
+                            };
+                            if libc::setrlimit(libc::RLIMIT_CPU, &limit) != 0 {
+                                return Err(std::io::Error::last_os_error());
+                            }
+                        }
+                        if let Some(bytes) = memory_bytes {
+                            let limit = libc::rlimit {
+                                rlim_cur: bytes as libc::rlim_t,
+                                rlim_max: bytes as libc::rlim_t,
+                            };
+                            if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                                return Err(std::io::Error::last_os_error());
+                            }
+                        }
+                        Ok(())
+                    });
+                }
+            }
+        }
+
+        #[cfg(any(not(unix), target_os = "linux"))]
+        {
+            let _ = config;
+        }
+    }
+}
+
+/// How often the coordinator samples a worker group's resource usage
+const BUDGET_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// CPU time and resident memory of a worker's whole process group
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GroupUsage {
+    pub cpu_seconds: u64,
+    pub rss_bytes: u64,
+}
+
+impl GroupUsage {
+    /// Describe the first `[parallel]` limit this usage exceeds, if any
+    pub fn exceeded(&self, config: &ParallelConfig) -> Option<String> {
+        if let Some(limit) = config.worker_cpu_seconds {
+            if self.cpu_seconds > limit {
+                return Some(format!(
+                    "used {}s of CPU time (worker_cpu_seconds = {})",
+                    self.cpu_seconds, limit
+                ));
+            }
+        }
+        if let Some(limit) = config.worker_memory_mb {
+            let used_mb = self.rss_bytes / (1024 * 1024);
+            if used_mb > limit {
+                return Some(format!(
+                    "used {} MB of memory (worker_memory_mb = {})",
+                    used_mb, limit
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Wait for a worker, killing its process group if it goes over budget
+///
+/// Returns whether the worker exited successfully. On Linux, when
+/// `worker_cpu_seconds` or `worker_memory_mb` is set, the group's combined
+/// CPU time (including children it has reaped) and resident memory are
+/// sampled every second and the whole group is killed once either limit is
+/// exceeded.
+pub fn wait_for_worker(child: &mut Child, config: &ParallelConfig) -> bool {
+    let budgeted = config.worker_cpu_seconds.is_some() || config.worker_memory_mb.is_some();
+    if !budgeted || !cfg!(target_os = "linux") {
+        return child.wait().map(|s| s.success()).unwrap_or(false);
+    }
+
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return status.success(),
+            Ok(None) => {}
+            Err(_) => return false,
+        }
+        if let Some(reason) = group_usage(child.id()).and_then(|u| u.exceeded(config)) {
+            eprintln!(
+                "⛔ Worker process group {} {}; killing it",
+                child.id(),
+                reason
+            );
+            terminate_worker_group(child.id());
+            let _ = child.wait();
+            return false;
+        }
+        std::thread::sleep(BUDGET_POLL_INTERVAL);
+    }
+}
+
+/// Sum the resource usage of every process in a process group
+#[cfg(target_os = "linux")]
+pub fn group_usage(pgid: u32) -> Option<GroupUsage> {
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if ticks_per_second <= 0 || page_size <= 0 {
+        return None;
+    }
+
+    let mut ticks = 0u64;
+    let mut pages = 0u64;
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|n| n.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let Some(fields) = stat_fields(pid) else {
+            continue;
+        };
+        if fields.get(5).and_then(|f| f.parse::<u32>().ok()) != Some(pgid) {
+            continue;
+        }
+        // utime, stime, cutime and cstime (fields 14-17), then rss (field 24)
+        for field in 14..=17 {
+            ticks += fields
+                .get(field)
+                .and_then(|f| f.parse::<u64>().ok())
+                .unwrap_or(0);
+        }
+        pages += fields
+            .get(24)
+            .and_then(|f| f.parse::<u64>().ok())
+            .unwrap_or(0);
+    }
+
+    Some(GroupUsage {
+        cpu_seconds: ticks / ticks_per_second as u64,
+        rss_bytes: pages * page_size as u64,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn group_usage(_pgid: u32) -> Option<GroupUsage> {
+    None
+}
+
+/// Fields of `/proc/<pid>/stat`, indexed by their 1-based number in proc(5)
+///
+/// The command name (field 2) may contain spaces, so the fields are split
+/// after its closing paren; fields 1 and 2 are left empty.
+#[cfg(target_os = "linux")]
+fn stat_fields(pid: u32) -> Option<Vec<String>> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let mut fields = vec![String::new(); 3];
+    fields.extend(
+        stat.rsplit_once(')')?
+            .1
+            .split_whitespace()
+            .map(str::to_string),
+    );
+    Some(fields)
+}
+
+/// Kill whatever is left of a finished worker's process group (e.g. dev servers)
+#[cfg(unix)]
+pub fn terminate_worker_group(pid: u32) {
+    // Negative PID targets the group; ignore errors, the group may already be gone.
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn terminate_worker_group(_pid: u32) {}
+
+/// Ask a supervisor to stop after its current session
+///
+/// Sends SIGINT, which the supervisor's Ctrl+C handler turns into a stop signal.
+/// Returns false if the signal could not be delivered.
+#[cfg(unix)]
+pub fn request_stop(pid: u32) -> bool {
+    unsafe { libc::kill(pid as libc::pid_t, libc::SIGINT) == 0 }
+}
+
+#[cfg(not(unix))]
+pub fn request_stop(_pid: u32) -> bool {
+    false
+}
+
+/// Kill a single process immediately
+#[cfg(unix)]
+pub fn kill_process(pid: u32) {
+    unsafe {
+        libc::kill(pid as libc::pid_t, libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn kill_process(_pid: u32) {}
+
+/// Check whether a process is still running
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> bool {
+    // Signal 0 performs error checking only; EPERM still means the process exists.
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+pub fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+/// Slack between a process starting and it registering itself, in seconds
+const REGISTRATION_SLACK_SECS: i64 = 5;
+
+/// Check whether `pid` is still the process registered at `registered_at`
+///
+/// PIDs are reused, so a live PID alone doesn't prove the registry row still
+/// describes it: a process that started after the registration is someone
+/// else. Returns false when the start time can't be read, so callers never
+/// signal a process they couldn't identify.
+pub fn registered_process_alive(pid: u32, registered_at: &str) -> bool {
+    let Ok(registered) = NaiveDateTime::parse_from_str(registered_at, REGISTRY_TIME_FORMAT) else {
+        return false;
+    };
+    process_alive(pid)
+        && process_started_at(pid).is_some_and(|started| {
+            started <= registered + chrono::Duration::seconds(REGISTRATION_SLACK_SECS)
+        })
+}
+
+/// Start time of a process (UTC), from `/proc/<pid>/stat` and the boot time
+#[cfg(target_os = "linux")]
+fn process_started_at(pid: u32) -> Option<NaiveDateTime> {
+    // Start time in clock ticks after boot is field 22
+    let start_ticks: i64 = stat_fields(pid)?.get(22)?.parse().ok()?;
+    let boot_time: i64 = std::fs::read_to_string("/proc/stat")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))?
+        .trim()
+        .parse()
+        .ok()?;
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks_per_second <= 0 {
+        return None;
+    }
+    let started = boot_time + start_ticks / ticks_per_second as i64;
+    chrono::DateTime::from_timestamp(started, 0).map(|t| t.naive_utc())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_started_at(_pid: u32) -> Option<NaiveDateTime> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worker_port_ranges_do_not_overlap() {
+        let config = ParallelConfig {
+            port_range_start: 9000,
+            ports_per_worker: 10,
+            ..ParallelConfig::default()
+        };
+
+        let first = WorkerSlot::new(0, &config).unwrap();
+        let second = WorkerSlot::new(1, &config).unwrap();
+        assert_eq!((first.port_start, first.port_end), (9000, 9009));
+        assert_eq!((second.port_start, second.port_end), (9010, 9019));
+        assert!(second.env().contains(&(WORKER_ID_ENV, "1".to_string())));
+        assert!(second.env().contains(&("PORT", "9010".to_string())));
+    }
+
+    #[test]
+    fn test_worker_port_range_overflow_is_rejected() {
+        let config = ParallelConfig {
+            port_range_start: 65530,
+            ports_per_worker: 10,
+            ..ParallelConfig::default()
+        };
+        assert!(WorkerSlot::new(0, &config).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_process_alive() {
+        assert!(process_alive(std::process::id()));
+    }
+
+    #[test]
+    fn test_group_usage_over_budget() {
+        let config = ParallelConfig {
+            worker_cpu_seconds: Some(60),
+            worker_memory_mb: Some(512),
+            ..ParallelConfig::default()
+        };
+        let within = GroupUsage {
+            cpu_seconds: 60,
+            rss_bytes: 512 * 1024 * 1024,
+        };
+        assert_eq!(within.exceeded(&config), None);
+
+        let cpu = GroupUsage {
+            cpu_seconds: 61,
+            ..within
+        };
+        assert!(cpu
+            .exceeded(&config)
+            .unwrap()
+            .contains("worker_cpu_seconds"));
+        let memory = GroupUsage {
+            rss_bytes: 600 * 1024 * 1024,
+            ..within
+        };
+        assert!(memory
+            .exceeded(&config)
+            .unwrap()
+            .contains("worker_memory_mb"));
+        assert_eq!(cpu.exceeded(&ParallelConfig::default()), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_group_usage_counts_own_group() {
+        let pgid = unsafe { libc::getpgrp() } as u32;
+        let usage = group_usage(pgid).unwrap();
+        assert!(usage.rss_bytes > 0);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_reused_pid_is_not_the_registered_process() {
+        let pid = std::process::id();
+        let now = chrono::Utc::now()
+            .naive_utc()
+            .format(REGISTRY_TIME_FORMAT)
+            .to_string();
+        assert!(registered_process_alive(pid, &now));
+        // A registration from before this process started belonged to someone else
+        assert!(!registered_process_alive(pid, "2000-01-01 00:00:00"));
+        assert!(!registered_process_alive(pid, "not a timestamp"));
+    }
+}
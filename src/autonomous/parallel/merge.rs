@@ -1,12 +1,23 @@
 use crate::autonomous::git;
+use crate::config::{MergeStrategy, ParallelConfig};
 use anyhow::Result;
 
-/// Rebase a branch onto main and fast-forward merge
-pub fn rebase_and_merge(branch_name: &str) -> Result<bool> {
-    // 1. Stash any changes in main
+/// Integrate a finished feature branch into the configured base branch
+///
+/// Uses the configured strategy (rebase + fast-forward, merge commit, or
+/// squash) and pushes the base branch if a remote is configured. A failed
+/// push is reported but does not undo the local merge.
+pub fn merge_into_base(
+    branch_name: &str,
+    feature_description: &str,
+    config: &ParallelConfig,
+) -> Result<bool> {
+    let base = config.base_branch.as_str();
+
+    // 1. Stash any changes in the base branch
     let stashed = git::stash_push("Auto-stash before parallel merge")?;
 
-    if !git::checkout_branch("main")? {
+    if !git::checkout_branch(base)? {
         // Restore stash before returning to avoid leaving stale stash entries
         if stashed {
             git::stash_pop().ok();
@@ -14,27 +25,47 @@ pub fn rebase_and_merge(branch_name: &str) -> Result<bool> {
         return Ok(false);
     }
 
-    // 2. Rebase the feature branch onto main (this checks it out in the main repo)
-    if !git::rebase(branch_name, "main")? {
-        // ALWAYS return to main
-        git::checkout_branch("main")?;
-        // Restore stash before returning to avoid leaving stale stash entries
-        if stashed {
-            git::stash_pop().ok();
+    // 2. Integrate using the configured strategy (always ends on the base branch)
+    let success = match config.strategy {
+        MergeStrategy::Rebase => rebase_and_fast_forward(branch_name, base)?,
+        MergeStrategy::Merge => git::merge_no_ff(
+            branch_name,
+            &format!("Merge branch '{}' into {}", branch_name, base),
+        )?,
+        MergeStrategy::Squash => {
+            git::merge_squash(branch_name, &format!("feat: {}", feature_description))?
         }
-        return Ok(false);
-    }
-
-    // 3. Checkout main again (rebase leaves you on the feature branch)
-    git::checkout_branch("main")?;
+    };
 
-    // 4. Fast-forward merge
-    let success = git::merge_ff_only(branch_name)?;
-
-    // 5. Pop stash if we stashed anything
+    // 3. Pop stash if we stashed anything
     if stashed {
         git::stash_pop().ok();
     }
 
+    // 4. Publish the updated base branch
+    if success {
+        if let Some(ref remote) = config.push_remote {
+            println!("  → Pushing {} to {}...", base, remote);
+            if !git::push(remote, base)? {
+                println!("  ⚠️ Push to {} failed, merge kept locally", remote);
+            }
+        }
+    }
+
     Ok(success)
 }
+
+/// Rebase a branch onto the base branch and fast-forward merge
+fn rebase_and_fast_forward(branch_name: &str, base: &str) -> Result<bool> {
+    // Rebase the feature branch onto base (this checks it out in the main repo)
+    if !git::rebase(branch_name, base)? {
+        // ALWAYS return to the base branch
+        git::checkout_branch(base)?;
+        return Ok(false);
+    }
+
+    // Checkout base again (rebase leaves you on the feature branch)
+    git::checkout_branch(base)?;
+
+    git::merge_ff_only(branch_name)
+}
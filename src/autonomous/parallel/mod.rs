@@ -1,8 +1,11 @@
 pub mod coordinator;
+pub mod isolation;
 pub mod merge;
 pub mod process;
+pub mod recovery;
+pub mod scheduler;
+pub mod speculative;
 pub mod types;
-pub mod utils;
 pub mod worktree;
 
 pub use process::run_parallel;
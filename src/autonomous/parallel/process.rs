@@ -1,14 +1,52 @@
 use anyhow::{Context, Result};
 use std::collections::VecDeque;
 use std::path::Path;
+use std::process::Command;
 use std::sync::mpsc;
 use std::time::Duration;
 
 use crate::autonomous::{display, features, session};
+use crate::config::ParallelConfig;
+use crate::db::features::FeatureStatus;
+use crate::db::history::ChangeSource;
 
 use super::coordinator::Coordinator;
+use super::isolation::{
+    terminate_worker_group, wait_for_worker, WorkerSlot, PARENT_INSTANCE_ENV, PROJECT_PATH_ENV,
+};
+use super::recovery::recover_previous_run;
+use super::scheduler::OverlapScheduler;
 use super::types::WorkerResult;
-use super::worktree::create_worktree;
+use super::worktree::{create_worktree, worktree_database_path};
+
+/// Build the `vibe` command for a worker running one feature in a worktree
+///
+/// The worker registers under `parent_instance` and gets the slot's ports
+/// and process group (plus per-process rlimits where group budgets aren't
+/// available).
+pub(super) fn worker_command(
+    feature_id: i64,
+    worktree: &Path,
+    slot: &WorkerSlot,
+    config: &ParallelConfig,
+    parent_instance: i64,
+    base_path: &Path,
+) -> Command {
+    let mut command = Command::new("opencode-forger");
+    command
+        .args([
+            "vibe",
+            "--limit",
+            "1",
+            "--feature-id",
+            &feature_id.to_string(),
+        ])
+        .current_dir(worktree)
+        .env(PARENT_INSTANCE_ENV, parent_instance.to_string())
+        .env(PROJECT_PATH_ENV, base_path);
+    slot.configure(&mut command, config);
+    command
+}
 
 /// Run parallel workers using git worktrees
 pub fn run_parallel(
@@ -38,11 +76,44 @@ pub fn run_parallel(
     let logger = crate::common::logging::get();
     let db_path = Path::new(&settings.database_file);
 
+    if !crate::autonomous::git::branch_exists(&config.parallel.base_branch)? {
+        anyhow::bail!(
+            "Base branch '{}' does not exist. Set [parallel] base_branch in forger.toml.",
+            config.parallel.base_branch
+        );
+    }
+
     // Register instance globally
     let instance_repo = crate::db::InstanceRepository::open()?;
     let instance_id = instance_repo.register(pid, "coordinator", settings.log_path.as_deref())?;
     logger.info(&format!("Process registered as instance #{}", instance_id));
 
+    // Ctrl+C (or `opencode-forger kill`) stops scheduling and waits for running workers
+    ctrlc::set_handler(move || {
+        std::fs::write(session::STOP_SIGNAL_FILE, "").ok();
+        println!("\n→ Ctrl+C detected, stopping after running workers finish...");
+
+        if let Ok(repo) = crate::db::InstanceRepository::open() {
+            let _ = repo.mark_stopped(instance_id);
+        }
+    })
+    .ok();
+
+    // Clean up after a coordinator that died mid-run
+    let base_path = std::env::current_dir()?;
+    let recovery = recover_previous_run(&config, db_path, &base_path, &instance_repo, instance_id)?;
+    if !recovery.is_empty() {
+        let message = format!(
+            "Recovery: {} orphan(s) killed, {} branch(es) merged, {} kept, {} cleaned",
+            recovery.orphans_killed,
+            recovery.branches_merged,
+            recovery.branches_kept,
+            recovery.branches_cleaned
+        );
+        println!("🩹 {}", message);
+        logger.info(&message);
+    }
+
     let mut iteration = 0usize;
 
     loop {
@@ -67,6 +138,8 @@ pub fn run_parallel(
             break;
         }
 
+        let _ = instance_repo.heartbeat(instance_id, None);
+
         // Get pending features
         let pending = features::get_pending_features(db_path, usize::MAX)?;
         if pending.is_empty() {
@@ -89,48 +162,84 @@ pub fn run_parallel(
             println!("   • #{}: {}", f.id.unwrap_or(0), f.description);
         }
 
-        let base_path = std::env::current_dir()?;
-        let mut coordinator = Coordinator::new(worker_count, base_path.clone());
+        let mut coordinator = Coordinator::new(
+            worker_count,
+            base_path.clone(),
+            config.parallel.clone(),
+            db_path.to_path_buf(),
+        );
+        let mut scheduler = match crate::db::Database::open(db_path) {
+            Ok(db) => OverlapScheduler::with_predictions(
+                config.parallel.avoid_file_overlap,
+                &db,
+                &pending,
+            ),
+            Err(e) => {
+                logger.warning(&format!("Scheduler: predictions unavailable: {}", e));
+                OverlapScheduler::new(false)
+            }
+        };
         let mut pending_queue: VecDeque<_> = pending.into();
         let (tx, rx) = mpsc::channel::<WorkerResult>();
         let mut active_workers = 0usize;
         let mut stop_requested = false;
+        // Slot ids are reused as workers finish so port ranges stay bounded
+        let mut free_slots: Vec<usize> = (0..worker_count).rev().collect();
 
-        let spawn_worker = |feature: crate::db::features::Feature| -> Result<()> {
+        let spawn_worker = |feature: crate::db::features::Feature, slot_id: usize| -> Result<()> {
+            let slot = WorkerSlot::new(slot_id, &config.parallel)?;
             let (worktree_path, branch_name) = create_worktree(&feature, &base_path, &config)?;
-            println!("🌳 Created worktree: {}", branch_name);
+            println!(
+                "🌳 Created worktree: {} (worker {}, ports {}-{})",
+                branch_name, slot.id, slot.port_start, slot.port_end
+            );
+            let database_path = worktree_database_path(&worktree_path, &config)?;
 
             let feature_id = feature.id.unwrap_or(0);
+            let feature_description = feature.description.clone();
+            crate::db::Database::open(db_path)?
+                .features()
+                .with_source(ChangeSource::Parallel)
+                .set_status(feature_id, FeatureStatus::InProgress)?;
             let wt = worktree_path.clone();
             let bn = branch_name.clone();
             let tx = tx.clone();
+            let parallel = config.parallel.clone();
+
+            let mut command = worker_command(
+                feature_id,
+                &wt,
+                &slot,
+                &config.parallel,
+                instance_id,
+                &base_path,
+            );
 
             std::thread::spawn(move || {
-                let success = match std::panic::catch_unwind(|| {
-                    std::process::Command::new("opencode-forger")
-                        .args([
-                            "vibe",
-                            "--limit",
-                            "1",
-                            "--feature-id",
-                            &feature_id.to_string(),
-                        ])
-                        .current_dir(&wt)
-                        .status()
-                        .map(|s| s.success())
-                        .unwrap_or(false)
-                }) {
-                    Ok(success) => success,
-                    Err(_) => {
-                        eprintln!("Worker {} panicked", feature_id);
-                        false
-                    }
-                };
+                let success =
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+                        let Ok(mut child) = command.spawn() else {
+                            return false;
+                        };
+                        let success = wait_for_worker(&mut child, &parallel);
+                        // Stop servers the worker left running in its process group
+                        terminate_worker_group(child.id());
+                        success
+                    })) {
+                        Ok(success) => success,
+                        Err(_) => {
+                            eprintln!("Worker {} panicked", feature_id);
+                            false
+                        }
+                    };
 
                 let _ = tx.send(WorkerResult {
                     feature_id,
+                    worker_id: slot.id,
+                    feature_description,
                     branch_name: bn,
                     worktree_path: wt,
+                    database_path,
                     success,
                 });
             });
@@ -139,8 +248,9 @@ pub fn run_parallel(
         };
 
         while active_workers < worker_count {
-            if let Some(feature) = pending_queue.pop_front() {
-                spawn_worker(feature)?;
+            if let Some(feature) = scheduler.next_feature(&mut pending_queue) {
+                let slot_id = free_slots.pop().unwrap_or(active_workers);
+                spawn_worker(feature, slot_id)?;
                 active_workers += 1;
             } else {
                 break;
@@ -155,6 +265,7 @@ pub fn run_parallel(
 
             let result = rx.recv().context("Worker result channel closed")?;
             active_workers = active_workers.saturating_sub(1);
+            let _ = instance_repo.heartbeat(instance_id, None);
 
             println!(
                 "{}  Worker {} finished ({})",
@@ -162,12 +273,20 @@ pub fn run_parallel(
                 result.feature_id,
                 if result.success { "success" } else { "failed" }
             );
+            scheduler.release(result.feature_id);
+            free_slots.push(result.worker_id);
             coordinator.queue_for_merge(result);
 
             if !stop_requested {
-                if let Some(feature) = pending_queue.pop_front() {
-                    spawn_worker(feature)?;
-                    active_workers += 1;
+                // A finished worker may unblock several deferred features
+                while active_workers < worker_count {
+                    if let Some(feature) = scheduler.next_feature(&mut pending_queue) {
+                        let slot_id = free_slots.pop().unwrap_or(active_workers);
+                        spawn_worker(feature, slot_id)?;
+                        active_workers += 1;
+                    } else {
+                        break;
+                    }
                 }
             }
         }
@@ -175,7 +294,10 @@ pub fn run_parallel(
         // Process merge queue
         println!("\n📦 Processing merge queue...");
         let merged = coordinator.process_merge_queue()?;
-        println!("✅ Merged {} features to main", merged);
+        println!(
+            "✅ Merged {} features to {}",
+            merged, config.parallel.base_branch
+        );
 
         logger.info(&format!(
             "Parallel iteration complete: {} workers, {} merged",
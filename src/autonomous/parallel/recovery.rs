@@ -0,0 +1,304 @@
+//! Startup recovery for interrupted parallel runs
+//!
+//! If a coordinator dies, its worktrees, feature branches and worker processes
+//! are left behind. Before scheduling new work the coordinator kills orphaned
+//! worker process groups, then walks the leftover feature branches: branches
+//! with no new commits are cleaned up, the rest are verified and either merged
+//! or kept for manual review according to `[parallel] recovery`. A merged
+//! branch's worker database is reconciled like a finished worker's.
+
+use anyhow::Result;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::process::Command;
+
+use super::coordinator::stage_worker_db;
+use super::isolation::{process_alive, registered_process_alive, terminate_worker_group};
+use super::merge::merge_into_base;
+use super::worktree::{remove_worktree, worktree_database_path};
+use crate::autonomous::{git, verification};
+use crate::config::{Config, RecoveryPolicy};
+use crate::db::features::Feature;
+use crate::db::history::ChangeSource;
+use crate::db::reconcile::{reconcile_worker_db, BranchOutcome};
+use crate::db::{Database, InstanceRepository};
+
+/// What startup recovery did
+#[derive(Debug, Default)]
+pub struct RecoverySummary {
+    pub orphans_killed: usize,
+    pub branches_cleaned: usize,
+    pub branches_merged: usize,
+    pub branches_kept: usize,
+}
+
+impl RecoverySummary {
+    pub fn is_empty(&self) -> bool {
+        self.orphans_killed == 0
+            && self.branches_cleaned == 0
+            && self.branches_merged == 0
+            && self.branches_kept == 0
+    }
+}
+
+/// Reconcile leftovers from a previous parallel run
+pub fn recover_previous_run(
+    config: &Config,
+    db_path: &Path,
+    base_path: &Path,
+    registry: &InstanceRepository,
+    current_instance: i64,
+) -> Result<RecoverySummary> {
+    let mut summary = RecoverySummary {
+        orphans_killed: kill_orphaned_workers(registry, base_path, current_instance)?,
+        ..RecoverySummary::default()
+    };
+
+    let parallel = &config.parallel;
+    let branches: Vec<String> = git::list_branches(&parallel.branch_prefix)?
+        .into_iter()
+        .filter(|b| *b != parallel.base_branch)
+        .filter(|b| parse_feature_id(b, &parallel.branch_prefix).is_some())
+        .collect();
+    if branches.is_empty() {
+        return Ok(summary);
+    }
+
+    println!(
+        "🔎 Found {} feature branch(es) from a previous run",
+        branches.len()
+    );
+    let worktrees = git::list_worktrees()?;
+    let db = Database::open(db_path)?;
+    let features = db.features().list_all()?;
+    let interactive = std::io::stdin().is_terminal();
+
+    for branch in branches {
+        let feature_id = parse_feature_id(&branch, &parallel.branch_prefix).unwrap_or(0);
+        let worktree = worktrees
+            .iter()
+            .find(|(_, b)| b.as_deref() == Some(branch.as_str()))
+            .map(|(path, _)| path.clone());
+
+        // Never lose uncommitted worker changes
+        if let Some(ref path) = worktree {
+            if commit_uncommitted_changes(path)? {
+                println!("   • {}: committed uncommitted worker changes", branch);
+            }
+        }
+
+        let ahead = git::commits_ahead(&parallel.base_branch, &branch).unwrap_or(0);
+        if ahead == 0 {
+            if let Some(ref path) = worktree {
+                remove_worktree(path, &branch)?;
+            }
+            git::delete_branch_force(&branch)?;
+            println!("   • {}: no new commits, cleaned up", branch);
+            summary.branches_cleaned += 1;
+            continue;
+        }
+
+        let feature = features.iter().find(|f| f.id == Some(feature_id));
+        let verified = match feature {
+            Some(f) => verify_branch(f, &branch, worktree.as_deref(), base_path, config)?,
+            None => None,
+        };
+        let verdict = match verified {
+            Some(true) => "verification passed",
+            Some(false) => "verification failed",
+            None => "not verified",
+        };
+        println!(
+            "   • {}: {} commit(s) ahead of {}, {}",
+            branch, ahead, parallel.base_branch, verdict
+        );
+
+        let merge = match parallel.recovery {
+            RecoveryPolicy::Merge => verified == Some(true),
+            RecoveryPolicy::Keep => false,
+            RecoveryPolicy::Ask if interactive => crate::tui::prompts::confirm(
+                &format!("     Merge {} into {}?", branch, parallel.base_branch),
+                verified == Some(true),
+            )?,
+            RecoveryPolicy::Ask => false,
+        };
+
+        // The worker's database goes with its worktree; keep a copy to merge back
+        let staged = worktree.as_deref().and_then(|path| {
+            let worker_db = worktree_database_path(path, config).ok()?;
+            stage_worker_db(
+                db_path,
+                &worker_db,
+                &format!("feature-{}-recovered.db", feature_id),
+            )
+        });
+
+        // The branch can't be rebased while checked out in a worktree
+        if let Some(ref path) = worktree {
+            remove_worktree(path, &branch)?;
+        }
+
+        let description = feature.map(|f| f.description.as_str()).unwrap_or(&branch);
+        if merge && merge_into_base(&branch, description, parallel)? {
+            if let (Some(staged), Some(id)) = (&staged, feature.and_then(|f| f.id)) {
+                if let Err(e) = reconcile_worker_db(&db, staged, id, &BranchOutcome::Merged) {
+                    println!("     ⚠️ Could not sync worker database: {:#}", e);
+                }
+            }
+            if let (Some(true), Some(id)) = (verified, feature.and_then(|f| f.id)) {
+                db.features()
+                    .with_source(ChangeSource::Parallel)
+                    .mark_passing(id)?;
+            }
+            git::delete_branch_force(&branch)?;
+            println!("     ✅ Merged into {}", parallel.base_branch);
+            summary.branches_merged += 1;
+        } else {
+            println!("     → Kept branch {} for manual review", branch);
+            summary.branches_kept += 1;
+        }
+        if let Some(staged) = staged {
+            std::fs::remove_file(&staged).ok();
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Kill worker process groups whose coordinator is gone
+///
+/// A worker's group is only killed if its PID still belongs to the registered
+/// process; otherwise the row is just marked as errored. Also marks dead
+/// coordinators from this project as errored in the registry.
+fn kill_orphaned_workers(
+    registry: &InstanceRepository,
+    base_path: &Path,
+    current_instance: i64,
+) -> Result<usize> {
+    let project_path = base_path.display().to_string();
+    let running = registry.list_running_for_project(&project_path)?;
+    let mut killed = 0;
+
+    for instance in &running {
+        if instance.id == current_instance {
+            continue;
+        }
+
+        let Some(parent_id) = instance.parent_id else {
+            // A top-level instance whose process vanished without deregistering
+            if !process_alive(instance.pid) {
+                registry.mark_error(instance.id)?;
+            }
+            continue;
+        };
+
+        let parent_alive = registry
+            .get(parent_id)?
+            .map(|p| p.status == "running" && process_alive(p.pid))
+            .unwrap_or(false);
+        if parent_alive {
+            continue;
+        }
+
+        if registered_process_alive(instance.pid, &instance.start_time) {
+            println!(
+                "🧹 Killing orphaned {} process group {} (instance #{})",
+                instance.role, instance.pid, instance.id
+            );
+            terminate_worker_group(instance.pid);
+            killed += 1;
+        } else if process_alive(instance.pid) {
+            println!(
+                "   Not killing PID {}: can't confirm it is still instance #{}",
+                instance.pid, instance.id
+            );
+        }
+        registry.mark_error(instance.id)?;
+    }
+
+    Ok(killed)
+}
+
+/// Run the feature's verification command against a branch
+///
+/// Uses the branch's existing worktree, or a temporary detached worktree.
+/// Returns `None` when the feature has no verification command.
+fn verify_branch(
+    feature: &Feature,
+    branch: &str,
+    worktree: Option<&Path>,
+    base_path: &Path,
+    config: &Config,
+) -> Result<Option<bool>> {
+    let steps = feature.verification_pipeline();
+    if steps.is_empty() {
+        return Ok(None);
+    }
+
+    let (dir, temporary) = match worktree {
+        Some(path) => (path.to_path_buf(), false),
+        None => {
+            let path = base_path.join(format!(".forger-recovery-{}", feature.id.unwrap_or(0)));
+            let status = Command::new("git")
+                .args(["worktree", "add", "--detach"])
+                .arg(&path)
+                .arg(branch)
+                .status()?;
+            if !status.success() {
+                return Ok(None);
+            }
+            (path, true)
+        }
+    };
+
+    let passed = verification::run_pipeline(&steps, &config.security, &dir)
+        .map(|run| run.success())
+        .unwrap_or(false);
+
+    if temporary {
+        remove_worktree(&dir, branch)?;
+    }
+    Ok(Some(passed))
+}
+
+/// Commit anything a worker left uncommitted in its worktree
+pub(super) fn commit_uncommitted_changes(worktree: &Path) -> Result<bool> {
+    let status = Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(worktree)
+        .output()?;
+    if String::from_utf8_lossy(&status.stdout).trim().is_empty() {
+        return Ok(false);
+    }
+
+    let add = Command::new("git")
+        .args(["add", "-A"])
+        .current_dir(worktree)
+        .status()?;
+    let commit = Command::new("git")
+        .args(["commit", "-m", "wip: recovered uncommitted worker changes"])
+        .current_dir(worktree)
+        .status()?;
+    Ok(add.success() && commit.success())
+}
+
+/// Extract the feature id from a `<prefix><id>-<slug>` branch name
+fn parse_feature_id(branch: &str, prefix: &str) -> Option<i64> {
+    branch.strip_prefix(prefix)?.split('-').next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feature_id() {
+        assert_eq!(
+            parse_feature_id("feature/12-user-login", "feature/"),
+            Some(12)
+        );
+        assert_eq!(parse_feature_id("forger/7-x", "forger/"), Some(7));
+        assert_eq!(parse_feature_id("feature/readme", "feature/"), None);
+        assert_eq!(parse_feature_id("main", "feature/"), None);
+    }
+}
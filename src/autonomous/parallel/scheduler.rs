@@ -0,0 +1,276 @@
+//! File-overlap-aware scheduling for parallel workers
+//!
+//! Parallel workers that edit the same files (routes, schema, package.json)
+//! are what make `merge_into_base` fail. The scheduler predicts which files
+//! each feature will touch and keeps features with overlapping predictions
+//! from running at the same time.
+
+use anyhow::Result;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use crate::autonomous::git;
+use crate::common::logging as debug_logger;
+use crate::db::features::Feature;
+use crate::db::Database;
+
+/// Meta key prefix for files listed in a feature's implementation packet
+const PACKET_FILES_KEY_PREFIX: &str = "packet_files:";
+
+/// Persist the files an implementation packet plans to touch
+///
+/// Stored in the `meta` table; worker databases carry it back to the main
+/// database when the coordinator reconciles them.
+pub fn record_packet_files(db: &Database, feature_id: i64, files: &[String]) -> Result<()> {
+    let normalized: BTreeSet<String> = files.iter().filter_map(|f| normalize_path(f)).collect();
+    let value = serde_json::to_string(&normalized)?;
+    db.meta().set(
+        &format!("{}{}", PACKET_FILES_KEY_PREFIX, feature_id),
+        &value,
+    )
+}
+
+/// Predict which files a feature will touch
+///
+/// Combines three sources, most specific first:
+/// 1. Files listed in a previously produced implementation packet.
+/// 2. Files in the failed-attempt diff captured in `last_error`.
+/// 3. `history`: files changed by the feature's earlier commits.
+pub fn predict_touched_files(
+    db: &Database,
+    feature: &Feature,
+    history: &[String],
+) -> Result<BTreeSet<String>> {
+    let mut files = BTreeSet::new();
+
+    if let Some(id) = feature.id {
+        if let Some(raw) = db
+            .meta()
+            .get(&format!("{}{}", PACKET_FILES_KEY_PREFIX, id))?
+        {
+            if let Ok(stored) = serde_json::from_str::<Vec<String>>(&raw) {
+                files.extend(stored);
+            }
+        }
+    }
+
+    if let Some(ref error) = feature.last_error {
+        files.extend(files_from_diff(error));
+    }
+
+    files.extend(history.iter().filter_map(|f| normalize_path(f)));
+
+    Ok(files)
+}
+
+/// Extract file paths from `diff --git a/... b/...` headers
+fn files_from_diff(text: &str) -> BTreeSet<String> {
+    text.lines()
+        .filter_map(|line| line.strip_prefix("diff --git "))
+        .flat_map(|rest| rest.split_whitespace())
+        .filter_map(|part| {
+            part.strip_prefix("a/")
+                .or_else(|| part.strip_prefix("b/"))
+                .and_then(normalize_path)
+        })
+        .collect()
+}
+
+/// Normalize a repository-relative path for comparison
+fn normalize_path(path: &str) -> Option<String> {
+    let trimmed = path.trim().trim_start_matches("./");
+    if trimmed.is_empty() || trimmed == "/dev/null" {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+/// Schedules features so that concurrently running workers don't share files
+pub struct OverlapScheduler {
+    enabled: bool,
+    predictions: HashMap<i64, BTreeSet<String>>,
+    active: HashMap<i64, BTreeSet<String>>,
+    reported: HashSet<(i64, i64)>,
+}
+
+impl OverlapScheduler {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            predictions: HashMap::new(),
+            active: HashMap::new(),
+            reported: HashSet::new(),
+        }
+    }
+
+    /// Build a scheduler with predictions for every queued feature
+    pub fn with_predictions(enabled: bool, db: &Database, features: &[Feature]) -> Self {
+        let mut scheduler = Self::new(enabled);
+        if !enabled {
+            return scheduler;
+        }
+
+        let logger = debug_logger::get();
+        // One git log for every queued feature's earlier commits
+        let keys: Vec<(&str, &str)> = features
+            .iter()
+            .map(|f| (f.key.as_str(), f.description.as_str()))
+            .collect();
+        let history = git::files_changed_by_feature_commits(&keys).unwrap_or_else(|e| {
+            logger.warning(&format!("Scheduler: could not read feature commits: {}", e));
+            vec![Vec::new(); features.len()]
+        });
+
+        for (feature, history) in features.iter().zip(&history) {
+            let Some(id) = feature.id else { continue };
+            match predict_touched_files(db, feature, history) {
+                Ok(files) => {
+                    if !files.is_empty() {
+                        logger.debug(&format!(
+                            "Scheduler: feature #{} predicted to touch {} file(s): {}",
+                            id,
+                            files.len(),
+                            files.iter().cloned().collect::<Vec<_>>().join(", ")
+                        ));
+                    }
+                    scheduler.set_prediction(id, files);
+                }
+                Err(e) => logger.warning(&format!(
+                    "Scheduler: could not predict files for feature #{}: {}",
+                    id, e
+                )),
+            }
+        }
+        scheduler
+    }
+
+    /// Set the predicted file set for a feature
+    pub fn set_prediction(&mut self, feature_id: i64, files: BTreeSet<String>) {
+        self.predictions.insert(feature_id, files);
+    }
+
+    /// Pop the first queued feature that doesn't overlap a running worker
+    ///
+    /// Deferred features stay in the queue in their original order. Each
+    /// deferral is logged once per blocking feature.
+    pub fn next_feature(&mut self, queue: &mut VecDeque<Feature>) -> Option<Feature> {
+        let index = queue.iter().position(|feature| {
+            let id = feature.id.unwrap_or(0);
+            match self.find_conflict(id) {
+                Some((blocker, shared)) => {
+                    self.report_deferral(feature, blocker, &shared);
+                    false
+                }
+                None => true,
+            }
+        })?;
+
+        let feature = queue.remove(index)?;
+        let id = feature.id.unwrap_or(0);
+        let files = self.predictions.get(&id).cloned().unwrap_or_default();
+        self.active.insert(id, files);
+        Some(feature)
+    }
+
+    /// Mark a feature's worker as finished so its files are free again
+    pub fn release(&mut self, feature_id: i64) {
+        self.active.remove(&feature_id);
+    }
+
+    /// Find a running feature whose predicted files overlap this feature's
+    fn find_conflict(&self, feature_id: i64) -> Option<(i64, Vec<String>)> {
+        if !self.enabled {
+            return None;
+        }
+        let files = self.predictions.get(&feature_id)?;
+        if files.is_empty() {
+            return None;
+        }
+
+        let mut active: Vec<_> = self.active.iter().collect();
+        active.sort_by_key(|(id, _)| **id);
+        active.into_iter().find_map(|(active_id, active_files)| {
+            let shared: Vec<String> = files.intersection(active_files).cloned().collect();
+            if shared.is_empty() {
+                None
+            } else {
+                Some((*active_id, shared))
+            }
+        })
+    }
+
+    fn report_deferral(&mut self, feature: &Feature, blocker: i64, shared: &[String]) {
+        let id = feature.id.unwrap_or(0);
+        if !self.reported.insert((id, blocker)) {
+            return;
+        }
+
+        let message = format!(
+            "Deferred feature #{} ({}): overlaps running feature #{} on {}",
+            id,
+            feature.description,
+            blocker,
+            shared.join(", ")
+        );
+        println!("⏸️  {}", message);
+        debug_logger::get().info(&format!("Scheduler: {}", message));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feature(id: i64) -> Feature {
+        Feature {
+            id: Some(id),
+            key: format!("feature-{}", id),
+            ..Feature::new("functional", &format!("Feature {}", id))
+        }
+    }
+
+    fn files(paths: &[&str]) -> BTreeSet<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_files_from_diff() {
+        let error = "assertion failed\n\n### Failed Implementation Diff:\n```diff\n\
+                     diff --git a/src/routes.rs b/src/routes.rs\n+fn x() {}\n\
+                     diff --git a/package.json b/package.json\n```";
+        assert_eq!(
+            files_from_diff(error),
+            files(&["package.json", "src/routes.rs"])
+        );
+    }
+
+    #[test]
+    fn test_overlapping_features_are_deferred() {
+        let mut scheduler = OverlapScheduler::new(true);
+        scheduler.set_prediction(1, files(&["src/routes.rs", "src/a.rs"]));
+        scheduler.set_prediction(2, files(&["src/routes.rs"]));
+        scheduler.set_prediction(3, files(&["src/b.rs"]));
+
+        let mut queue: VecDeque<_> = vec![feature(1), feature(2), feature(3)].into();
+
+        assert_eq!(scheduler.next_feature(&mut queue).unwrap().id, Some(1));
+        // Feature 2 shares src/routes.rs with running feature 1
+        assert_eq!(scheduler.next_feature(&mut queue).unwrap().id, Some(3));
+        assert!(scheduler.next_feature(&mut queue).is_none());
+        assert_eq!(queue.len(), 1);
+
+        scheduler.release(1);
+        assert_eq!(scheduler.next_feature(&mut queue).unwrap().id, Some(2));
+    }
+
+    #[test]
+    fn test_disabled_scheduler_keeps_queue_order() {
+        let mut scheduler = OverlapScheduler::new(false);
+        scheduler.set_prediction(1, files(&["src/routes.rs"]));
+        scheduler.set_prediction(2, files(&["src/routes.rs"]));
+
+        let mut queue: VecDeque<_> = vec![feature(1), feature(2)].into();
+        assert_eq!(scheduler.next_feature(&mut queue).unwrap().id, Some(1));
+        assert_eq!(scheduler.next_feature(&mut queue).unwrap().id, Some(2));
+    }
+}
@@ -0,0 +1,224 @@
+//! Speculative attempts for stuck features
+//!
+//! When a feature keeps failing, the top K alternative approaches are raced:
+//! each runs as a worker in its own worktree, and the coordinator verifies each
+//! branch as its worker finishes. The first branch whose verification command
+//! passes is merged; the remaining workers are killed and their branches discarded.
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Child;
+use std::time::Duration;
+
+use super::coordinator::stage_worker_db;
+use super::isolation::{terminate_worker_group, WorkerSlot};
+use super::merge::merge_into_base;
+use super::process::worker_command;
+use super::recovery::commit_uncommitted_changes;
+use super::worktree::{create_worktree_for_branch, remove_worktree, worktree_database_path};
+use crate::autonomous::alternative::{Approach, APPROACH_ENV};
+use crate::autonomous::{git, session, verification};
+use crate::config::Config;
+use crate::db::features::{Feature, VerificationStep};
+use crate::db::history::ChangeSource;
+use crate::db::reconcile::{reconcile_worker_db, BranchOutcome};
+use crate::db::Database;
+
+/// How often finished attempts are polled
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// One approach running in its own worktree
+struct Attempt {
+    number: usize,
+    branch: String,
+    worktree: PathBuf,
+    database_path: PathBuf,
+    child: Option<Child>,
+}
+
+/// Race alternative approaches for a feature
+///
+/// Attempts fork from, and the winner merges into, the currently checked-out
+/// branch. Returns the index (into `approaches`) of the merged attempt, or
+/// `None` if no attempt passed verification.
+pub fn race_approaches(
+    config: &Config,
+    feature: &Feature,
+    approaches: &[&Approach],
+    parent_instance: i64,
+) -> Result<Option<usize>> {
+    let feature_id = feature.id.unwrap_or(0);
+    let steps = feature.verification_pipeline();
+    if steps.is_empty() {
+        anyhow::bail!(
+            "Feature #{} has no verification command to judge attempts by",
+            feature_id
+        );
+    }
+
+    let base_path = std::env::current_dir()?;
+    let db_path = base_path.join(&config.paths.database_file);
+    let mut config = config.clone();
+    if let Some(branch) = git::current_branch()? {
+        config.parallel.base_branch = branch;
+    }
+
+    println!(
+        "🏁 Racing {} approaches for feature #{} from {}",
+        approaches.len(),
+        feature_id,
+        config.parallel.base_branch
+    );
+
+    let mut attempts = Vec::with_capacity(approaches.len());
+    for (index, approach) in approaches.iter().enumerate() {
+        let number = index + 1;
+        let branch = format!(
+            "{}{}-attempt-{}",
+            config.parallel.branch_prefix, feature_id, number
+        );
+        let (worktree, branch) = create_worktree_for_branch(feature, &branch, &base_path, &config)?;
+        let database_path = worktree_database_path(&worktree, &config)?;
+
+        let slot = WorkerSlot::new(index, &config.parallel)?;
+        let mut command = worker_command(
+            feature_id,
+            &worktree,
+            &slot,
+            &config.parallel,
+            parent_instance,
+            &base_path,
+        );
+        command.env(APPROACH_ENV, approach.instructions());
+
+        let child = match command.spawn() {
+            Ok(child) => Some(child),
+            Err(e) => {
+                println!("   ⚠️ Attempt {} failed to start: {}", number, e);
+                None
+            }
+        };
+        println!(
+            "   • Attempt {} ({}) [{}]: {}",
+            number, branch, approach.category, approach.approach
+        );
+
+        attempts.push(Attempt {
+            number,
+            branch,
+            worktree,
+            database_path,
+            child,
+        });
+    }
+
+    let winner = wait_for_winner(&mut attempts, &steps, &config);
+
+    // Stop attempts that are still running
+    for attempt in &mut attempts {
+        if let Some(mut child) = attempt.child.take() {
+            terminate_worker_group(child.id());
+            let _ = child.wait();
+        }
+    }
+
+    let main_db = Database::open(&db_path)?;
+    let mut merged = None;
+    for (index, attempt) in attempts.iter().enumerate() {
+        if Some(index) != winner {
+            remove_worktree(&attempt.worktree, &attempt.branch)?;
+            git::delete_branch_force(&attempt.branch)?;
+            continue;
+        }
+
+        commit_uncommitted_changes(&attempt.worktree)?;
+        let staged = stage_worker_db(
+            &db_path,
+            &attempt.database_path,
+            &format!("feature-{}-attempt-{}.db", feature_id, attempt.number),
+        );
+        remove_worktree(&attempt.worktree, &attempt.branch)?;
+
+        let outcome = if merge_into_base(&attempt.branch, &feature.description, &config.parallel)? {
+            git::delete_branch_force(&attempt.branch)?;
+            println!(
+                "   ✅ Attempt {} merged into {}",
+                attempt.number, config.parallel.base_branch
+            );
+            merged = Some(index);
+            BranchOutcome::Merged
+        } else {
+            println!(
+                "   ⚠️ Attempt {} passed but could not be merged; kept {} for manual review",
+                attempt.number, attempt.branch
+            );
+            BranchOutcome::MergeFailed(format!(
+                "Speculative attempt {} passed verification but could not be merged into {}; branch {} left for manual review",
+                attempt.number, config.parallel.base_branch, attempt.branch
+            ))
+        };
+
+        if let Some(staged) = staged {
+            if let Err(e) = reconcile_worker_db(&main_db, &staged, feature_id, &outcome) {
+                println!("   ⚠️ Could not sync attempt database: {:#}", e);
+            }
+            std::fs::remove_file(&staged).ok();
+        }
+        if outcome == BranchOutcome::Merged {
+            main_db
+                .features()
+                .with_source(ChangeSource::Parallel)
+                .mark_passing(feature_id)
+                .context("Failed to mark raced feature as passing")?;
+            if let Some(commit) = git::head_commit()? {
+                main_db.history().attach_commit(feature_id, &commit)?;
+            }
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Verify attempts as their workers exit; returns the first that passes
+fn wait_for_winner(
+    attempts: &mut [Attempt],
+    steps: &[VerificationStep],
+    config: &Config,
+) -> Option<usize> {
+    while attempts.iter().any(|a| a.child.is_some()) {
+        if session::stop_signal_exists() {
+            println!("   → Stop signal received, abandoning race");
+            return None;
+        }
+
+        for (index, attempt) in attempts.iter_mut().enumerate() {
+            let Some(child) = attempt.child.as_mut() else {
+                continue;
+            };
+            match child.try_wait() {
+                Ok(None) => continue,
+                Ok(Some(_)) | Err(_) => {
+                    // Stop servers the worker left running in its process group
+                    terminate_worker_group(child.id());
+                    attempt.child = None;
+                }
+            }
+
+            let passed = verification::run_pipeline(steps, &config.security, &attempt.worktree)
+                .map(|run| run.success())
+                .unwrap_or(false);
+            println!(
+                "   {} Attempt {} finished, verification {}",
+                if passed { "✅" } else { "❌" },
+                attempt.number,
+                if passed { "passed" } else { "failed" }
+            );
+            if passed {
+                return Some(index);
+            }
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    None
+}
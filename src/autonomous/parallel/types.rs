@@ -4,7 +4,12 @@ use std::path::PathBuf;
 #[derive(Debug)]
 pub struct WorkerResult {
     pub feature_id: i64,
+    /// Slot the worker ran in (determines its port range)
+    pub worker_id: usize,
+    pub feature_description: String,
     pub branch_name: String,
     pub worktree_path: PathBuf,
+    /// Worker's private database inside the worktree
+    pub database_path: PathBuf,
     pub success: bool,
 }
@@ -2,16 +2,34 @@ use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use super::utils::slugify;
 use crate::autonomous::git;
 use crate::db::features::Feature;
 
-/// Create a worktree for a feature, with shared config symlinked
+/// Create a worktree for a feature, with shared config symlinked and a private database
 pub fn create_worktree(
     feature: &Feature,
     base_path: &Path,
     config: &crate::config::Config,
 ) -> Result<(PathBuf, String)> {
+    let branch_name = format!(
+        "{}{}-{}",
+        config.parallel.branch_prefix,
+        feature.id.unwrap_or(0),
+        feature.key
+    );
+    create_worktree_for_branch(feature, &branch_name, base_path, config)
+}
+
+/// Create a worktree for a feature on an explicitly named branch
+///
+/// Used by speculative attempts, which run the same feature on several branches.
+pub fn create_worktree_for_branch(
+    feature: &Feature,
+    branch_name: &str,
+    base_path: &Path,
+    config: &crate::config::Config,
+) -> Result<(PathBuf, String)> {
+    let branch_name = branch_name.to_string();
     // Check for git index lock to avoid hanging/contention
     let lock_file = base_path.join(".git/index.lock");
     if lock_file.exists() {
@@ -23,8 +41,7 @@ pub fn create_worktree(
     }
 
     let feature_id = feature.id.unwrap_or(0);
-    let slug = slugify(&feature.description);
-    let branch_name = format!("feature/{}-{}", feature_id, slug);
+    let parallel = &config.parallel;
     let worktree_path = base_path.join(&branch_name);
 
     // Clean up potential leftovers from previous runs
@@ -44,29 +61,54 @@ pub fn create_worktree(
         }
     }
 
-    // Force delete the branch if it exists (fresh start for the feature execution)
-    // We prioritize consistency over "resuming" potentially broken branch state.
-    // However, we cannot delete a branch if we are currently on it in the main repo.
+    // Start the feature from a fresh branch. A leftover branch with commits not
+    // on the base branch may hold work that passed, so back it up instead of
+    // deleting it. We cannot touch a branch that is checked out in the main repo.
     let current_branch_output = Command::new("git")
         .args(["rev-parse", "--abbrev-ref", "HEAD"])
         .output();
     if let Ok(output) = current_branch_output {
         let current = String::from_utf8_lossy(&output.stdout).trim().to_string();
         if current == branch_name {
-            // We are on the branch we want to delete, must move off it first
-            git::checkout_branch("main")?;
+            // We are on the branch we want to replace, must move off it first
+            git::checkout_branch(&parallel.base_branch)?;
         }
     }
 
-    git::delete_branch_force(&branch_name)?;
+    if git::branch_exists(&branch_name)?
+        && git::commits_ahead(&parallel.base_branch, &branch_name).unwrap_or(0) > 0
+    {
+        let backup = format!(
+            "backup/{}-{}",
+            branch_name,
+            chrono::Local::now().format("%Y%m%d%H%M%S")
+        );
+        if git::rename_branch(&branch_name, &backup)? {
+            println!("💾 Backed up unmerged branch {} to {}", branch_name, backup);
+        } else {
+            anyhow::bail!(
+                "Branch {} has unmerged commits and could not be backed up",
+                branch_name
+            );
+        }
+    } else {
+        git::delete_branch_force(&branch_name)?;
+    }
 
-    // Create the worktree with a new branch
+    // Create the worktree with a new branch starting from the base branch
     let worktree_str = worktree_path
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("Worktree path contains invalid UTF-8"))?;
 
     let status = Command::new("git")
-        .args(["worktree", "add", worktree_str, "-b", &branch_name])
+        .args([
+            "worktree",
+            "add",
+            worktree_str,
+            "-b",
+            &branch_name,
+            &parallel.base_branch,
+        ])
         .status()
         .context("Failed to create worktree")?;
 
@@ -74,63 +116,17 @@ pub fn create_worktree(
         anyhow::bail!("git worktree add failed for feature {}", feature_id);
     }
 
-    // Ensure .forger directory exists in worktree before symlinking database files
-    let worktree_forger_dir = worktree_path.join(".forger");
-    if !worktree_forger_dir.exists() {
-        std::fs::create_dir_all(&worktree_forger_dir)
-            .with_context(|| "Failed to create .forger dir in worktree".to_string())?;
-    }
-
-    // Symlink the database file using the configured path (not hardcoded .forger/)
-    let db_path = Path::new(&config.paths.database_file);
-    let db_name = db_path
-        .file_name()
-        .unwrap_or_else(|| std::ffi::OsStr::new("progress.db"))
-        .to_string_lossy();
-    let db_parent = db_path.parent().unwrap_or(Path::new(".forger"));
-
-    // Create parent directory in worktree for the database
-    let worktree_db_parent = worktree_path.join(db_parent);
-    if !worktree_db_parent.exists() {
-        std::fs::create_dir_all(&worktree_db_parent).with_context(|| {
-            format!(
-                "Failed to create db parent dir in worktree: {}",
-                worktree_db_parent.display()
-            )
-        })?;
-    }
-
-    // Symlink the database and WAL files to share progress between main and worktree.
-    // CONCURRENCY NOTE: This creates a single point of contention as all workers
-    // share the same SQLite database (with WAL mode enabled for better concurrency).
-    // While WAL mode supports multiple readers and a single writer with busy timeouts,
-    // very high parallelism (e.g., >4 workers) may experience lock contention.
-    // Future hardening could consider per-worker DBs with merge, or a coordinator model.
-    let db_files = [
-        db_name.to_string(),
-        format!("{}-shm", db_name),
-        format!("{}-wal", db_name),
-    ];
-    for filename in &db_files {
-        let main_file = std::env::current_dir()?.join(db_parent).join(filename);
-        let worktree_file = worktree_path.join(db_parent).join(filename);
-
-        if let Ok(meta) = worktree_file.symlink_metadata() {
-            if meta.file_type().is_dir() {
-                std::fs::remove_dir_all(&worktree_file).ok();
-            } else {
-                std::fs::remove_file(&worktree_file).ok();
-            }
-        }
-
-        #[cfg(unix)]
-        std::os::unix::fs::symlink(&main_file, &worktree_file)
-            .with_context(|| format!("Failed to symlink {}", filename))?;
-
-        #[cfg(windows)]
-        std::os::windows::fs::symlink_file(&main_file, &worktree_file)
-            .with_context(|| format!("Failed to symlink {}", filename))?;
-    }
+    // Give the worker its own snapshot of the database so parallel workers never
+    // contend for SQLite locks; the coordinator merges progress back afterwards.
+    let main_db = base_path.join(database_relative_path(config)?);
+    let worker_db = worktree_database_path(&worktree_path, config)?;
+    let main = crate::db::Database::open(&main_db)?;
+    crate::db::reconcile::create_worker_snapshot(&main, &worker_db).with_context(|| {
+        format!(
+            "Failed to create database snapshot for feature {}",
+            feature_id
+        )
+    })?;
 
     // Also symlink forger.toml if it exists
     let main_config = std::env::current_dir()?.join("forger.toml");
@@ -168,6 +164,56 @@ pub fn create_worktree(
     Ok((worktree_path, branch_name))
 }
 
+/// Create a worktree of HEAD without a branch
+///
+/// Used by `db check --isolate` to run verification commands of concurrent
+/// jobs in separate directories; remove it with [`remove_worktree`].
+pub fn create_detached_worktree(path: &Path) -> Result<()> {
+    if path.exists() {
+        remove_worktree(path, "")?;
+    }
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Worktree path contains invalid UTF-8"))?;
+    let output = Command::new("git")
+        .args(["worktree", "add", "--detach", path_str, "HEAD"])
+        .output()
+        .context("Failed to create worktree")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git worktree add failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Database path relative to the project root, as configured in `[paths]`
+fn database_relative_path(config: &crate::config::Config) -> Result<PathBuf> {
+    let db_path = Path::new(&config.paths.database_file);
+    if db_path.is_relative() {
+        return Ok(db_path.to_path_buf());
+    }
+    let cwd = std::env::current_dir()?;
+    db_path
+        .strip_prefix(&cwd)
+        .map(Path::to_path_buf)
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Parallel mode needs the database inside the project, got {}",
+                db_path.display()
+            )
+        })
+}
+
+/// Location of a worker's private database inside its worktree
+pub fn worktree_database_path(
+    worktree_path: &Path,
+    config: &crate::config::Config,
+) -> Result<PathBuf> {
+    Ok(worktree_path.join(database_relative_path(config)?))
+}
+
 /// Remove a worktree after completion
 pub fn remove_worktree(worktree_path: &Path, _branch_name: &str) -> Result<()> {
     // Force remove worktree (workers might have left untracked files)
@@ -4,9 +4,10 @@
 //! allowlist and blocked patterns before execution.
 
 use anyhow::{bail, Result};
+use std::collections::BTreeMap;
 use std::io::Read;
 use std::path::Path;
-use std::process::{Child, Command, Output, Stdio};
+use std::process::{Child, Command, ExitStatus, Stdio};
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -15,24 +16,40 @@ use crate::config::SecurityConfig;
 #[cfg(unix)]
 use std::os::unix::process::CommandExt;
 
+/// Where and how long a verification command runs
+#[derive(Debug, Clone, Default)]
+pub struct CommandOptions<'a> {
+    pub working_dir: Option<&'a Path>,
+    /// Defaults to `security.verification_timeout_secs`
+    pub timeout: Option<Duration>,
+    /// Variables set on top of the allowed part of the environment
+    pub env: Option<&'a BTreeMap<String, String>>,
+}
+
+/// Output of a verification command
+#[derive(Debug)]
+pub struct CommandOutput {
+    /// Exit status; `None` if the command was killed for exceeding its timeout
+    pub status: Option<ExitStatus>,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    /// The timeout the command ran under
+    pub timeout: Duration,
+}
+
 /// Validate and run a verification command safely.
 ///
-/// Returns the command output if the command is allowed, or an error
-/// if it violates security constraints.
+/// The command gets only the environment variables allowed by
+/// `security.verification_env`, plus `options.env`. Returns the output if the
+/// command is allowed (a timeout is reported in the output, not as an error),
+/// or an error if it violates security constraints or cannot be started.
 pub fn run_verified_command(
     cmd: &str,
     security_config: &SecurityConfig,
-    working_dir: Option<&Path>,
-) -> Result<Output> {
+    options: &CommandOptions,
+) -> Result<CommandOutput> {
     // First, check if the command matches any blocked patterns
-    if is_command_blocked(cmd, security_config) {
-        bail!(
-            "🚫 Security: Command rejected (matches blocked pattern).\n\
-             Command: {}\n\
-             Hint: Update security.blocked_patterns in config to allow this command.",
-            cmd
-        );
-    }
+    ensure_allowed(cmd, security_config)?;
 
     // Execute via sh -c for shell expansion, but only after validation
     let mut command = Command::new("sh");
@@ -48,9 +65,14 @@ pub fn run_verified_command(
         command.process_group(0);
     }
 
-    if let Some(dir) = working_dir {
+    if let Some(dir) = options.working_dir {
         command.current_dir(dir);
     }
+    command.env_clear();
+    command.envs(std::env::vars().filter(|(key, _)| env_allowed(key, security_config)));
+    if let Some(env) = options.env {
+        command.envs(env);
+    }
 
     let mut child = command.spawn().map_err(|e| {
         anyhow::anyhow!(
@@ -70,8 +92,9 @@ pub fn run_verified_command(
     let stdout_handle = spawn_reader_thread(stdout);
     let stderr_handle = spawn_reader_thread(stderr);
 
-    // Timeout duration (5 minutes)
-    let timeout = Duration::from_secs(300);
+    let timeout = options.timeout.unwrap_or(Duration::from_secs(
+        security_config.verification_timeout_secs,
+    ));
     let start_time = Instant::now();
 
     loop {
@@ -87,10 +110,11 @@ pub fn run_verified_command(
 
                 let stdout = stdout_handle.join().unwrap_or_default();
                 let stderr = stderr_handle.join().unwrap_or_default();
-                return Ok(Output {
-                    status,
+                return Ok(CommandOutput {
+                    status: Some(status),
                     stdout,
                     stderr,
+                    timeout,
                 });
             }
             Ok(None) => {
@@ -102,13 +126,12 @@ pub fn run_verified_command(
 
                     let stdout = stdout_handle.join().unwrap_or_default();
                     let stderr = stderr_handle.join().unwrap_or_default();
-
-                    bail!(
-                        "Verification command timed out after {}s.\nStdout: {}\nStderr: {}",
-                        timeout.as_secs(),
-                        String::from_utf8_lossy(&stdout),
-                        String::from_utf8_lossy(&stderr)
-                    );
+                    return Ok(CommandOutput {
+                        status: None,
+                        stdout,
+                        stderr,
+                        timeout,
+                    });
                 }
                 thread::sleep(Duration::from_millis(100));
             }
@@ -139,6 +162,30 @@ fn terminate_child(child: &mut Child) {
     let _ = child.wait();
 }
 
+/// Reject a command that matches a blocked pattern, without running it
+pub fn ensure_allowed(cmd: &str, security_config: &SecurityConfig) -> Result<()> {
+    if is_command_blocked(cmd, security_config) {
+        bail!(
+            "🚫 Security: Command rejected (matches blocked pattern).\n\
+             Command: {}\n\
+             Hint: Update security.blocked_patterns in config to allow this command.",
+            cmd
+        );
+    }
+    Ok(())
+}
+
+/// Whether an environment variable may be passed to verification commands
+fn env_allowed(key: &str, security_config: &SecurityConfig) -> bool {
+    security_config
+        .verification_env
+        .iter()
+        .any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => key == pattern,
+        })
+}
+
 /// Check if a command matches any blocked pattern.
 fn is_command_blocked(cmd: &str, security_config: &SecurityConfig) -> bool {
     if !security_config.enforce_allowlist {
@@ -181,6 +228,7 @@ mod tests {
                 "sudo".to_string(),
                 "| bash".to_string(), // Block any piping to bash
             ],
+            ..Default::default()
         }
     }
 
@@ -218,12 +266,61 @@ mod tests {
         // If the command backgrounds a process, that descendant can keep stdout/stderr
         // pipes open and hang output collection unless we terminate the process group.
         let start = Instant::now();
-        let output = run_verified_command("sleep 60 & echo done", &config, None).unwrap();
+        let output =
+            run_verified_command("sleep 60 & echo done", &config, &CommandOptions::default())
+                .unwrap();
 
-        assert!(output.status.success());
+        assert!(output.status.unwrap().success());
         assert!(String::from_utf8_lossy(&output.stdout).contains("done"));
 
         // This should return quickly; it must not wait for the background sleep.
         assert!(start.elapsed() < Duration::from_secs(5));
     }
+
+    #[test]
+    fn test_timeout_is_reported_in_output() {
+        let config = SecurityConfig {
+            verification_timeout_secs: 1,
+            ..test_security_config()
+        };
+        let start = Instant::now();
+        let output = run_verified_command(
+            "echo started; sleep 30",
+            &config,
+            &CommandOptions::default(),
+        )
+        .unwrap();
+
+        assert!(output.status.is_none());
+        assert_eq!(output.timeout, Duration::from_secs(1));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("started"));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_environment_is_scrubbed_to_allowlist() {
+        let config = SecurityConfig {
+            verification_env: vec!["PATH".to_string(), "FORGER_TEST_*".to_string()],
+            ..test_security_config()
+        };
+        let extra = BTreeMap::from([("STEP_VAR".to_string(), "step".to_string())]);
+        std::env::set_var("FORGER_TEST_ALLOWED", "yes");
+        std::env::set_var("FORGER_SECRET_TOKEN", "leak");
+
+        let output = run_verified_command(
+            "env",
+            &config,
+            &CommandOptions {
+                env: Some(&extra),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let env = String::from_utf8_lossy(&output.stdout);
+
+        assert!(env.contains("FORGER_TEST_ALLOWED=yes"));
+        assert!(env.contains("STEP_VAR=step"));
+        assert!(!env.contains("FORGER_SECRET_TOKEN"));
+        assert!(!env.contains("HOME="));
+    }
 }
@@ -2,10 +2,14 @@
 //!
 //! Handles fetching and parsing token/cost statistics from OpenCode sessions.
 
+use serde::{Deserialize, Serialize};
 use std::process::Command;
 
+/// `meta` key holding the latest token stats, read by `portfolio`
+pub const TOKEN_STATS_KEY: &str = "token_stats";
+
 /// Token usage statistics from OpenCode
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct TokenStats {
     pub input_tokens: u64,
     pub output_tokens: u64,
@@ -3,7 +3,7 @@ use std::path::Path;
 
 use crate::autonomous::features::{get_feature_by_id, get_first_pending_feature};
 use crate::config::Config;
-use crate::db::features::Feature;
+use crate::db::features::{Feature, FeatureStatus};
 
 use crate::autonomous::decision::SupervisorAction;
 use crate::autonomous::settings::{LoopAction, LoopSettings};
@@ -98,7 +98,12 @@ pub fn prepare_command(
                 };
 
                 if let Some(feature) = feature_opt {
-                    templates::generate_continue_template(&feature, config)?;
+                    if feature.status.is_actionable() {
+                        crate::db::Database::open(db_path)?
+                            .features()
+                            .set_status(feature.db_id()?, FeatureStatus::InProgress)?;
+                    }
+                    templates::generate_continue_template(&feature, config, db_path, logger)?;
                     println!(
                         "📋 Feature #{}: {}",
                         feature.id.unwrap_or(0),
@@ -142,7 +147,7 @@ pub fn prepare_command(
             templates::generate_fix_template(&feature, &error, db_path)?;
             Ok(ActionCommand {
                 name: "auto-fix-active".to_string(),
-                active_feature: Some(feature),
+                active_feature: Some(*feature),
                 should_break: false,
                 no_progress: false,
             })
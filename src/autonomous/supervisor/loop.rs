@@ -5,11 +5,15 @@ use std::thread;
 use std::time::Duration;
 
 use crate::config::Config;
+use crate::db::backup;
 use crate::db::features::Feature;
 
 use crate::autonomous::alternative;
 use crate::autonomous::decision::{determine_action, SupervisorAction};
 use crate::autonomous::display;
+use crate::autonomous::parallel::isolation::PARENT_INSTANCE_ENV;
+use crate::autonomous::parallel::scheduler::record_packet_files;
+use crate::autonomous::parallel::speculative::race_approaches;
 use crate::autonomous::session;
 use crate::autonomous::settings::{handle_session_result, LoopAction, LoopSettings};
 use crate::autonomous::stats;
@@ -49,6 +53,13 @@ fn execute_two_phase_feature(
                 packet.commands_to_run.len()
             ));
 
+            // Share predicted files with the parallel scheduler
+            if let Err(e) = crate::db::Database::open(Path::new(&settings.database_file))
+                .and_then(|db| record_packet_files(&db, packet.feature_id, &packet.touched_files()))
+            {
+                logger.warning(&format!("Failed to record packet files: {}", e));
+            }
+
             // Phase 2: Coding
             let coding_result = execute_coding_phase(&packet, feature, settings, logger)?;
             Ok(coding_result)
@@ -67,7 +78,12 @@ fn execute_two_phase_feature(
             } else {
                 println!("⚠️ Max retries exceeded, falling back to single-phase");
                 // Fall back to traditional single-phase session
-                crate::autonomous::templates::generate_continue_template(feature, config)?;
+                crate::autonomous::templates::generate_continue_template(
+                    feature,
+                    config,
+                    Path::new(&settings.database_file),
+                    logger,
+                )?;
                 logger.info("Falling back to single-phase implementation");
 
                 session::execute_opencode_session(
@@ -105,9 +121,13 @@ pub fn run_supervisor_loop(
     enhancement_mode: bool,
     target_feature_id: Option<i64>,
     banner_width: usize,
+    instance_id: i64,
 ) -> Result<()> {
     let db_path = Path::new(&settings.database_file);
     let logger = debug_logger::get();
+    let registry = crate::db::InstanceRepository::open()
+        .map_err(|e| logger.warning(&format!("Registry unavailable, no heartbeats: {}", e)))
+        .ok();
 
     // Mutable state for the loop
     let mut iteration = 0usize;
@@ -116,6 +136,7 @@ pub fn run_supervisor_loop(
     let mut last_run_success = true;
     let mut alternative_attempts: HashMap<String, u32> = HashMap::new();
     let mut last_error_context: Option<String> = None;
+    let mut session_record: Option<i64> = None;
 
     // --- Main Loop (Bounded by max_iterations) ---
     loop {
@@ -154,6 +175,15 @@ pub fn run_supervisor_loop(
             break;
         }
 
+        let snapshot_every = config.autonomous.snapshot_every as usize;
+        if snapshot_every > 0 && (iteration - 1).is_multiple_of(snapshot_every) {
+            take_rolling_snapshot(db_path, config, iteration);
+        }
+
+        // Status changes from here on (including regression checks) belong to this session
+        finish_session_record(db_path, session_record.take());
+        session_record = start_session_record(db_path, iteration);
+
         // --- Step 1: Determine Action ---
         let action = determine_action(db_path, config, target_feature_id)?;
 
@@ -190,15 +220,28 @@ pub fn run_supervisor_loop(
             break;
         }
 
+        // Report liveness and the current feature for `opencode-forger ps`
+        if let Some(ref registry) = registry {
+            let feature_id = active_feature.as_ref().and_then(|f| f.id);
+            if let Err(e) = registry.heartbeat(instance_id, feature_id) {
+                logger.warning(&format!("Failed to record heartbeat: {}", e));
+            }
+        }
+
         if action_no_progress {
             no_progress_count += 1;
-            maybe_generate_alternatives(
+            if maybe_generate_alternatives(
                 config,
                 active_feature.as_ref(),
                 no_progress_count,
                 last_error_context.as_deref(),
                 &mut alternative_attempts,
-            )?;
+                instance_id,
+            )? {
+                // A speculative attempt resolved the feature; skip the backoff
+                no_progress_count = 0;
+                continue;
+            }
             if settings.max_no_progress != u32::MAX && no_progress_count == settings.max_no_progress
             {
                 println!(
@@ -313,13 +356,16 @@ pub fn run_supervisor_loop(
             no_progress_count = 0;
         } else {
             no_progress_count += 1;
-            maybe_generate_alternatives(
+            if maybe_generate_alternatives(
                 config,
                 active_feature.as_ref(),
                 no_progress_count,
                 last_error_context.as_deref(),
                 &mut alternative_attempts,
-            )?;
+                instance_id,
+            )? {
+                no_progress_count = 0;
+            }
             if settings.max_no_progress != u32::MAX && no_progress_count == settings.max_no_progress
             {
                 println!(
@@ -337,9 +383,10 @@ pub fn run_supervisor_loop(
             }
         }
 
-        // Display token usage
-        if config.ui.show_progress {
-            if let Some(ref stats) = stats::fetch_token_stats() {
+        // Record token usage for `portfolio` and display it
+        if let Some(ref stats) = stats::fetch_token_stats() {
+            record_token_stats(db_path, stats);
+            if config.ui.show_progress {
                 display::display_token_stats(stats, banner_width);
             }
         }
@@ -367,6 +414,8 @@ pub fn run_supervisor_loop(
         }
     }
 
+    finish_session_record(db_path, session_record.take());
+
     // --- Final Result ---
     if last_run_success {
         Ok(())
@@ -381,32 +430,93 @@ pub fn run_supervisor_loop(
     }
 }
 
+/// @param db_path Path to the feature database.
+/// @param iteration Current iteration number.
+/// @returns Id of the session row, or None if the database isn't available yet.
+fn start_session_record(db_path: &Path, iteration: usize) -> Option<i64> {
+    if !db_path.exists() {
+        return None;
+    }
+    let db = crate::db::Database::open(db_path).ok()?;
+    let (passing, _) = db.features().count().ok()?;
+    db.sessions().start_session(iteration, passing).ok()
+}
+
+/// @param db_path Path to the feature database.
+/// @param stats Project totals reported by `opencode stats`.
+fn record_token_stats(db_path: &Path, stats: &stats::TokenStats) {
+    let Ok(value) = serde_json::to_string(stats) else {
+        return;
+    };
+    if let Ok(db) = crate::db::Database::open(db_path) {
+        let _ = db.meta().set(stats::TOKEN_STATS_KEY, &value);
+    }
+}
+
+/// @param db_path Path to the feature database.
+/// @param config Loaded configuration.
+/// @param iteration Iteration about to start.
+fn take_rolling_snapshot(db_path: &Path, config: &Config, iteration: usize) {
+    // Workers run on a throwaway copy; the coordinator snapshots the real one
+    if !db_path.exists() || std::env::var(PARENT_INSTANCE_ENV).is_ok() {
+        return;
+    }
+    let logger = debug_logger::get();
+    let dir = backup::snapshot_dir(db_path);
+    let label = format!("{}-{}", backup::ROLLING_LABEL, iteration);
+    let result = crate::db::Database::open(db_path)
+        .and_then(|db| backup::take_snapshot(&db, &dir, &label))
+        .and_then(|path| {
+            backup::prune_snapshots(&dir, config.autonomous.snapshots_kept as usize)?;
+            Ok(path)
+        });
+    match result {
+        Ok(path) => logger.info(&format!("Database snapshot: {}", path.display())),
+        Err(e) => logger.warning(&format!("Failed to snapshot database: {:#}", e)),
+    }
+}
+
+/// @param db_path Path to the feature database.
+/// @param session_id Session row to close, if one was started.
+fn finish_session_record(db_path: &Path, session_id: Option<i64>) {
+    let Some(session_id) = session_id else {
+        return;
+    };
+    if let Ok(db) = crate::db::Database::open(db_path) {
+        if let Ok((passing, _)) = db.features().count() {
+            let _ = db.sessions().complete_session(session_id, passing);
+        }
+    }
+}
+
 /// @param config Loaded configuration.
 /// @param feature Active feature reference.
 /// @param no_progress_count Current no-progress counter.
 /// @param error_context Optional error context string.
 /// @param attempt_tracker Tracks how many generations ran per feature.
-/// @returns Result of generation attempt.
+/// @param instance_id Registry id that speculative workers register under.
+/// @returns True if a speculative attempt got the feature passing and merged.
 fn maybe_generate_alternatives(
     config: &Config,
     feature: Option<&Feature>,
     no_progress_count: u32,
     error_context: Option<&str>,
     attempt_tracker: &mut HashMap<String, u32>,
-) -> Result<()> {
+    instance_id: i64,
+) -> Result<bool> {
     if !config.alternative_approaches.enabled {
-        return Ok(());
+        return Ok(false);
     }
     if config.alternative_approaches.retry_threshold == 0 {
-        return Ok(());
+        return Ok(false);
     }
     if no_progress_count < config.alternative_approaches.retry_threshold {
-        return Ok(());
+        return Ok(false);
     }
 
     let feature = match feature {
         Some(feature) => feature,
-        None => return Ok(()),
+        None => return Ok(false),
     };
 
     let attempts = attempt_tracker
@@ -418,7 +528,7 @@ fn maybe_generate_alternatives(
         config.agent.max_research_attempts
     };
     if *attempts >= max_attempts {
-        return Ok(());
+        return Ok(false);
     }
     *attempts += 1;
 
@@ -428,5 +538,43 @@ fn maybe_generate_alternatives(
         cache_path.display()
     );
 
-    Ok(())
+    let k = config.alternative_approaches.speculative_attempts as usize;
+    // Workers never start races of their own
+    let is_worker = std::env::var(PARENT_INSTANCE_ENV).is_ok();
+    if k == 0 || is_worker || !feature.has_verification() {
+        return Ok(false);
+    }
+
+    let logger = debug_logger::get();
+    let approaches = match alternative::load_approaches(&cache_path) {
+        Ok(set) => set,
+        Err(e) => {
+            logger.warning(&format!("Cannot race approaches: {:#}", e));
+            return Ok(false);
+        }
+    };
+    let top = approaches.top(k);
+    if top.is_empty() {
+        return Ok(false);
+    }
+
+    match race_approaches(config, feature, &top, instance_id) {
+        Ok(Some(index)) => {
+            logger.info(&format!(
+                "Speculative attempt {} resolved feature #{}",
+                index + 1,
+                feature.id.unwrap_or(0)
+            ));
+            Ok(true)
+        }
+        Ok(None) => {
+            println!("❌ No speculative attempt passed verification");
+            Ok(false)
+        }
+        Err(e) => {
+            println!("⚠️ Speculative attempts failed: {:#}", e);
+            logger.warning(&format!("Speculative attempts failed: {:#}", e));
+            Ok(false)
+        }
+    }
 }
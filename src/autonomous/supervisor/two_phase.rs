@@ -87,6 +87,19 @@ impl ImplementationPacket {
         }
         Ok(())
     }
+
+    /// All files the packet plans to touch, from both file actions and edits
+    pub fn touched_files(&self) -> Vec<String> {
+        let mut files: Vec<String> = self
+            .files_to_modify
+            .iter()
+            .map(|f| f.path.clone())
+            .chain(self.edits.iter().map(|e| e.file.clone()))
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    }
 }
 
 /// Result from reasoning phase
@@ -118,7 +131,13 @@ pub fn execute_reasoning_phase(
     ));
 
     // Generate reasoning phase prompt
-    let prompt = generate_reasoning_prompt(feature, config)?;
+    let knowledge = crate::autonomous::knowledge::knowledge_section(
+        Path::new(&settings.database_file),
+        feature,
+        config,
+        logger,
+    );
+    let prompt = generate_reasoning_prompt(feature, config, &knowledge)?;
 
     // Write to temp file for opencode
     let prompt_path = Path::new(".opencode/command/reasoning-phase.md");
@@ -241,7 +260,11 @@ pub fn execute_coding_phase(
 }
 
 /// Generate prompt for reasoning phase
-fn generate_reasoning_prompt(feature: &Feature, config: &Config) -> Result<String> {
+fn generate_reasoning_prompt(
+    feature: &Feature,
+    config: &Config,
+    knowledge: &str,
+) -> Result<String> {
     let steps_text = if feature.steps.is_empty() {
         "Not specified - create comprehensive implementation plan".to_string()
     } else {
@@ -267,8 +290,8 @@ You are in **reasoning phase** of a two-phase orchestration workflow. Your job i
 ## Acceptance Criteria
 
 {}
-
-## Context
+{}
+{}## Context
 - App spec file: `{}`
 
 ## Instructions
@@ -323,6 +346,8 @@ After you output the JSON, the coding phase will execute your plan.
         feature.id.unwrap_or(0),
         feature.description,
         steps_text,
+        crate::autonomous::alternative::assigned_approach_section(),
+        knowledge,
         config.paths.app_spec_file,
         feature.id.unwrap_or(0),
         feature.description
@@ -456,6 +481,10 @@ mod tests {
         assert_eq!(deserialized.files_to_modify.len(), 2);
         assert_eq!(deserialized.edits.len(), 1);
         assert_eq!(deserialized.commands_to_run.len(), 1);
+        assert_eq!(
+            deserialized.touched_files(),
+            vec!["src/auth.rs".to_string(), "src/main.rs".to_string()]
+        );
     }
 
     #[test]
@@ -2,11 +2,12 @@ use anyhow::Result;
 use std::path::Path;
 
 use crate::autonomous::settings::LoopSettings;
+use crate::autonomous::verification::{project_root, VerificationCache};
 use crate::autonomous::verifier::{
     handle_verification_failure, handle_verification_success, run_verification, VerificationResult,
 };
 use crate::config::Config;
-use crate::db::features::Feature;
+use crate::db::features::{Feature, FeatureStatus};
 
 /// Outcome of verifying a feature.
 pub struct VerificationOutcome {
@@ -34,17 +35,40 @@ pub fn perform_verification(
     println!("🔍 Supervisor: Verifying feature...");
     println!("   Feature: {}", feature.description);
 
-    let verification_result = run_verification(feature, &config.security)?;
+    // Hash the tree before verifying: the run may write files, and a pass only
+    // vouches for the tree it ran against
+    let db = crate::db::Database::open(db_path)?;
+    let cache = if config.agent.verification_cache {
+        VerificationCache::open(&db, &project_root(), &config.paths)
+    } else {
+        None
+    };
+
+    let verification_result = run_verification(feature, &config.security, &config.flaky)?;
     let mut made_progress = false;
     let mut error_context = None;
 
+    // Keep every run with its per-test results for `db runs` and fix prompts,
+    // and count the check towards the feature's flake rate
+    if let VerificationResult::Passed { verdict } | VerificationResult::Failed { verdict, .. } =
+        &verification_result
+    {
+        verdict.record(&db, feature.db_id()?)?;
+
+        // A pass on this tree lets the next regression sample skip the feature
+        if let Some(cache) = cache.as_ref().filter(|_| verdict.success()) {
+            cache.store(&feature.verification_pipeline(), feature.db_id()?)?;
+        }
+    }
+
     match verification_result {
-        VerificationResult::Passed => {
+        VerificationResult::Passed { .. } => {
             *last_run_success = true;
             made_progress = true;
             handle_verification_success(feature, db_path, config, settings, iteration)?;
         }
-        VerificationResult::Failed { error_message } => {
+        VerificationResult::Failed { error_message, .. }
+        | VerificationResult::CommandError { error_message } => {
             *last_run_success = false;
             error_context = Some(error_message.clone());
             handle_verification_failure(feature, &error_message, db_path, settings)?;
@@ -54,16 +78,17 @@ pub fn perform_verification(
                 println!("  ❌ No verification command (manual check required)");
                 *last_run_success = false;
                 error_context = Some("No verification command produced by agent".to_string());
-                let db = crate::db::Database::open(db_path)?;
                 db.features().mark_failing_with_error(
-                    &feature.description,
+                    feature.db_id()?,
                     Some("No verification command produced by agent"),
                 )?;
             } else {
-                println!("  ⚠️ No verification command; marking as manually verified");
+                println!("  ⚠️ No verification command; marking as manual (done, not verified automatically)");
                 *last_run_success = true;
                 made_progress = true;
                 handle_verification_success(feature, db_path, config, settings, iteration)?;
+                db.features()
+                    .set_status(feature.db_id()?, FeatureStatus::Manual)?;
             }
         }
         VerificationResult::SecurityBlocked { reason } => {
@@ -71,11 +96,8 @@ pub fn perform_verification(
             println!("     {}", reason);
             *last_run_success = false;
             error_context = Some(format!("Security blocked: {}", reason));
-            let db = crate::db::Database::open(db_path)?;
-            db.features().mark_failing_with_error(
-                &feature.description,
-                Some(&format!("Security blocked: {}", reason)),
-            )?;
+            db.features()
+                .mark_blocked(feature.db_id()?, &format!("Security blocked: {}", reason))?;
         }
     }
 
@@ -1,3 +1,4 @@
+use crate::common::logging::DebugLogger;
 use crate::config::Config;
 use crate::db::features::Feature;
 use crate::template_xml;
@@ -9,7 +10,12 @@ use std::path::Path;
 use super::settings::{LoopAction, LoopSettings};
 
 /// Generate a standard fix template
-pub fn generate_fix_template(feature: &Feature, error: &str, _db_path: &Path) -> Result<()> {
+///
+/// If the feature's latest verification run was parsed per test, the agent
+/// gets the summary of the failed tests instead of the raw output.
+pub fn generate_fix_template(feature: &Feature, error: &str, db_path: &Path) -> Result<()> {
+    let error = fix_error_message(feature, error, db_path);
+
     // Read template
     let template_path = Path::new("templates/commands/auto-fix.xml");
     let template = if template_path.exists() {
@@ -25,12 +31,15 @@ pub fn generate_fix_template(feature: &Feature, error: &str, _db_path: &Path) ->
     // Replace variables
     let content = template
         .replace("{{failing_feature}}", &feature.description)
-        .replace("{{error_message}}", error)
+        .replace("{{error_message}}", &error)
         .replace("{{current_feature}}", "latest changes")
         .replace("{{explore_instructions}}", explore_msg)
         .replace(
             "{{verification_command}}",
-            feature.verification_command.as_deref().unwrap_or("unknown"),
+            feature
+                .verification_summary()
+                .as_deref()
+                .unwrap_or("unknown"),
         );
 
     // Resolve includes (e.g. core/database.md)
@@ -42,11 +51,37 @@ pub fn generate_fix_template(feature: &Feature, error: &str, _db_path: &Path) ->
     Ok(())
 }
 
+/// The failed-test summary of the feature's latest run, or `error` as given
+fn fix_error_message(feature: &Feature, error: &str, db_path: &Path) -> String {
+    let latest = feature.id.filter(|_| db_path.exists()).and_then(|id| {
+        crate::db::Database::open(db_path)
+            .and_then(|db| db.verification().latest(id))
+            .ok()
+            .flatten()
+    });
+    match latest {
+        Some(run) if !run.passed && run.tests_failed > 0 => match run.summary {
+            // The error already leads with the summary (plus e.g. the failed diff)
+            Some(summary) if !error.starts_with(&summary) => summary,
+            _ => error.to_string(),
+        },
+        _ => error.to_string(),
+    }
+}
+
 /// Generate a minimal continue template with feature context injected by supervisor.
 /// This removes LLM responsibility for querying the database.
 /// Generate a minimal continue template with feature context injected by supervisor.
 /// This removes LLM responsibility for querying the database.
-pub fn generate_continue_template(feature: &Feature, config: &Config) -> Result<()> {
+///
+/// Knowledge facts relevant to the feature are read from `db_path` and injected
+/// (see `autonomous::knowledge`).
+pub fn generate_continue_template(
+    feature: &Feature,
+    config: &Config,
+    db_path: &Path,
+    logger: &DebugLogger,
+) -> Result<()> {
     let regression_sample = config.agent.verification_sample_size.max(1);
 
     let content = format!(
@@ -59,8 +94,8 @@ Implement this feature completely:
 
 ## Acceptance Criteria
 {}
-
-## 🛑 MANDATORY: GET YOUR BEARINGS
+{}
+{}## 🛑 MANDATORY: GET YOUR BEARINGS
 1. Read `{}` to refresh context.
 2. Run `opencode-forger db stats` to see overall progress.
 3. **REGRESSION CHECK**: Run {} of the features marked as passing to verify they still work.
@@ -79,7 +114,7 @@ Do not over-engineer or explore unrelated files.
 3. Write necessary tests if applicable.
 4. **VERIFY** that the verification command below is still correct for your implementation.
 5. If the command changed (e.g. new test file path), you **MUST** update it in the database:
-   `opencode-forger db exec "UPDATE features SET verification_command = 'your-new-command' WHERE id = {}"`
+   `opencode-forger db feature set-verify {} 'your-new-command'`
 6. Output `===SESSION_COMPLETE===` when implementation is done
 
 ## What Supervisor Does (NOT YOU)
@@ -108,12 +143,14 @@ The supervisor will automatically handle after your session:
                 .collect::<Vec<_>>()
                 .join("\n")
         },
+        crate::autonomous::alternative::assigned_approach_section(),
+        crate::autonomous::knowledge::knowledge_section(db_path, feature, config, logger),
         config.paths.app_spec_file,
         regression_sample,
         feature.id.unwrap_or(0),
         feature.id.unwrap_or(0),
         feature
-            .verification_command
+            .verification_summary()
             .as_deref()
             .unwrap_or("# No verification command specified")
     );
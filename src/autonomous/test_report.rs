@@ -0,0 +1,607 @@
+//! Test reporter parsers
+//!
+//! Extracts per-test results from the output of common test runners so a
+//! failed verification can be described by the tests that failed rather than
+//! a wall of output. Supported: `cargo test`, pytest (`-v` lines and the short
+//! summary), jest/vitest `--json`, `go test -json` and JUnit XML.
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::db::verification::{TestCase, TestStatus};
+
+/// Longest failure message kept per test
+const MAX_MESSAGE_CHARS: usize = 800;
+
+/// Test reporter an output was recognized as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Cargo,
+    Pytest,
+    Jest,
+    GoTest,
+    Junit,
+}
+
+impl ReportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportFormat::Cargo => "cargo",
+            ReportFormat::Pytest => "pytest",
+            ReportFormat::Jest => "jest",
+            ReportFormat::GoTest => "go",
+            ReportFormat::Junit => "junit",
+        }
+    }
+}
+
+/// Per-test results parsed from a verification command's output
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestReport {
+    pub format: ReportFormat,
+    pub tests: Vec<TestCase>,
+}
+
+impl TestReport {
+    pub fn failed(&self) -> impl Iterator<Item = &TestCase> {
+        self.tests.iter().filter(|t| t.status == TestStatus::Failed)
+    }
+
+    /// Short description of the failed tests, `None` if nothing failed
+    ///
+    /// Lists at most `max_tests` failures with the first lines of their message.
+    pub fn failure_summary(&self, max_tests: usize) -> Option<String> {
+        let failed: Vec<&TestCase> = self.failed().collect();
+        if failed.is_empty() {
+            return None;
+        }
+
+        let passed = self
+            .tests
+            .iter()
+            .filter(|t| t.status == TestStatus::Passed)
+            .count();
+        let mut summary = format!(
+            "{} of {} tests failed ({} passed, {}):\n",
+            failed.len(),
+            self.tests.len(),
+            passed,
+            self.format.as_str()
+        );
+        for test in failed.iter().take(max_tests) {
+            summary.push_str(&format!("\n✗ {}\n", test.name));
+            if let Some(message) = &test.message {
+                for line in message.lines().filter(|l| !l.trim().is_empty()).take(6) {
+                    summary.push_str(&format!("    {}\n", line.trim_end()));
+                }
+            }
+        }
+        if failed.len() > max_tests {
+            summary.push_str(&format!(
+                "\n... and {} more failed tests\n",
+                failed.len() - max_tests
+            ));
+        }
+        Some(summary)
+    }
+}
+
+type Parser = fn(&str) -> Vec<TestCase>;
+
+/// Parse the output of a verification command, if it came from a known reporter
+pub fn parse(stdout: &str, stderr: &str) -> Option<TestReport> {
+    let parsers: [(ReportFormat, Parser); 5] = [
+        (ReportFormat::Junit, parse_junit),
+        (ReportFormat::Jest, parse_jest),
+        (ReportFormat::GoTest, parse_go_test),
+        (ReportFormat::Cargo, parse_cargo),
+        (ReportFormat::Pytest, parse_pytest),
+    ];
+    for output in [stdout, stderr] {
+        for (format, parser) in parsers {
+            let tests = parser(output);
+            if !tests.is_empty() {
+                return Some(TestReport { format, tests });
+            }
+        }
+    }
+    None
+}
+
+/// Tests collected by name, in the order they were first seen
+#[derive(Default)]
+struct Collector {
+    tests: Vec<TestCase>,
+    index: HashMap<String, usize>,
+}
+
+impl Collector {
+    fn entry(&mut self, name: &str) -> &mut TestCase {
+        let next = self.tests.len();
+        let i = *self.index.entry(name.to_string()).or_insert(next);
+        if i == next {
+            self.tests.push(TestCase {
+                name: name.to_string(),
+                status: TestStatus::Passed,
+                duration_ms: None,
+                message: None,
+            });
+        }
+        &mut self.tests[i]
+    }
+
+    fn set(&mut self, name: &str, status: TestStatus) -> &mut TestCase {
+        let test = self.entry(name);
+        test.status = status;
+        test
+    }
+}
+
+fn message(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if text.chars().count() <= MAX_MESSAGE_CHARS {
+        return Some(text.to_string());
+    }
+    let cut: String = text.chars().take(MAX_MESSAGE_CHARS).collect();
+    Some(format!("{}...", cut))
+}
+
+/// `test path::name ... ok|FAILED|ignored`, with `---- name stdout ----` failure blocks
+fn parse_cargo(output: &str) -> Vec<TestCase> {
+    let mut collector = Collector::default();
+    let mut failure: Option<(String, String)> = None;
+
+    for line in output.lines() {
+        if let Some((name, text)) = failure.as_mut() {
+            if line.starts_with("---- ") || line == "failures:" {
+                let text = std::mem::take(text);
+                collector.entry(name).message = message(&text);
+                failure = None;
+            } else {
+                text.push_str(line);
+                text.push('\n');
+                continue;
+            }
+        }
+
+        if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"))
+        {
+            failure = Some((name.to_string(), String::new()));
+        } else if let Some((name, result)) = line
+            .strip_prefix("test ")
+            .and_then(|rest| rest.rsplit_once(" ... "))
+        {
+            let status = match result.trim() {
+                "ok" => TestStatus::Passed,
+                "FAILED" => TestStatus::Failed,
+                r if r.starts_with("ignored") => TestStatus::Skipped,
+                _ => continue,
+            };
+            collector.set(name.trim(), status);
+        }
+    }
+    if let Some((name, text)) = failure {
+        collector.entry(&name).message = message(&text);
+    }
+    collector.tests
+}
+
+/// pytest `-v` result lines, the short test summary and `--durations` lines
+fn parse_pytest(output: &str) -> Vec<TestCase> {
+    let mut collector = Collector::default();
+
+    for line in output.lines() {
+        let mut words = line.split_whitespace();
+        let (Some(first), Some(second)) = (words.next(), words.next()) else {
+            continue;
+        };
+
+        // Short summary: `FAILED tests/test_a.py::test_x - AssertionError: ...`
+        if matches!(first, "FAILED" | "ERROR") && second.contains("::") {
+            let test = collector.set(second, TestStatus::Failed);
+            if let Some((_, text)) = line.split_once(" - ") {
+                test.message = message(text);
+            }
+            continue;
+        }
+
+        // Verbose: `tests/test_a.py::test_x PASSED [ 50%]`
+        if first.contains("::") {
+            let status = match second {
+                "PASSED" | "XPASS" => TestStatus::Passed,
+                "FAILED" | "ERROR" => TestStatus::Failed,
+                "SKIPPED" | "XFAIL" => TestStatus::Skipped,
+                _ => continue,
+            };
+            collector.set(first, status);
+            continue;
+        }
+
+        // Durations: `0.52s call     tests/test_a.py::test_x`
+        if second == "call" {
+            if let (Some(seconds), Some(name)) = (first.strip_suffix('s'), words.next()) {
+                if let (Ok(seconds), true) = (seconds.parse::<f64>(), name.contains("::")) {
+                    collector.entry(name).duration_ms = Some((seconds * 1000.0).round() as u64);
+                }
+            }
+        }
+    }
+    collector.tests
+}
+
+/// jest/vitest `--json`: `testResults[].assertionResults[]`
+fn parse_jest(output: &str) -> Vec<TestCase> {
+    let Some(json) = json_document(output) else {
+        return Vec::new();
+    };
+    let Some(files) = json.get("testResults").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+
+    let mut tests = Vec::new();
+    for file in files {
+        let assertions = file
+            .get("assertionResults")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+
+        // A suite that failed to load has no assertions, only a message
+        if assertions.is_empty() && file.get("status").and_then(Value::as_str) == Some("failed") {
+            tests.push(TestCase {
+                name: file
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or("test suite")
+                    .to_string(),
+                status: TestStatus::Failed,
+                duration_ms: None,
+                message: file
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .and_then(message),
+            });
+            continue;
+        }
+
+        for assertion in assertions {
+            let name = assertion
+                .get("fullName")
+                .or_else(|| assertion.get("title"))
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let status = match assertion.get("status").and_then(Value::as_str) {
+                Some("passed") => TestStatus::Passed,
+                Some("failed") => TestStatus::Failed,
+                _ => TestStatus::Skipped,
+            };
+            let failures: Vec<&str> = assertion
+                .get("failureMessages")
+                .and_then(Value::as_array)
+                .map(|m| m.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            tests.push(TestCase {
+                name: name.to_string(),
+                status,
+                duration_ms: assertion
+                    .get("duration")
+                    .and_then(Value::as_f64)
+                    .map(|d| d.round() as u64),
+                message: message(&failures.join("\n")),
+            });
+        }
+    }
+    tests
+}
+
+/// The JSON object in an output that may have log lines before it
+fn json_document(output: &str) -> Option<Value> {
+    let start = output.find("\n{").map(|i| i + 1).unwrap_or(0);
+    let candidate = output[start..].trim();
+    if !candidate.starts_with('{') {
+        return None;
+    }
+    serde_json::from_str(candidate).ok()
+}
+
+/// `go test -json`: one event per line with `Action`, `Test`, `Elapsed` and `Output`
+fn parse_go_test(output: &str) -> Vec<TestCase> {
+    let mut collector = Collector::default();
+    let mut logs: HashMap<String, String> = HashMap::new();
+
+    for line in output.lines() {
+        let Ok(event) = serde_json::from_str::<Value>(line.trim()) else {
+            continue;
+        };
+        let (Some(action), Some(name)) = (
+            event.get("Action").and_then(Value::as_str),
+            event.get("Test").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+
+        let status = match action {
+            "output" => {
+                let text = event.get("Output").and_then(Value::as_str).unwrap_or("");
+                let trimmed = text.trim_start();
+                if !trimmed.starts_with("=== ") && !trimmed.starts_with("--- ") {
+                    logs.entry(name.to_string()).or_default().push_str(text);
+                }
+                continue;
+            }
+            "pass" => TestStatus::Passed,
+            "fail" => TestStatus::Failed,
+            "skip" => TestStatus::Skipped,
+            _ => continue,
+        };
+        let test = collector.set(name, status);
+        test.duration_ms = event
+            .get("Elapsed")
+            .and_then(Value::as_f64)
+            .map(|s| (s * 1000.0).round() as u64);
+        if status == TestStatus::Failed {
+            test.message = logs.get(name).and_then(|l| message(l));
+        }
+    }
+    collector.tests
+}
+
+/// JUnit XML `<testcase>` elements with `<failure>`, `<error>` or `<skipped>` children
+fn parse_junit(output: &str) -> Vec<TestCase> {
+    let Some(start) = output.find("<?xml").or_else(|| output.find("<testsuite")) else {
+        return Vec::new();
+    };
+    let mut reader = Reader::from_str(&output[start..]);
+
+    let mut tests = Vec::new();
+    let mut current: Option<TestCase> = None;
+    let mut in_failure = false;
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                let attr = |name: &str| {
+                    e.try_get_attribute(name)
+                        .ok()
+                        .flatten()
+                        .and_then(|a| a.unescape_value().ok().map(|v| v.to_string()))
+                };
+                match e.name().as_ref() {
+                    b"testcase" => {
+                        if let Some(test) = current.take() {
+                            tests.push(test);
+                        }
+                        let name = attr("name").unwrap_or_default();
+                        let name = match attr("classname").filter(|c| !c.is_empty()) {
+                            Some(class) => format!("{}.{}", class, name),
+                            None => name,
+                        };
+                        current = Some(TestCase {
+                            name,
+                            status: TestStatus::Passed,
+                            duration_ms: attr("time")
+                                .and_then(|t| t.parse::<f64>().ok())
+                                .map(|s| (s * 1000.0).round() as u64),
+                            message: None,
+                        });
+                    }
+                    b"failure" | b"error" => {
+                        if let Some(test) = current.as_mut() {
+                            test.status = TestStatus::Failed;
+                            test.message = attr("message").and_then(|m| message(&m));
+                            in_failure = true;
+                            text.clear();
+                        }
+                    }
+                    b"skipped" => {
+                        if let Some(test) = current.as_mut() {
+                            test.status = TestStatus::Skipped;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(e)) if in_failure => text.push_str(&e.decode().unwrap_or_default()),
+            Ok(Event::CData(e)) if in_failure => text.push_str(&e.decode().unwrap_or_default()),
+            Ok(Event::End(e)) => match e.name().as_ref() {
+                b"failure" | b"error" => {
+                    if let Some(test) = current.as_mut() {
+                        // The body usually has the full assertion and stack trace
+                        if let Some(body) = message(&text) {
+                            test.message = Some(match test.message.take() {
+                                Some(m) if !body.contains(&m) => format!("{}\n{}", m, body),
+                                _ => body,
+                            });
+                        }
+                    }
+                    in_failure = false;
+                }
+                b"testcase" => {
+                    if let Some(test) = current.take() {
+                        tests.push(test);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    if let Some(test) = current.take() {
+        tests.push(test);
+    }
+    tests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statuses(report: &TestReport) -> Vec<(&str, TestStatus)> {
+        report
+            .tests
+            .iter()
+            .map(|t| (t.name.as_str(), t.status))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_cargo_test() {
+        let output = "\
+running 3 tests
+test auth::tests::login_ok ... ok
+test auth::tests::slow ... ignored
+test auth::tests::login_bad_password ... FAILED
+
+failures:
+
+---- auth::tests::login_bad_password stdout ----
+thread 'auth::tests::login_bad_password' panicked at src/auth.rs:42:9:
+assertion `left == right` failed
+  left: 200
+ right: 401
+
+failures:
+    auth::tests::login_bad_password
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out
+";
+        let report = parse(output, "").unwrap();
+        assert_eq!(report.format, ReportFormat::Cargo);
+        assert_eq!(
+            statuses(&report),
+            vec![
+                ("auth::tests::login_ok", TestStatus::Passed),
+                ("auth::tests::slow", TestStatus::Skipped),
+                ("auth::tests::login_bad_password", TestStatus::Failed),
+            ]
+        );
+        let message = report.tests[2].message.as_deref().unwrap();
+        assert!(message.contains("left: 200"));
+        assert!(!message.contains("failures:"));
+    }
+
+    #[test]
+    fn test_parse_pytest() {
+        let output = "\
+tests/test_auth.py::test_login PASSED                                    [ 50%]
+tests/test_auth.py::test_logout FAILED                                   [100%]
+============================= slowest durations ==============================
+0.52s call     tests/test_auth.py::test_logout
+=========================== short test summary info ============================
+FAILED tests/test_auth.py::test_logout - AssertionError: assert 302 == 200
+";
+        let report = parse(output, "").unwrap();
+        assert_eq!(report.format, ReportFormat::Pytest);
+        assert_eq!(
+            statuses(&report),
+            vec![
+                ("tests/test_auth.py::test_login", TestStatus::Passed),
+                ("tests/test_auth.py::test_logout", TestStatus::Failed),
+            ]
+        );
+        assert_eq!(report.tests[1].duration_ms, Some(520));
+        assert_eq!(
+            report.tests[1].message.as_deref(),
+            Some("AssertionError: assert 302 == 200")
+        );
+    }
+
+    #[test]
+    fn test_parse_jest_json() {
+        let output = r#"Determining test suites to run...
+{"numTotalTests":2,"testResults":[{"name":"/app/login.test.ts","status":"failed","assertionResults":[
+ {"fullName":"login accepts valid users","status":"passed","duration":12,"failureMessages":[]},
+ {"fullName":"login rejects bad passwords","status":"failed","duration":30.4,
+  "failureMessages":["Error: expect(received).toBe(expected)\n\nExpected: 401\nReceived: 200"]}]}]}"#;
+        let report = parse(output, "").unwrap();
+        assert_eq!(report.format, ReportFormat::Jest);
+        assert_eq!(
+            statuses(&report),
+            vec![
+                ("login accepts valid users", TestStatus::Passed),
+                ("login rejects bad passwords", TestStatus::Failed),
+            ]
+        );
+        assert_eq!(report.tests[1].duration_ms, Some(30));
+        assert!(report.tests[1]
+            .message
+            .as_deref()
+            .unwrap()
+            .contains("Expected: 401"));
+    }
+
+    #[test]
+    fn test_parse_go_test_json() {
+        let output = r#"{"Action":"run","Package":"app/auth","Test":"TestLogin"}
+{"Action":"output","Package":"app/auth","Test":"TestLogin","Output":"=== RUN   TestLogin\n"}
+{"Action":"output","Package":"app/auth","Test":"TestLogin","Output":"    auth_test.go:12: got 200, want 401\n"}
+{"Action":"output","Package":"app/auth","Test":"TestLogin","Output":"--- FAIL: TestLogin (0.01s)\n"}
+{"Action":"fail","Package":"app/auth","Test":"TestLogin","Elapsed":0.01}
+{"Action":"pass","Package":"app/auth","Test":"TestLogout","Elapsed":0.002}
+{"Action":"fail","Package":"app/auth","Elapsed":0.02}"#;
+        let report = parse(output, "").unwrap();
+        assert_eq!(report.format, ReportFormat::GoTest);
+        assert_eq!(
+            statuses(&report),
+            vec![
+                ("TestLogin", TestStatus::Failed),
+                ("TestLogout", TestStatus::Passed),
+            ]
+        );
+        assert_eq!(
+            report.tests[0].message.as_deref(),
+            Some("auth_test.go:12: got 200, want 401")
+        );
+        assert_eq!(report.tests[0].duration_ms, Some(10));
+    }
+
+    #[test]
+    fn test_parse_junit_xml() {
+        let output = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="auth" tests="3">
+    <testcase classname="auth" name="login" time="0.25"/>
+    <testcase classname="auth" name="logout" time="0.1">
+      <failure message="expected 302">AssertionError: expected 302 but was 200
+    at logout.spec.ts:9</failure>
+    </testcase>
+    <testcase classname="auth" name="reset"><skipped/></testcase>
+  </testsuite>
+</testsuites>"#;
+        let report = parse(output, "").unwrap();
+        assert_eq!(report.format, ReportFormat::Junit);
+        assert_eq!(
+            statuses(&report),
+            vec![
+                ("auth.login", TestStatus::Passed),
+                ("auth.logout", TestStatus::Failed),
+                ("auth.reset", TestStatus::Skipped),
+            ]
+        );
+        assert_eq!(report.tests[0].duration_ms, Some(250));
+        let message = report.tests[1].message.as_deref().unwrap();
+        // The attribute is repeated in the body, so only the body is kept
+        assert!(message.starts_with("AssertionError: expected 302 but was 200"));
+        assert!(message.contains("logout.spec.ts:9"));
+    }
+
+    #[test]
+    fn test_unrecognized_output_and_summary() {
+        assert!(parse("Error: Cannot find module 'vitest'", "").is_none());
+
+        let report = parse("test a ... ok\ntest b ... FAILED\ntest c ... FAILED\n", "").unwrap();
+        let summary = report.failure_summary(1).unwrap();
+        assert!(summary.starts_with("2 of 3 tests failed (1 passed, cargo)"));
+        assert!(summary.contains("✗ b"));
+        assert!(summary.contains("and 1 more failed tests"));
+    }
+}
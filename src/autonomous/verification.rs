@@ -1,3 +1,429 @@
+//! Verification command execution and failure classification
+//!
+//! A feature is verified by a pipeline of steps (or its single
+//! `verification_command`). Each step is checked against the security policy,
+//! run with its own timeout, directory and environment, and judged by its
+//! expected exit code and output pattern. A failed pipeline can be re-run
+//! until a [`Quorum`] is decided, which is how flaky features are detected.
+//! Passing pipelines are cached by the git tree they ran against.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::autonomous::git;
+use crate::autonomous::security::{self, CommandOptions};
+use crate::autonomous::test_report::{self, TestReport};
+use crate::config::{PathsConfig, Quorum, SecurityConfig};
+use crate::db::features::VerificationStep;
+use crate::db::verification::NewVerificationRun;
+use crate::db::Database;
+
+/// Failed tests listed in a failure summary
+const SUMMARY_MAX_TESTS: usize = 10;
+
+/// Raw output kept when a failure can't be summarized per test
+const MAX_OUTPUT_CHARS: usize = 4000;
+
+/// A finished verification command and its parsed test results
+#[derive(Debug, Clone)]
+pub struct CommandRun {
+    /// Pipeline step the command ran for, if any
+    pub step: Option<String>,
+    pub command: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub duration: Duration,
+    pub stdout: String,
+    pub stderr: String,
+    /// Per-test results, if the output came from a known test reporter
+    pub report: Option<TestReport>,
+    /// Step expectation that wasn't met (exit code or output pattern)
+    pub unmet_expectation: Option<String>,
+}
+
+impl CommandRun {
+    /// Concise summary of the failed tests, if the reporter was recognized
+    pub fn failure_summary(&self) -> Option<String> {
+        self.report
+            .as_ref()
+            .and_then(|r| r.failure_summary(SUMMARY_MAX_TESTS))
+    }
+
+    /// What to show the agent about a failure
+    ///
+    /// The failed tests when the output could be parsed, otherwise the tail of
+    /// stdout and stderr (both, since many runners report failures on stdout).
+    pub fn error_message(&self) -> String {
+        let details = match self.failure_summary() {
+            Some(summary) => summary,
+            None => {
+                let parts: Vec<String> = [&self.stdout, &self.stderr]
+                    .into_iter()
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| tail(s, MAX_OUTPUT_CHARS / 2))
+                    .collect();
+                if parts.is_empty() {
+                    "Verification command failed with no output".to_string()
+                } else {
+                    parts.join("\n\n")
+                }
+            }
+        };
+        match &self.unmet_expectation {
+            Some(expectation) => format!("{}\n{}", expectation, details),
+            None => details,
+        }
+    }
+
+    /// Store the run and its tests in the `verification_runs` table
+    pub fn record(&self, db: &Database, feature_id: i64) -> Result<i64> {
+        let summary = (!self.success).then(|| self.error_message());
+        let tests = self
+            .report
+            .as_ref()
+            .map(|r| r.tests.as_slice())
+            .unwrap_or_default();
+        db.verification().record(&NewVerificationRun {
+            feature_id,
+            step: self.step.as_deref(),
+            command: &self.command,
+            passed: self.success,
+            exit_code: self.exit_code,
+            reporter: self.report.as_ref().map(|r| r.format.as_str()),
+            duration_ms: self.duration.as_millis() as u64,
+            summary: summary.as_deref(),
+            tests,
+        })
+    }
+}
+
+/// The steps of a verification pipeline that ran, up to the first failure
+#[derive(Debug, Clone, Default)]
+pub struct PipelineRun {
+    pub runs: Vec<CommandRun>,
+}
+
+impl PipelineRun {
+    /// Whether every step ran and passed
+    pub fn success(&self) -> bool {
+        !self.runs.is_empty() && self.runs.iter().all(|run| run.success)
+    }
+
+    /// Time spent running the steps
+    pub fn duration(&self) -> Duration {
+        self.runs.iter().map(|run| run.duration).sum()
+    }
+
+    /// The step that stopped the pipeline
+    pub fn failed_step(&self) -> Option<&CommandRun> {
+        self.runs.iter().find(|run| !run.success)
+    }
+
+    /// What to show the agent about a failure, naming the failed step
+    pub fn error_message(&self) -> String {
+        let Some(run) = self.failed_step() else {
+            return String::new();
+        };
+        match run.step.as_deref() {
+            Some(step) if step != VerificationStep::DEFAULT_NAME => format!(
+                "Step '{}' failed (`{}`):\n{}",
+                step,
+                run.command,
+                run.error_message()
+            ),
+            _ => run.error_message(),
+        }
+    }
+
+    /// Store every step's run in the `verification_runs` table
+    pub fn record(&self, db: &Database, feature_id: i64) -> Result<()> {
+        for run in &self.runs {
+            run.record(db, feature_id)?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of running a pipeline repeatedly under a quorum
+#[derive(Debug, Clone)]
+pub struct Verdict {
+    pub quorum: Quorum,
+    /// Every pipeline run, in order
+    pub runs: Vec<PipelineRun>,
+}
+
+impl Verdict {
+    /// Number of pipeline runs that passed
+    pub fn passes(&self) -> usize {
+        self.runs.iter().filter(|run| run.success()).count()
+    }
+
+    /// Whether the first run passed or, after a failure, enough runs passed
+    pub fn success(&self) -> bool {
+        self.runs.first().is_some_and(PipelineRun::success)
+            || self.passes() >= self.quorum.required.max(1) as usize
+    }
+
+    /// Whether the runs disagreed, i.e. the feature flaked
+    pub fn flaky(&self) -> bool {
+        let passes = self.passes();
+        passes > 0 && passes < self.runs.len()
+    }
+
+    /// Time spent on every run
+    pub fn duration(&self) -> Duration {
+        self.runs.iter().map(PipelineRun::duration).sum()
+    }
+
+    /// The most recent failed run, if any
+    pub fn last_failure(&self) -> Option<&PipelineRun> {
+        self.runs.iter().rev().find(|run| !run.success())
+    }
+
+    /// What to show the agent about a failure
+    pub fn error_message(&self) -> String {
+        let message = self
+            .last_failure()
+            .map(PipelineRun::error_message)
+            .unwrap_or_default();
+        if self.runs.len() > 1 {
+            format!(
+                "Passed {} of {} runs:\n{}",
+                self.passes(),
+                self.runs.len(),
+                message
+            )
+        } else {
+            message
+        }
+    }
+
+    /// Store every run and count the check towards the feature's flake rate
+    pub fn record(&self, db: &Database, feature_id: i64) -> Result<()> {
+        for run in &self.runs {
+            run.record(db, feature_id)?;
+        }
+        db.verification().record_check(feature_id, self.flaky())
+    }
+}
+
+/// Passing verification results keyed by the project's tree state
+///
+/// A pipeline that passed against an identical tree (tracked, modified and
+/// untracked files, minus forger's own state) is not run again. Only passes
+/// are cached: a failure is re-run so flakes and fixes are noticed.
+pub struct VerificationCache<'a> {
+    db: &'a Database,
+    tree_hash: String,
+}
+
+impl<'a> VerificationCache<'a> {
+    /// Cache for the current state of `root`, or `None` outside a git repository
+    pub fn open(db: &'a Database, root: &Path, paths: &PathsConfig) -> Option<Self> {
+        let database = format!("{}*", paths.database_file);
+        let mut excludes = vec![".forger"];
+        if Path::new(&paths.database_file).is_relative() {
+            excludes.push(&database);
+        }
+        let tree_hash = git::working_tree_hash(root, &excludes).ok().flatten()?;
+        Some(Self { db, tree_hash })
+    }
+
+    /// When the pipeline last passed against this tree, if it did
+    pub fn lookup(&self, steps: &[VerificationStep]) -> Result<Option<String>> {
+        self.db
+            .verification()
+            .cached_pass(&self.tree_hash, &pipeline_key(steps)?)
+    }
+
+    /// Remember that the pipeline passed against this tree
+    pub fn store(&self, steps: &[VerificationStep], feature_id: i64) -> Result<()> {
+        self.db
+            .verification()
+            .cache_pass(&self.tree_hash, &pipeline_key(steps)?, feature_id)
+    }
+}
+
+/// Cache key for a pipeline: every step setting that can change the outcome
+fn pipeline_key(steps: &[VerificationStep]) -> Result<String> {
+    serde_json::to_string(steps).context("Failed to serialize verification steps")
+}
+
+/// Directory verification runs from: the project root, which for a parallel
+/// worker is its worktree
+pub fn project_root() -> PathBuf {
+    crate::config::find_project_root()
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Reject a pipeline with a step blocked by the security policy, without running it
+pub fn ensure_pipeline_allowed(
+    steps: &[VerificationStep],
+    security_config: &SecurityConfig,
+) -> Result<()> {
+    for step in steps {
+        security::ensure_allowed(&step.command, security_config)
+            .map_err(|e| step_error(step, e))?;
+    }
+    Ok(())
+}
+
+/// Run a verification pipeline in order, stopping at the first failed step
+///
+/// Every step is checked against the security policy before the first one
+/// runs. Steps run from `root` or their `workdir` below it. Errors if a step
+/// is blocked, has an invalid output pattern or cannot be run; a step that
+/// times out fails like any other.
+pub fn run_pipeline(
+    steps: &[VerificationStep],
+    security_config: &SecurityConfig,
+    root: &Path,
+) -> Result<PipelineRun> {
+    ensure_pipeline_allowed(steps, security_config)?;
+
+    let mut pipeline = PipelineRun::default();
+    for step in steps {
+        let run = run_step(step, security_config, root).map_err(|e| step_error(step, e))?;
+        let passed = run.success;
+        pipeline.runs.push(run);
+        if !passed {
+            break;
+        }
+    }
+    Ok(pipeline)
+}
+
+/// Run a pipeline, re-running it under `quorum` if the first run fails
+///
+/// A first-run pass is accepted, so a stable feature runs once. After a
+/// failure, stops as soon as `required` runs have passed or too many have
+/// failed for that to still happen.
+pub fn run_quorum(
+    steps: &[VerificationStep],
+    security_config: &SecurityConfig,
+    root: &Path,
+    quorum: Quorum,
+) -> Result<Verdict> {
+    let runs = quorum.runs.max(1);
+    let required = quorum.required.clamp(1, runs);
+    let quorum = Quorum { required, runs };
+
+    let mut verdict = Verdict {
+        quorum,
+        runs: vec![run_pipeline(steps, security_config, root)?],
+    };
+    if verdict.success() {
+        return Ok(verdict);
+    }
+    let (mut passes, mut failures) = (0, 1);
+    while passes < required && failures <= runs - required {
+        let run = run_pipeline(steps, security_config, root)?;
+        if run.success() {
+            passes += 1;
+        } else {
+            failures += 1;
+        }
+        verdict.runs.push(run);
+    }
+    Ok(verdict)
+}
+
+/// Run a single pipeline step and check its expectations
+fn run_step(
+    step: &VerificationStep,
+    security_config: &SecurityConfig,
+    root: &Path,
+) -> Result<CommandRun> {
+    let pattern = step
+        .expect_output
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .context("Invalid expected output pattern")?;
+    let working_dir = match &step.workdir {
+        Some(dir) => root.join(dir),
+        None => root.to_path_buf(),
+    };
+
+    let mut run = execute(
+        &step.command,
+        security_config,
+        &CommandOptions {
+            working_dir: Some(&working_dir),
+            timeout: step.timeout_secs.map(Duration::from_secs),
+            env: Some(&step.env),
+        },
+    )?;
+    run.step = Some(step.name.clone());
+    run.success = run.exit_code == Some(step.expect_exit_code);
+
+    if !run.success && step.expect_exit_code != 0 && run.unmet_expectation.is_none() {
+        run.unmet_expectation = Some(format!(
+            "Expected exit code {}, got {}",
+            step.expect_exit_code,
+            run.exit_code
+                .map(|code| code.to_string())
+                .unwrap_or_else(|| "none (killed by a signal)".to_string())
+        ));
+    }
+    if let Some(pattern) = pattern.filter(|_| run.success) {
+        if !pattern.is_match(&run.stdout) && !pattern.is_match(&run.stderr) {
+            run.success = false;
+            run.unmet_expectation = Some(format!("Output did not match /{}/", pattern));
+        }
+    }
+    Ok(run)
+}
+
+/// Name the step in an error, unless it stands in for a bare verification command
+fn step_error(step: &VerificationStep, error: anyhow::Error) -> anyhow::Error {
+    if step.name == VerificationStep::DEFAULT_NAME {
+        error
+    } else {
+        anyhow::anyhow!("Step '{}': {}", step.name, error)
+    }
+}
+
+fn execute(
+    cmd: &str,
+    security_config: &SecurityConfig,
+    options: &CommandOptions,
+) -> Result<CommandRun> {
+    let started = Instant::now();
+    let output = security::run_verified_command(cmd, security_config, options)?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    Ok(CommandRun {
+        step: None,
+        command: cmd.to_string(),
+        success: output.status.is_some_and(|status| status.success()),
+        exit_code: output.status.and_then(|status| status.code()),
+        duration: started.elapsed(),
+        report: test_report::parse(&stdout, &stderr),
+        stdout,
+        stderr,
+        unmet_expectation: output
+            .status
+            .is_none()
+            .then(|| format!("Timed out after {}s", output.timeout.as_secs())),
+    })
+}
+
+/// Last `max_chars` characters of `text`
+fn tail(text: &str, max_chars: usize) -> String {
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().skip(count - max_chars).collect();
+    format!("... (truncated)\n{}", kept)
+}
+
 /// Types of verification failures - determines corrective action
 #[derive(Debug, PartialEq)]
 pub enum VerificationFailure {
@@ -26,6 +452,11 @@ impl VerificationFailure {
 pub fn classify_verification_failure(error: &str) -> VerificationFailure {
     let lower = error.to_lowercase();
 
+    // The step itself couldn't run (bad pattern, missing workdir, spawn failure)
+    if lower.starts_with("verification error") {
+        return VerificationFailure::CommandError;
+    }
+
     // Patterns that indicate the verification command is broken, not the code
     if lower.contains("no test files")
         || lower.contains("did not match any")
@@ -67,7 +498,7 @@ pub fn classify_verification_failure(error: &str) -> VerificationFailure {
 
 #[cfg(test)]
 mod tests {
-    use super::{classify_verification_failure, VerificationFailure};
+    use super::*;
 
     /// @description Ensures unrecognized CLI parameters are classified as command errors.
     #[test]
@@ -76,4 +507,174 @@ mod tests {
         let failure = classify_verification_failure(error);
         assert_eq!(failure, VerificationFailure::CommandError);
     }
+
+    #[test]
+    fn test_error_message_keeps_stdout_and_prefers_test_summary() {
+        let security = SecurityConfig::default();
+        let run_one = |cmd: &str| {
+            let steps = [VerificationStep::new(VerificationStep::DEFAULT_NAME, cmd)];
+            run_pipeline(&steps, &security, Path::new(".")).unwrap()
+        };
+
+        let pipeline = run_one("echo 'expected 401'; echo 'warning: slow' >&2; exit 1");
+        assert!(!pipeline.success());
+        assert_eq!(pipeline.runs[0].exit_code, Some(1));
+        let message = pipeline.error_message();
+        assert!(message.contains("expected 401") && message.contains("warning: slow"));
+
+        let pipeline = run_one("printf 'test a ... ok\\ntest b ... FAILED\\n'; exit 101");
+        assert!(pipeline.error_message().starts_with("1 of 2 tests failed"));
+    }
+
+    #[test]
+    fn test_pipeline_stops_at_first_failed_step() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join("web")).unwrap();
+        let security = SecurityConfig::default();
+
+        let mut build = VerificationStep::new("build", "test \"$MODE\" = release");
+        build.env.insert("MODE".to_string(), "release".to_string());
+        let mut lint = VerificationStep::new("lint", "pwd; echo '2 warnings'; exit 3");
+        lint.workdir = Some("web".to_string());
+        lint.expect_exit_code = 3;
+        lint.expect_output = Some("^0 warnings".to_string());
+        let unit = VerificationStep::new("unit", "true");
+
+        let pipeline = run_pipeline(&[build, lint.clone(), unit], &security, temp.path()).unwrap();
+        assert!(!pipeline.success());
+        assert_eq!(pipeline.runs.len(), 2);
+        assert!(pipeline.runs[0].success);
+        let failed = pipeline.failed_step().unwrap();
+        assert_eq!(failed.step.as_deref(), Some("lint"));
+        assert!(failed.stdout.trim_end().ends_with("2 warnings"));
+        assert!(failed.stdout.contains("/web"));
+        assert!(pipeline
+            .error_message()
+            .starts_with("Step 'lint' failed (`pwd; echo '2 warnings'; exit 3`):\nOutput did not match /^0 warnings/"));
+
+        lint.expect_output = None;
+        let pipeline = run_pipeline(&[lint], &security, temp.path()).unwrap();
+        assert!(pipeline.success());
+
+        // A step that runs past its timeout fails instead of erroring
+        let mut slow = VerificationStep::new("e2e", "sleep 5");
+        slow.timeout_secs = Some(1);
+        let pipeline = run_pipeline(&[slow], &security, temp.path()).unwrap();
+        assert!(!pipeline.success());
+        assert_eq!(pipeline.runs[0].exit_code, None);
+        assert!(pipeline
+            .error_message()
+            .starts_with("Step 'e2e' failed (`sleep 5`):\nTimed out after 1s"));
+
+        // A blocked step stops the pipeline before anything runs
+        let marker = temp.path().join("ran");
+        let steps = [
+            VerificationStep::new("build", &format!("touch {}", marker.display())),
+            VerificationStep::new("e2e", "rm -rf /"),
+        ];
+        let error = run_pipeline(&steps, &security, temp.path()).unwrap_err();
+        assert!(error.to_string().starts_with("Step 'e2e': "));
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn test_quorum_retries_flaky_pipeline() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let security = SecurityConfig::default();
+        // Fails on the first run only
+        let steps = [VerificationStep::new(
+            VerificationStep::DEFAULT_NAME,
+            "test -f ran && exit 0; touch ran; exit 1",
+        )];
+
+        let verdict = run_quorum(&steps, &security, temp.path(), Quorum::retries(2)).unwrap();
+        assert!(verdict.success() && verdict.flaky());
+        assert_eq!(verdict.runs.len(), 2);
+
+        // After a failure, runs continue until 2 of 3 passed
+        std::fs::remove_file(temp.path().join("ran")).unwrap();
+        let quorum = Quorum {
+            required: 2,
+            runs: 3,
+        };
+        let verdict = run_quorum(&steps, &security, temp.path(), quorum).unwrap();
+        assert!(verdict.success() && verdict.flaky());
+        assert_eq!(verdict.runs.len(), 3);
+        assert!(verdict.error_message().starts_with("Passed 2 of 3 runs:\n"));
+
+        // Stable failures stop once the quorum can no longer be reached
+        let failing = [VerificationStep::new(
+            VerificationStep::DEFAULT_NAME,
+            "exit 1",
+        )];
+        let verdict = run_quorum(&failing, &security, temp.path(), quorum).unwrap();
+        assert!(!verdict.success() && !verdict.flaky());
+        assert_eq!(verdict.runs.len(), 2);
+        assert!(verdict.error_message().starts_with("Passed 0 of 2 runs:\n"));
+    }
+
+    #[test]
+    fn test_quorum_accepts_a_first_run_pass() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let security = SecurityConfig::default();
+        let steps = [VerificationStep::new(
+            VerificationStep::DEFAULT_NAME,
+            "echo run >> runs.log",
+        )];
+        let quorum = Quorum {
+            required: 2,
+            runs: 3,
+        };
+
+        let verdict = run_quorum(&steps, &security, temp.path(), quorum).unwrap();
+        assert!(verdict.success() && !verdict.flaky());
+        assert_eq!(verdict.runs.len(), 1);
+        let log = std::fs::read_to_string(temp.path().join("runs.log")).unwrap();
+        assert_eq!(log.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_cache_is_keyed_by_tree_and_pipeline() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        std::fs::write(root.join("app.txt"), "v1").unwrap();
+        std::fs::write(root.join(".gitignore"), ".forger/\n").unwrap();
+        let db = Database::open(&root.join(".forger/progress.db")).unwrap();
+        let paths = PathsConfig::default();
+        let steps = [VerificationStep::new(
+            VerificationStep::DEFAULT_NAME,
+            "true",
+        )];
+        let other = [VerificationStep::new(
+            VerificationStep::DEFAULT_NAME,
+            "false",
+        )];
+
+        let cache = VerificationCache::open(&db, root, &paths).unwrap();
+        assert!(cache.lookup(&steps).unwrap().is_none());
+        cache.store(&steps, 1).unwrap();
+        assert!(cache.lookup(&steps).unwrap().is_some());
+        assert!(cache.lookup(&other).unwrap().is_none());
+
+        // Forger's own state doesn't count as a change
+        std::fs::write(root.join(".forger/notes"), "x").unwrap();
+        let cache = VerificationCache::open(&db, root, &paths).unwrap();
+        assert!(cache.lookup(&steps).unwrap().is_some());
+
+        std::fs::write(root.join("app.txt"), "v2").unwrap();
+        let cache = VerificationCache::open(&db, root, &paths).unwrap();
+        assert!(cache.lookup(&steps).unwrap().is_none());
+        // The real index is untouched
+        assert!(
+            String::from_utf8_lossy(&git(&["status", "--porcelain"]).stdout).contains("?? app.txt")
+        );
+    }
 }
@@ -4,74 +4,88 @@
 
 use anyhow::Result;
 use std::path::Path;
-use std::process::Output;
 
 use crate::conductor;
-use crate::config::{Config, SecurityConfig};
+use crate::config::{Config, FlakyConfig, Quorum, SecurityConfig};
 use crate::db::features::Feature;
 
 use super::features::FeatureProgress;
 use super::git;
-use super::security;
 use super::settings::LoopSettings;
+use super::verification::{self, Verdict};
 use super::webhook;
 use crate::common::logging as debug_logger;
 
 /// Result of verifying a feature
 pub enum VerificationResult {
-    /// Verification passed
-    Passed,
-    /// Verification failed with an error message and optional diff context
-    Failed { error_message: String },
+    /// Every verification step passed, possibly after re-runs
+    Passed { verdict: Verdict },
+    /// A step failed on every run; the message names it and summarizes the failed tests when possible
+    Failed {
+        error_message: String,
+        verdict: Verdict,
+    },
     /// No verification command was provided
     NoCommand,
-    /// Command was blocked by security policy
+    /// A step was blocked by security policy
     SecurityBlocked { reason: String },
+    /// A step could not be run: an invalid output pattern, a missing workdir or a spawn failure
+    CommandError { error_message: String },
 }
 
-/// Runs the verification command for a feature and returns the result.
+/// Runs the verification pipeline for a feature and returns the result.
 ///
 /// This function:
-/// 1. Validates the command against security policy.
-/// 2. Executes the command.
-/// 3. Classifies the output as pass/fail.
+/// 1. Validates every step against security policy.
+/// 2. Executes the steps in order, stopping at the first failure, and parses
+///    per-test results from their output.
+/// 3. Re-runs a failed pipeline up to `[flaky] retries` times.
+/// 4. Classifies the outcome as pass/fail.
 pub fn run_verification(
     feature: &Feature,
     security_config: &SecurityConfig,
+    flaky_config: &FlakyConfig,
 ) -> Result<VerificationResult> {
-    let Some(ref cmd) = feature.verification_command else {
+    let steps = feature.verification_pipeline();
+    if steps.is_empty() {
         return Ok(VerificationResult::NoCommand);
-    };
+    }
+
+    if let Err(e) = verification::ensure_pipeline_allowed(&steps, security_config) {
+        return Ok(VerificationResult::SecurityBlocked {
+            reason: e.to_string(),
+        });
+    }
 
     // Use security-validated command runner
-    let output = match security::run_verified_command(cmd, security_config, None) {
-        Ok(out) => out,
+    let verdict = match verification::run_quorum(
+        &steps,
+        security_config,
+        &verification::project_root(),
+        Quorum::retries(flaky_config.retries),
+    ) {
+        Ok(verdict) => verdict,
         Err(e) => {
-            return Ok(VerificationResult::SecurityBlocked {
-                reason: e.to_string(),
+            return Ok(VerificationResult::CommandError {
+                error_message: format!("Verification error: {}", e),
             });
         }
     };
 
-    if output.status.success() {
-        Ok(VerificationResult::Passed)
-    } else {
-        let error_message = extract_error_message(&output);
-        Ok(VerificationResult::Failed { error_message })
-    }
-}
-
-/// Extracts the error message from command output (preferring stderr).
-fn extract_error_message(output: &Output) -> String {
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let stdout = String::from_utf8_lossy(&output.stdout);
-
-    if !stderr.is_empty() {
-        stderr.to_string()
-    } else if !stdout.is_empty() {
-        stdout.to_string()
+    if verdict.success() {
+        if verdict.flaky() {
+            println!(
+                "  ⚠️ Verification passed on run {} of {} (flaky)",
+                verdict.runs.len(),
+                verdict.quorum.runs
+            );
+        }
+        Ok(VerificationResult::Passed { verdict })
     } else {
-        "Verification command failed with no output".to_string()
+        Ok(VerificationResult::Failed {
+            error_message: verdict.error_message(),
+            verdict,
+        })
     }
 }
 
@@ -93,15 +107,19 @@ pub fn handle_verification_success(
 
     // Mark as passing in the database
     let db = crate::db::Database::open(db_path)?;
-    db.features().mark_passing(&feature.description)?;
+    db.features().mark_passing(feature.db_id()?)?;
     println!("  ✓ Marked as passing in DB");
     logger.info(&format!(
         "Verification PASSED for '{}'",
         feature.description
     ));
 
-    // Mark in Conductor plan if active track matches
-    if let Some(track) = conductor::get_active_track(config)? {
+    // Mark in the feature's own Conductor plan, else in the active track
+    let track = match conductor::track_for_feature(config, &feature.key) {
+        Some(track) => Some(track),
+        None => conductor::get_active_track(config)?,
+    };
+    if let Some(track) = track {
         let plan_path = track.path.join("plan.md");
         if let Ok(tasks) = conductor::parse_plan(&plan_path) {
             if let Some(task) = conductor::get_next_task(&tasks) {
@@ -113,12 +131,15 @@ pub fn handle_verification_success(
 
     // Commit if needed
     if settings.auto_commit {
-        match git::commit_completed_feature(&feature.description, settings.verbose) {
-            Ok(_) => {
+        match git::commit_completed_feature(&feature.key, &feature.description, settings.verbose) {
+            Ok(commit) => {
                 logger.info(&format!(
                     "Auto-committed changes for '{}'",
                     feature.description
                 ));
+                if let (Some(id), Some(commit)) = (feature.id, commit) {
+                    db.history().attach_commit(id, &commit)?;
+                }
             }
             Err(e) => {
                 logger.error(&format!(
@@ -156,7 +177,7 @@ pub fn handle_verification_failure(
     println!("  ❌ Verification FAILED");
     println!(
         "     Command: {}",
-        feature.verification_command.as_deref().unwrap_or("N/A")
+        feature.verification_summary().as_deref().unwrap_or("N/A")
     );
     println!("     Error: {}", error_message.lines().next().unwrap_or(""));
 
@@ -203,7 +224,7 @@ pub fn handle_verification_failure(
     // Mark as failing with error context for auto-fix
     let db = crate::db::Database::open(db_path)?;
     db.features()
-        .mark_failing_with_error(&feature.description, Some(&final_error_msg))?;
+        .mark_failing_with_error(feature.db_id()?, Some(&final_error_msg))?;
 
     println!("  → Feature marked as failing (will auto-fix next iteration with failure diff)");
     logger.info(&format!(
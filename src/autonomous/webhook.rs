@@ -5,7 +5,10 @@ use std::path::Path;
 use std::process::Command;
 
 use crate::config::Config;
-use crate::db::{features::Feature, Database};
+use crate::db::{
+    features::{Feature, FeatureStatus},
+    Database,
+};
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Failure Notification Types
@@ -307,18 +310,27 @@ fn notify_with_sender<S: WebhookSender>(
 
     println!("→ Updating webhook dashboard for: {}", feature.description);
 
-    // Try to load existing message ID
+    // Try to load existing message ID and the per-state feature counts
     let db_path = Path::new(db_path_str);
-    let message_id = if db_path.exists() {
+    let (message_id, statuses) = if db_path.exists() {
         match Database::open(db_path) {
-            Ok(db) => db.meta().get("discord_message_id").unwrap_or(None),
-            Err(_) => None,
+            Ok(db) => (
+                db.meta().get("discord_message_id").unwrap_or(None),
+                db.features().count_by_status().unwrap_or_default(),
+            ),
+            Err(_) => (None, Vec::new()),
         }
     } else {
-        None
+        (None, Vec::new())
     };
 
-    let payload = build_webhook_payload(feature, session_number, current_passing, total_features)?;
+    let payload = build_webhook_payload(
+        feature,
+        session_number,
+        current_passing,
+        total_features,
+        &statuses,
+    )?;
 
     match message_id {
         Some(id) => {
@@ -391,6 +403,7 @@ fn build_webhook_payload(
     session_number: usize,
     current_passing: usize,
     total_features: usize,
+    statuses: &[(FeatureStatus, usize)],
 ) -> Result<String> {
     let project_name = std::env::current_dir()?
         .file_name()
@@ -417,6 +430,7 @@ fn build_webhook_payload(
     // malformed JSON from special characters like newlines, quotes, backslashes
     let mut data = std::collections::HashMap::new();
     data.insert("feature_name", escape_json_string(&feature.description));
+    data.insert("feature_key", escape_json_string(&feature.key));
     data.insert(
         "feature_category",
         escape_json_string(&capitalize_first(&feature.category)),
@@ -432,6 +446,7 @@ fn build_webhook_payload(
     data.insert("progress_total", total_features.to_string());
     data.insert("progress_percent", progress_percent.to_string());
     data.insert("progress_bar", progress_bar);
+    data.insert("status_breakdown", format_status_breakdown(statuses));
 
     // Render template
     let handlebars = handlebars::Handlebars::new();
@@ -444,6 +459,23 @@ fn build_webhook_payload(
         .context("Failed to render webhook template")
 }
 
+/// One line per non-empty lifecycle state, e.g. `✗ failing: 2`
+///
+/// Lines are joined with an escaped newline since the value is placed inside a
+/// JSON string; backticks are avoided because handlebars HTML-escapes them.
+fn format_status_breakdown(statuses: &[(FeatureStatus, usize)]) -> String {
+    let lines: Vec<String> = statuses
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(status, count)| format!("{} {}: {}", status.symbol(), status, count))
+        .collect();
+    if lines.is_empty() {
+        "No features tracked".to_string()
+    } else {
+        lines.join("\\n")
+    }
+}
+
 fn extract_json_id(json: &str) -> Option<String> {
     // Parse JSON properly to extract the top-level "id" field
     // This is more robust than string matching and handles edge cases correctly
@@ -501,12 +533,10 @@ mod tests {
         // Setup Feature
         let feature = Feature {
             id: Some(1),
-            category: "test".to_string(),
-            description: "Test Loop".to_string(),
+            key: format!("feature-{}", 1),
             passes: true,
-            verification_command: None,
-            steps: vec![],
-            last_error: None,
+            status: FeatureStatus::Passing,
+            ..Feature::new("test", "Test Loop")
         };
 
         // Setup Mock Sender
@@ -532,4 +562,30 @@ mod tests {
         assert_eq!(*last_method.lock().unwrap(), "PATCH");
         assert!(last_url.lock().unwrap().ends_with("/messages/999999"));
     }
+
+    #[test]
+    fn test_payload_includes_status_breakdown() {
+        let feature = Feature {
+            id: Some(1),
+            key: format!("feature-{}", 1),
+            passes: true,
+            status: FeatureStatus::Passing,
+            ..Feature::new("test", "Test feature")
+        };
+        let statuses = vec![
+            (FeatureStatus::Pending, 0),
+            (FeatureStatus::Passing, 3),
+            (FeatureStatus::Failing, 2),
+        ];
+
+        let payload = build_webhook_payload(&feature, 1, 3, 5, &statuses).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        let fields = parsed["embeds"][0]["fields"].as_array().unwrap();
+        let states = fields
+            .iter()
+            .find(|f| f["name"].as_str().unwrap().contains("Feature States"))
+            .unwrap();
+
+        assert_eq!(states["value"], "✓ passing: 3\n✗ failing: 2");
+    }
 }
@@ -1,10 +1,17 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use iocraft::prelude::*;
 use std::path::PathBuf;
 
-use crate::cli::DbAction;
+use crate::autonomous::verification::{self, VerificationCache};
+use crate::cli::output::{self, OutputFormat};
+use crate::cli::{DbAction, FeatureAction};
 use crate::config::Config;
 use crate::db;
+use crate::db::backup;
+use crate::db::features::import::{DuplicatePolicy, ImportFormat};
+use crate::db::features::FeatureStatus;
+use crate::db::history::{ChangeSource, HistoryEntry};
+use crate::db::migrations;
 use crate::regression;
 
 /// Handles database subcommands including initialization, migration, export, and queries.
@@ -16,11 +23,12 @@ use crate::regression;
 /// # Arguments
 ///
 /// * `action` - The database action to perform, wrapped in `DbAction` enum.
+/// * `format` - How listings and query results are printed (`--format`).
 ///
 /// # Returns
 ///
 /// Result indicating success or containing an error from the database operation.
-pub fn handle_db(action: &DbAction) -> Result<()> {
+pub fn handle_db(action: &DbAction, format: OutputFormat) -> Result<()> {
     // Load config to get database_file path (from forger.toml)
     let config = Config::load(None).unwrap_or_default();
     let default_db_path = PathBuf::from(&config.paths.database_file);
@@ -67,6 +75,44 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
 
             Ok(())
         }
+        DbAction::Import {
+            file,
+            from,
+            on_duplicate,
+        } => {
+            let content = std::fs::read_to_string(file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let import_format = match from {
+                Some(f) => f.parse()?,
+                None => ImportFormat::detect(file, &content)?,
+            };
+            let policy: DuplicatePolicy = on_duplicate.parse()?;
+            let features = import_format
+                .parse(&content)
+                .with_context(|| format!("Failed to parse {}", file.display()))?;
+
+            println!(
+                "📥 Importing {} feature(s) from {} ({})",
+                features.len(),
+                file.display(),
+                import_format.as_str()
+            );
+            let db = db::Database::open(&default_db_path)?;
+            let summary = db
+                .features()
+                .with_source(ChangeSource::Cli)
+                .import(&features, policy)?;
+
+            println!(
+                "✅ {} added, {} merged, {} overwritten, {} skipped (duplicates: {})",
+                summary.added,
+                summary.merged,
+                summary.overwritten,
+                summary.skipped,
+                policy.as_str()
+            );
+            Ok(())
+        }
         DbAction::Export { output } => {
             let output_path = output
                 .clone()
@@ -89,6 +135,82 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
 
             Ok(())
         }
+        DbAction::Backup { output } => {
+            if !default_db_path.exists() {
+                anyhow::bail!(
+                    "Database not found: {}. Run 'db init' first.",
+                    default_db_path.display()
+                );
+            }
+            let db = db::Database::open(&default_db_path)?;
+            let path = match output {
+                Some(path) => {
+                    backup::backup(&db, path)?;
+                    path.clone()
+                }
+                None => {
+                    backup::take_snapshot(&db, &backup::snapshot_dir(&default_db_path), "manual")?
+                }
+            };
+            println!("✅ Backed up database to {}", path.display());
+            Ok(())
+        }
+        DbAction::Snapshots => {
+            let snapshots = backup::list_snapshots(&backup::snapshot_dir(&default_db_path))?;
+            if format != OutputFormat::Table {
+                return output::print_records(format, &snapshots);
+            }
+            if snapshots.is_empty() {
+                println!("No snapshots found.");
+                return Ok(());
+            }
+            for snapshot in &snapshots {
+                println!("{}  ({} KB)", snapshot.name, snapshot.size_bytes / 1024);
+            }
+            Ok(())
+        }
+        DbAction::Restore { snapshot, dry_run } => {
+            if !default_db_path.exists() {
+                anyhow::bail!(
+                    "Database not found: {}. Run 'db init' first.",
+                    default_db_path.display()
+                );
+            }
+            let snapshot = backup::resolve_snapshot(&default_db_path, snapshot)?;
+            let changes = {
+                let db = db::Database::open(&default_db_path)?;
+                backup::diff_statuses(&db, &snapshot)?
+            };
+
+            if format != OutputFormat::Table {
+                output::print_records(format, &changes)?;
+            } else if changes.is_empty() {
+                println!("No feature status changes.");
+            } else {
+                let status = |s: Option<FeatureStatus>| {
+                    s.map(|s| s.as_str().to_string())
+                        .unwrap_or_else(|| "(absent)".to_string())
+                };
+                println!("Feature status changes ({}):", changes.len());
+                for change in &changes {
+                    println!(
+                        "  #{} {}: {} → {}",
+                        change.id,
+                        change.description,
+                        status(change.current),
+                        status(change.snapshot)
+                    );
+                }
+            }
+
+            if *dry_run {
+                return Ok(());
+            }
+            let safety = backup::restore(&default_db_path, &snapshot)?;
+            eprintln!("✅ Restored {}", snapshot.display());
+            eprintln!("   Previous database saved to {}", safety.display());
+            Ok(())
+        }
         DbAction::Stats => {
             if !default_db_path.exists() {
                 anyhow::bail!(
@@ -106,6 +228,20 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
             // Session stats
             let session_stats = db.sessions().get_stats()?;
 
+            if format != OutputFormat::Table {
+                let mut stats = serde_json::Map::new();
+                stats.insert("total_features".into(), total.into());
+                stats.insert("passing".into(), passing.into());
+                stats.insert("remaining".into(), remaining.into());
+                for (status, count) in db.features().count_by_status()? {
+                    stats.insert(format!("status_{}", status.as_str()), count.into());
+                }
+                if let serde_json::Value::Object(sessions) = serde_json::to_value(&session_stats)? {
+                    stats.extend(sessions);
+                }
+                return output::print_records(format, &[stats]);
+            }
+
             // Render the component to stdout
             element!(crate::tui::stats::DbStatsView(
                 total: total,
@@ -125,9 +261,8 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
                 );
             }
             let db = db::Database::open(&default_db_path)?;
-            let output = db.read_query(sql)?;
-            print!("{}", output);
-            Ok(())
+            let result = db.query_rows(sql)?;
+            output::print_rows(format, &result.columns, &result.rows)
         }
         DbAction::Exec { sql } => {
             if !default_db_path.exists() {
@@ -141,15 +276,29 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
             // Auto-detect SELECT and redirect to read_query
             let trimmed = sql.trim().to_uppercase();
             if trimmed.starts_with("SELECT") || trimmed.starts_with("PRAGMA") {
-                let output = db.read_query(sql)?;
-                print!("{}", output);
+                let result = db.query_rows(sql)?;
+                output::print_rows(format, &result.columns, &result.rows)?;
+            } else if !config.security.allow_raw_sql {
+                anyhow::bail!(
+                    "Raw SQL writes are disabled. Use the typed commands instead \
+                     (see 'opencode-forger db feature --help', 'db mark-pass', 'db set-status'), \
+                     or set `allow_raw_sql = true` under [security] in forger.toml."
+                );
             } else {
                 let affected = db.write_query(sql)?;
                 println!("{} row(s) affected", affected);
             }
             Ok(())
         }
-        DbAction::Check { path: _ } => {
+        DbAction::Check {
+            path: _,
+            jobs,
+            isolate,
+            junit,
+            json,
+            since,
+            no_cache,
+        } => {
             if !default_db_path.exists() {
                 anyhow::bail!(
                     "Database not found: {}. Run 'db init' first.",
@@ -157,15 +306,52 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
                 );
             }
             let db = db::Database::open(&default_db_path)?;
-            let features = db.features().list_all()?;
+            let mut features = db.features().list_all()?;
+            let json = *json || format == OutputFormat::Json;
 
-            println!(
-                "🔍 Running regression check on {} feature(s)...",
-                features.len()
-            );
+            if let Some(commit) = since {
+                let total = features.len();
+                features = regression::features_changed_since(&db, features, commit)?;
+                if !json {
+                    println!(
+                        "📂 {} of {} feature(s) touch files changed since {}",
+                        features.len(),
+                        total,
+                        commit
+                    );
+                }
+            }
+            if !json {
+                println!(
+                    "🔍 Running regression check on {} feature(s)...",
+                    features.len()
+                );
+            }
 
-            let summary = regression::run_regression_check(&features, None, None, false, None)?;
-            regression::report_results(&summary);
+            let cache = if !*no_cache && config.agent.verification_cache {
+                VerificationCache::open(&db, &verification::project_root(), &config.paths)
+            } else {
+                None
+            };
+            let options = regression::CheckOptions {
+                verbose: !json && *jobs > 1,
+                jobs: *jobs,
+                isolate: *isolate,
+                cache: cache.as_ref(),
+                ..regression::CheckOptions::new(&config)
+            };
+            let summary = regression::run_regression_check(&features, &options)?;
+            regression::record_runs(&db, &summary)?;
+
+            if let Some(junit) = junit {
+                std::fs::write(junit, regression::junit_report(&summary))
+                    .with_context(|| format!("Failed to write {}", junit.display()))?;
+            }
+            if json {
+                println!("{}", regression::json_report(&summary)?);
+            } else {
+                regression::report_results(&summary);
+            }
 
             if summary.automated_failed > 0 {
                 std::process::exit(1);
@@ -198,6 +384,50 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
             print!("{}", schema);
             Ok(())
         }
+        DbAction::MigrateSchema { status } => {
+            if !default_db_path.exists() {
+                anyhow::bail!(
+                    "Database not found: {}. Run 'db init' first.",
+                    default_db_path.display()
+                );
+            }
+            let current = migrations::version_of(&default_db_path)?;
+            let latest = migrations::latest_version();
+            if *status {
+                println!("Schema version: {} (latest: {})", current, latest);
+                if current > latest {
+                    println!(
+                        "⚠️ Database was written by a newer opencode-forger; upgrade to open it."
+                    );
+                }
+                for migration in migrations::MIGRATIONS {
+                    let mark = if migration.version <= current {
+                        "✓"
+                    } else {
+                        "○"
+                    };
+                    println!(
+                        "  {} {:>3}  {}",
+                        mark, migration.version, migration.description
+                    );
+                }
+                return Ok(());
+            }
+
+            db::Database::open(&default_db_path)?;
+            if current == latest {
+                println!("✅ Schema is up to date (version {})", latest);
+            } else {
+                for migration in migrations::MIGRATIONS
+                    .iter()
+                    .filter(|m| m.version > current)
+                {
+                    println!("  ✓ {:>3}  {}", migration.version, migration.description);
+                }
+                println!("✅ Migrated schema from version {} to {}", current, latest);
+            }
+            Ok(())
+        }
         DbAction::NextFeature => {
             if !default_db_path.exists() {
                 anyhow::bail!(
@@ -206,8 +436,12 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
                 );
             }
             let db = db::Database::open(&default_db_path)?;
-            let next =
-                db.read_query("SELECT id, description FROM features WHERE passes = 0 LIMIT 1")?;
+            // Same pick as the supervisor: actionable status, then priority and id
+            let next_id = db.features().list_actionable()?.first().and_then(|f| f.id);
+            let next = db.read_query(&format!(
+                "SELECT id, description, status FROM features WHERE id = {}",
+                next_id.unwrap_or(-1)
+            ))?;
             print!("{}", next);
             Ok(())
         }
@@ -219,15 +453,171 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
                 );
             }
             let db = db::Database::open(&default_db_path)?;
-            let affected =
-                db.write_query(&format!("UPDATE features SET passes = 1 WHERE id = {}", id))?;
-            if affected > 0 {
+            let marked = db
+                .features()
+                .with_source(ChangeSource::Cli)
+                .set_status(i64::from(*id), FeatureStatus::Passing)?;
+            if marked {
                 println!("Feature {} marked as passing", id);
             } else {
                 println!("No feature found with id {}", id);
             }
             Ok(())
         }
+        DbAction::Feature { action } => {
+            if !default_db_path.exists() {
+                anyhow::bail!(
+                    "Database not found: {}. Run 'db init' first.",
+                    default_db_path.display()
+                );
+            }
+            let db = db::Database::open(&default_db_path)?;
+            let repo = db.features().with_source(ChangeSource::Cli);
+            let not_found = |id: &i64| anyhow::anyhow!("No feature found with id {}", id);
+
+            match action {
+                FeatureAction::Add {
+                    category,
+                    description,
+                    verification_command,
+                    priority,
+                    steps,
+                } => {
+                    if repo.exists_by_description(description)? {
+                        println!("Feature already exists, skipped: {}", description);
+                        return Ok(());
+                    }
+                    let id = repo.insert(&db::features::Feature {
+                        steps: steps.clone(),
+                        priority: priority.parse()?,
+                        verification_command: verification_command.clone(),
+                        ..db::features::Feature::new(category, description)
+                    })?;
+                    println!("✅ Added feature {}: {}", id, description);
+                }
+                FeatureAction::SetSteps { id, steps } => {
+                    if !repo.replace_steps(*id, steps)? {
+                        return Err(not_found(id));
+                    }
+                    println!("Feature {} now has {} step(s)", id, steps.len());
+                }
+                FeatureAction::SetVerify {
+                    id,
+                    command,
+                    timeout_secs,
+                } => {
+                    if !repo.set_verification_command(*id, command)? {
+                        return Err(not_found(id));
+                    }
+                    if let Some(secs) = timeout_secs {
+                        repo.set_verification_timeout(*id, Some(*secs).filter(|s| *s > 0))?;
+                    }
+                    println!("Feature {} is now verified by: {}", id, command);
+                }
+                FeatureAction::AddVerifyStep {
+                    feature,
+                    name,
+                    command,
+                    timeout_secs,
+                    workdir,
+                    env,
+                    expect_exit_code,
+                    expect_output,
+                } => {
+                    let found = find_feature(&repo, feature)?;
+                    let id = found.db_id()?;
+                    if found.verification_steps.iter().any(|s| &s.name == name) {
+                        anyhow::bail!("Feature {} already has a step named '{}'", id, name);
+                    }
+                    // Reject what would only fail at verification time
+                    crate::autonomous::security::ensure_allowed(command, &config.security)?;
+
+                    let mut step = db::features::VerificationStep::new(name, command);
+                    step.timeout_secs = *timeout_secs;
+                    step.workdir = workdir.clone();
+                    step.expect_exit_code = *expect_exit_code;
+                    step.expect_output = expect_output.clone();
+                    for pair in env {
+                        let (key, value) = pair.split_once('=').ok_or_else(|| {
+                            anyhow::anyhow!("Invalid --env '{}', expected KEY=VALUE", pair)
+                        })?;
+                        step.env.insert(key.to_string(), value.to_string());
+                    }
+                    step.validate()?;
+
+                    let mut steps = found.verification_steps;
+                    steps.push(step);
+                    repo.set_verification_steps(id, &steps)?;
+                    println!("Feature {} is now verified by {} step(s):", id, steps.len());
+                    for (index, step) in steps.iter().enumerate() {
+                        println!("  {}. {}: {}", index + 1, step.name, step.command);
+                    }
+                }
+                FeatureAction::ClearVerifySteps { feature } => {
+                    let found = find_feature(&repo, feature)?;
+                    repo.set_verification_steps(found.db_id()?, &[])?;
+                    println!(
+                        "Feature {} is verified by: {}",
+                        found.db_id()?,
+                        found.verification_command.as_deref().unwrap_or("(nothing)")
+                    );
+                }
+                FeatureAction::Fail { id, error } => {
+                    if !repo.mark_failing_with_error(*id, Some(error))? {
+                        return Err(not_found(id));
+                    }
+                    println!("Feature {} marked as failing", id);
+                }
+                FeatureAction::SetCategory { id, category } => {
+                    if !repo.set_category(*id, category)? {
+                        return Err(not_found(id));
+                    }
+                    println!("Feature {} moved to category {}", id, category);
+                }
+                FeatureAction::Edit {
+                    feature,
+                    description,
+                    category,
+                    priority,
+                    verification_command,
+                    verification_timeout_secs,
+                } => {
+                    let found = find_feature(&repo, feature)?;
+                    let id = found.db_id()?;
+
+                    if description.is_none()
+                        && category.is_none()
+                        && priority.is_none()
+                        && verification_command.is_none()
+                        && verification_timeout_secs.is_none()
+                    {
+                        anyhow::bail!(
+                            "Nothing to edit; pass --description, --category, --priority, --verify or --verify-timeout"
+                        );
+                    }
+                    // Parse first so a bad value leaves the feature untouched
+                    let priority = priority.as_deref().map(str::parse).transpose()?;
+
+                    if let Some(description) = description {
+                        repo.set_description(id, description)?;
+                    }
+                    if let Some(category) = category {
+                        repo.set_category(id, category)?;
+                    }
+                    if let Some(priority) = priority {
+                        repo.set_priority(id, priority)?;
+                    }
+                    if let Some(command) = verification_command {
+                        repo.set_verification_command(id, command)?;
+                    }
+                    if let Some(secs) = verification_timeout_secs {
+                        repo.set_verification_timeout(id, Some(*secs).filter(|s| *s > 0))?;
+                    }
+                    println!("Feature {} ({}) updated", id, found.key);
+                }
+            }
+            Ok(())
+        }
         DbAction::Knowledge { action } => {
             let db = db::Database::open(&default_db_path)?;
             let repo = db.knowledge();
@@ -238,9 +628,15 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
                     value,
                     category,
                     description,
+                    feature,
+                    ttl,
                 } => {
                     let cat = category.as_deref().unwrap_or("general");
-                    repo.set(key, value, cat, description.as_deref())?;
+                    let scope = db::knowledge::FactScope {
+                        feature_id: feature_scope(&db, feature.as_deref())?,
+                        ttl: ttl.as_deref().map(db::knowledge::parse_ttl).transpose()?,
+                    };
+                    repo.set_scoped(key, value, cat, description.as_deref(), scope)?;
                     println!("✅ Fact saved: {} = {}", key, value);
                 }
                 crate::cli::KnowledgeAction::Get { key } => {
@@ -249,37 +645,55 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
                         if let Some(desc) = fact.description {
                             println!("# {}", desc);
                         }
+                        if let Some(expires) = fact.expires_at {
+                            println!("# expires {} UTC", expires);
+                        }
                     } else {
                         println!("Fact '{}' not found.", key);
                     }
                 }
-                crate::cli::KnowledgeAction::List { category } => {
-                    let facts = repo.list(category.as_deref())?;
-                    if facts.is_empty() {
-                        println!("No facts found.");
-                    } else {
-                        for fact in facts {
-                            println!("[{}] {} = {}", fact.category, fact.key, fact.value);
-                        }
-                    }
+                crate::cli::KnowledgeAction::List { category, feature } => {
+                    let feature_id = feature_scope(&db, feature.as_deref())?;
+                    let facts = repo.list(category.as_deref(), feature_id)?;
+                    print_facts(format, &facts)?;
+                }
+                crate::cli::KnowledgeAction::Search { terms, feature } => {
+                    let feature_id = feature_scope(&db, feature.as_deref())?;
+                    let facts = repo.search(&terms.join(" "), feature_id)?;
+                    print_facts(format, &facts)?;
                 }
                 crate::cli::KnowledgeAction::Delete { key } => {
                     repo.delete(key)?;
                     println!("🗑️ Fact '{}' deleted.", key);
                 }
-                crate::cli::KnowledgeAction::TrackServer { port, pid } => {
-                    repo.track_server(*port, *pid)?;
+                crate::cli::KnowledgeAction::Pin { key } => {
+                    if !repo.set_pinned(key, true)? {
+                        anyhow::bail!("Fact '{}' not found", key);
+                    }
+                    println!("📌 Fact '{}' pinned.", key);
+                }
+                crate::cli::KnowledgeAction::Unpin { key } => {
+                    if !repo.set_pinned(key, false)? {
+                        anyhow::bail!("Fact '{}' not found", key);
+                    }
+                    println!("Fact '{}' unpinned.", key);
+                }
+                crate::cli::KnowledgeAction::TrackServer { port, pid, worker } => {
+                    let worker = worker.or_else(worker_id_from_env);
+                    repo.track_server(*port, *pid, worker)?;
                     println!("✅ Tracking server on port {} (PID: {})", port, pid);
                 }
-                crate::cli::KnowledgeAction::GetServer { port } => {
-                    if let Some(pid) = repo.get_tracked_server(*port)? {
+                crate::cli::KnowledgeAction::GetServer { port, worker } => {
+                    let worker = worker.or_else(worker_id_from_env);
+                    if let Some(pid) = repo.get_tracked_server(*port, worker)? {
                         println!("port={}  pid={}", port, pid);
                     } else {
                         println!("No server tracked on port {}", port);
                     }
                 }
-                crate::cli::KnowledgeAction::UntrackServer { port } => {
-                    repo.untrack_server(*port)?;
+                crate::cli::KnowledgeAction::UntrackServer { port, worker } => {
+                    let worker = worker.or_else(worker_id_from_env);
+                    repo.untrack_server(*port, worker)?;
                     println!("🗑️ Untracked server on port {}", port);
                 }
             }
@@ -296,10 +710,214 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
             println!("✅ Project marked as initialized in database.");
             Ok(())
         }
+        DbAction::Sessions => {
+            if !default_db_path.exists() {
+                anyhow::bail!(
+                    "Database not found: {}. Run 'db init' first.",
+                    default_db_path.display()
+                );
+            }
+            let db = db::Database::open(&default_db_path)?;
+            let sessions = db.sessions().list_sessions()?;
+            if format == OutputFormat::Table && sessions.is_empty() {
+                println!("No sessions recorded.");
+                return Ok(());
+            }
+            output::print_records(format, &sessions)
+        }
+        DbAction::Instances { all } => {
+            if format == OutputFormat::Table {
+                return super::ps::handle_ps(*all);
+            }
+            let mut instances = db::InstanceRepository::open()?.list(!all)?;
+            instances.sort_by_key(|i| i.id);
+            output::print_records(format, &instances)
+        }
+        DbAction::History { id } => {
+            if !default_db_path.exists() {
+                anyhow::bail!(
+                    "Database not found: {}. Run 'db init' first.",
+                    default_db_path.display()
+                );
+            }
+            let db = db::Database::open(&default_db_path)?;
+            let Some(feature) = db.features().get(*id)? else {
+                anyhow::bail!("No feature found with id {}", id);
+            };
+            if format != OutputFormat::Table {
+                return output::print_records(format, &db.history().list_for_feature(*id)?);
+            }
+            println!(
+                "Feature #{}: {} ({})",
+                id, feature.description, feature.status
+            );
+
+            let history = db.history().list_for_feature(*id)?;
+            if history.is_empty() {
+                println!("No status changes recorded.");
+                return Ok(());
+            }
+            print_history(&history);
+            Ok(())
+        }
+        DbAction::Runs { feature, limit } => {
+            if !default_db_path.exists() {
+                anyhow::bail!(
+                    "Database not found: {}. Run 'db init' first.",
+                    default_db_path.display()
+                );
+            }
+            let db = db::Database::open(&default_db_path)?;
+            let feature_id = feature_scope(&db, feature.as_deref())?;
+            let runs = db.verification().list(feature_id, *limit)?;
+            if format == OutputFormat::Table && runs.is_empty() {
+                println!("No verification runs recorded.");
+                return Ok(());
+            }
+            output::print_records(format, &runs)
+        }
+        DbAction::Run { id } => {
+            if !default_db_path.exists() {
+                anyhow::bail!(
+                    "Database not found: {}. Run 'db init' first.",
+                    default_db_path.display()
+                );
+            }
+            let db = db::Database::open(&default_db_path)?;
+            let tests = db.verification().tests(*id)?;
+            if format != OutputFormat::Table {
+                return output::print_records(format, &tests);
+            }
+            let Some(run) = db.verification().get(*id)? else {
+                anyhow::bail!("No verification run with id {}", id);
+            };
+
+            println!(
+                "Run #{} of feature #{}: {} ({}, {} ms)",
+                run.id,
+                run.feature_id,
+                if run.passed { "passed" } else { "failed" },
+                run.created_at,
+                run.duration_ms
+            );
+            match &run.step {
+                Some(step) => println!("Step {}: {}", step, run.command),
+                None => println!("Command: {}", run.command),
+            }
+            if tests.is_empty() {
+                match &run.summary {
+                    Some(summary) => println!("\n{}", summary),
+                    None => println!("No per-test results (output not recognized)."),
+                }
+                return Ok(());
+            }
+            for test in &tests {
+                let mark = match test.status {
+                    db::verification::TestStatus::Passed => "✓",
+                    db::verification::TestStatus::Failed => "✗",
+                    db::verification::TestStatus::Skipped => "○",
+                };
+                let duration = test
+                    .duration_ms
+                    .map(|d| format!(" ({} ms)", d))
+                    .unwrap_or_default();
+                println!("  {} {}{}", mark, test.name, duration);
+                if let Some(message) = &test.message {
+                    for line in message.lines().take(6) {
+                        println!("      {}", line);
+                    }
+                }
+            }
+            Ok(())
+        }
+        DbAction::Flaky { all } => {
+            if !default_db_path.exists() {
+                anyhow::bail!(
+                    "Database not found: {}. Run 'db init' first.",
+                    default_db_path.display()
+                );
+            }
+            let db = db::Database::open(&default_db_path)?;
+            let (threshold, min_checks) = (config.flaky.threshold, config.flaky.min_checks);
+            let stats: Vec<_> = db
+                .verification()
+                .flake_stats()?
+                .into_iter()
+                .filter(|s| *all || s.is_flaky(threshold, min_checks))
+                .collect();
+            if format != OutputFormat::Table {
+                return output::print_records(format, &stats);
+            }
+            if stats.is_empty() {
+                println!(
+                    "No flaky features (threshold {:.0}% over at least {} checks).",
+                    threshold * 100.0,
+                    min_checks
+                );
+                return Ok(());
+            }
+
+            let columns: Vec<String> = [
+                "ID",
+                "KEY",
+                "CATEGORY",
+                "CHECKS",
+                "FLAKY",
+                "RATE",
+                "LAST FLAKE",
+                "DESCRIPTION",
+            ]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+            let rows: Vec<Vec<String>> = stats
+                .iter()
+                .map(|s| {
+                    let label = if s.is_flaky(threshold, min_checks) {
+                        " ⚠️"
+                    } else {
+                        ""
+                    };
+                    vec![
+                        s.feature_id.to_string(),
+                        s.key.clone(),
+                        s.category.clone(),
+                        s.checks.to_string(),
+                        s.flaky_checks.to_string(),
+                        format!("{:.0}%{}", s.rate() * 100.0, label),
+                        s.last_flake_at.clone().unwrap_or_else(|| "-".to_string()),
+                        s.description.clone(),
+                    ]
+                })
+                .collect();
+            print!("{}", db::query::format_table(&columns, &rows));
+            Ok(())
+        }
+        DbAction::SetStatus { id, status } => {
+            if !default_db_path.exists() {
+                anyhow::bail!(
+                    "Database not found: {}. Run 'db init' first.",
+                    default_db_path.display()
+                );
+            }
+            let status: FeatureStatus = status.parse()?;
+            let db = db::Database::open(&default_db_path)?;
+            if db
+                .features()
+                .with_source(ChangeSource::Cli)
+                .set_status(*id, status)?
+            {
+                println!("Feature {} is now {}", id, status);
+            } else {
+                println!("No feature found with id {}", id);
+            }
+            Ok(())
+        }
         DbAction::List {
             all,
             passing,
             remaining,
+            status,
         } => {
             if !default_db_path.exists() {
                 anyhow::bail!(
@@ -308,25 +926,157 @@ pub fn handle_db(action: &DbAction) -> Result<()> {
                 );
             }
             let db = db::Database::open(&default_db_path)?;
-            let features = match (all, passing, remaining) {
-                (true, _, _) => db.features().list_all()?,
-                (_, true, _) => db.features().list_passing()?,
-                (_, _, true) => db.features().list_remaining()?,
+            let features = match (all, passing, remaining, status) {
+                (_, _, _, Some(status)) => db.features().list_by_status(status.parse()?)?,
+                (true, _, _, _) => db.features().list_all()?,
+                (_, true, _, _) => db.features().list_passing()?,
+                (_, _, true, _) => db.features().list_remaining()?,
                 _ => db.features().list_passing()?,
             };
 
+            if format != OutputFormat::Table {
+                return output::print_records(format, &features);
+            }
             if features.is_empty() {
                 println!("No features found.");
                 return Ok(());
             }
 
-            println!("id | description                    | status");
-            println!("---|--------------------------------|--------");
+            println!("id | key                  | description                    | status");
+            println!("---|----------------------|--------------------------------|--------");
             for f in &features {
-                let status = if f.passes { "✓" } else { "○" };
-                println!("{} | {} | {}", f.id.unwrap_or(0), f.description, status);
+                println!(
+                    "{} | {} | {} | {} {}",
+                    f.id.unwrap_or(0),
+                    f.key,
+                    f.description,
+                    f.status.symbol(),
+                    f.status
+                );
             }
             Ok(())
         }
     }
 }
+
+/// Print a feature's status timeline, pointing out the commit range of a regression
+fn print_history(history: &[HistoryEntry]) {
+    let short = |commit: &Option<String>| {
+        commit
+            .as_deref()
+            .map(|c| c.chars().take(8).collect::<String>())
+            .unwrap_or_else(|| "-".to_string())
+    };
+
+    println!();
+    println!(
+        "{:<19}  {:<24}  {:<10}  {:<7}  {:<8}  ERROR",
+        "WHEN", "TRANSITION", "SOURCE", "SESSION", "COMMIT"
+    );
+    for entry in history {
+        let transition = format!(
+            "{} → {}",
+            entry.from_status.map(|s| s.as_str()).unwrap_or("?"),
+            entry.to_status
+        );
+        let session = entry
+            .session_id
+            .map(|s| format!("#{}", s))
+            .unwrap_or_else(|| "-".to_string());
+        let error = entry
+            .error
+            .as_deref()
+            .and_then(|e| e.lines().next())
+            .unwrap_or("");
+        println!(
+            "{:<19}  {:<24}  {:<10}  {:<7}  {:<8}  {}",
+            entry.created_at,
+            transition,
+            entry.source,
+            session,
+            short(&entry.commit_hash),
+            error
+        );
+    }
+
+    // Last time a done feature went back to failing, and the commit it last passed at
+    let regression = history
+        .iter()
+        .rposition(|e| e.from_status.is_some_and(|s| s.is_done()) && !e.to_status.is_done());
+    if let Some(index) = regression {
+        let last_good = history[..index]
+            .iter()
+            .rev()
+            .find_map(|e| e.commit_hash.as_ref().filter(|_| e.to_status.is_done()));
+        let detected = &history[index].commit_hash;
+        if let (Some(good), Some(bad)) = (last_good, detected) {
+            println!();
+            println!(
+                "⚠️ Regressed between {} and {} (git log {}..{})",
+                short(&Some(good.clone())),
+                short(detected),
+                good,
+                bad
+            );
+        }
+    }
+}
+
+/// Worker id exported to parallel workers by the coordinator
+/// Look up a feature by numeric id or stable key
+fn find_feature(
+    repo: &db::features::FeatureRepository,
+    feature: &str,
+) -> Result<db::features::Feature> {
+    let found = match feature.parse::<i64>() {
+        Ok(id) => repo.get(id)?,
+        Err(_) => repo.get_by_key(feature)?,
+    };
+    found.ok_or_else(|| anyhow::anyhow!("No feature found with id or key {}", feature))
+}
+
+/// Resolve an optional `--feature` argument to a feature id
+fn feature_scope(db: &db::Database, feature: Option<&str>) -> Result<Option<i64>> {
+    feature
+        .map(|f| find_feature(&db.features(), f)?.db_id())
+        .transpose()
+}
+
+fn print_facts(format: OutputFormat, facts: &[db::knowledge::Knowledge]) -> Result<()> {
+    if format != OutputFormat::Table {
+        return output::print_records(format, facts);
+    }
+    if facts.is_empty() {
+        println!("No facts found.");
+    }
+    for fact in facts {
+        let mut notes = Vec::new();
+        if fact.pinned {
+            notes.push("pinned".to_string());
+        }
+        if let Some(id) = fact.feature_id {
+            notes.push(format!("feature {}", id));
+        }
+        if let Some(expires) = &fact.expires_at {
+            notes.push(format!("expires {}", expires));
+        }
+        if notes.is_empty() {
+            println!("[{}] {} = {}", fact.category, fact.key, fact.value);
+        } else {
+            println!(
+                "[{}] {} = {} ({})",
+                fact.category,
+                fact.key,
+                fact.value,
+                notes.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+fn worker_id_from_env() -> Option<usize> {
+    std::env::var(crate::autonomous::parallel::isolation::WORKER_ID_ENV)
+        .ok()
+        .and_then(|v| v.parse().ok())
+}
@@ -1,6 +1,8 @@
 pub mod db;
 pub mod example;
 pub mod init;
+pub mod portfolio;
+pub mod ps;
 pub mod reset;
 pub mod templates;
 pub mod vibe;
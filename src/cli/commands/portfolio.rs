@@ -0,0 +1,255 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::autonomous::stats::{TokenStats, TOKEN_STATS_KEY};
+use crate::cli::output::{self, OutputFormat};
+use crate::config::Config;
+use crate::db::instances::RegisteredProject;
+use crate::db::InstanceRepository;
+
+/// Progress of one registered project
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProjectSummary {
+    pub project: String,
+    pub passing: usize,
+    pub remaining: usize,
+    /// Instances still marked as running in the registry
+    pub running: usize,
+    /// Latest registry heartbeat or session in the project's database (UTC)
+    pub last_activity: Option<String>,
+    pub current_feature: Option<String>,
+    /// Cost reported by `opencode stats` at the end of the last session
+    pub cost_usd: Option<f64>,
+    /// Why the project's database could not be read
+    pub error: Option<String>,
+}
+
+/// Handles the `portfolio` subcommand.
+///
+/// Lists every project in the global instance registry with its feature
+/// progress, last activity, current feature and accumulated cost. Each
+/// project's `progress.db` is opened read-only, so running supervisors are
+/// not disturbed and no migrations are applied.
+///
+/// # Arguments
+///
+/// * `format` - How the table is printed (`--format`).
+///
+/// # Returns
+///
+/// Result indicating success or containing an error from the registry.
+pub fn handle_portfolio(format: OutputFormat) -> Result<()> {
+    let registry = InstanceRepository::open()?;
+    let running = registry.list(true)?;
+
+    let summaries: Vec<ProjectSummary> = registry
+        .projects()?
+        .into_iter()
+        .map(|project| {
+            // The newest running instance reports the feature in progress
+            let feature_id = running
+                .iter()
+                .filter(|i| i.project_path.as_deref() == Some(project.path.as_str()))
+                .find_map(|i| i.feature_id);
+            summarize_project(&project, feature_id)
+        })
+        .collect();
+
+    if format != OutputFormat::Table {
+        return output::print_records(format, &summaries);
+    }
+    if summaries.is_empty() {
+        println!("No projects registered yet. Run `opencode-forger vibe` in a project first.");
+        return Ok(());
+    }
+
+    let columns: Vec<String> = [
+        "PROJECT",
+        "PASSING",
+        "REMAINING",
+        "RUNNING",
+        "LAST ACTIVITY",
+        "COST",
+        "CURRENT FEATURE",
+    ]
+    .iter()
+    .map(|c| c.to_string())
+    .collect();
+    let rows: Vec<Vec<String>> = summaries
+        .iter()
+        .map(|s| {
+            let dash = || "-".to_string();
+            vec![
+                s.project.clone(),
+                s.passing.to_string(),
+                s.remaining.to_string(),
+                s.running.to_string(),
+                s.last_activity.clone().unwrap_or_else(dash),
+                s.cost_usd
+                    .map(|c| format!("${:.2}", c))
+                    .unwrap_or_else(dash),
+                match &s.error {
+                    Some(error) => format!("⚠️ {}", error),
+                    None => s.current_feature.clone().unwrap_or_else(dash),
+                },
+            ]
+        })
+        .collect();
+    print!("{}", crate::db::query::format_table(&columns, &rows));
+
+    let passing: usize = summaries.iter().map(|s| s.passing).sum();
+    let total: usize = summaries.iter().map(|s| s.passing + s.remaining).sum();
+    let cost = summaries
+        .iter()
+        .filter_map(|s| s.cost_usd)
+        .fold(0.0, |total, c| total + c);
+    println!(
+        "\n{} projects, {}/{} features passing, ${:.2} total",
+        summaries.len(),
+        passing,
+        total,
+        cost
+    );
+    Ok(())
+}
+
+/// Read a project's progress from its database, recording errors in the summary
+fn summarize_project(project: &RegisteredProject, feature_id: Option<i64>) -> ProjectSummary {
+    let mut summary = ProjectSummary {
+        project: project.path.clone(),
+        running: project.running,
+        last_activity: Some(project.last_seen.clone()),
+        ..Default::default()
+    };
+
+    let root = Path::new(&project.path);
+    if !root.is_dir() {
+        summary.error = Some("project directory is gone".to_string());
+        return summary;
+    }
+    let db_path = match Config::load(Some(root)) {
+        Ok(config) => root.join(config.paths.database_file),
+        Err(e) => {
+            summary.error = Some(format!("unreadable config: {}", e));
+            return summary;
+        }
+    };
+    if !db_path.exists() {
+        summary.error = Some("no progress database".to_string());
+        return summary;
+    }
+
+    if let Err(e) = read_progress(&db_path, feature_id, &mut summary) {
+        summary.error = Some(format!("{:#}", e));
+    }
+    summary
+}
+
+/// Fill in feature counts, activity, current feature and cost from `progress.db`
+fn read_progress(
+    db_path: &Path,
+    feature_id: Option<i64>,
+    summary: &mut ProjectSummary,
+) -> Result<()> {
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {}", db_path.display()))?;
+
+    let (total, passing): (i64, i64) = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(passes != 0), 0) FROM features",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    summary.passing = passing as usize;
+    summary.remaining = (total - passing) as usize;
+
+    let last_session: Option<String> = conn.query_row(
+        "SELECT MAX(COALESCE(completed_at, started_at)) FROM sessions",
+        [],
+        |row| row.get(0),
+    )?;
+    // Both timestamps come from SQLite's datetime('now'), so they sort as text
+    if last_session > summary.last_activity {
+        summary.last_activity = last_session;
+    }
+
+    if let Some(id) = feature_id {
+        summary.current_feature = conn
+            .query_row(
+                "SELECT description FROM features WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+    }
+
+    let stats: Option<String> = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = ?1",
+            params![TOKEN_STATS_KEY],
+            |row| row.get(0),
+        )
+        .optional()?;
+    summary.cost_usd = stats
+        .and_then(|s| serde_json::from_str::<TokenStats>(&s).ok())
+        .map(|s| s.total_cost);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::features::Feature;
+    use crate::db::Database;
+
+    #[test]
+    fn test_summarize_project_reads_progress_and_cost() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let db = Database::open(&temp.path().join(".forger/progress.db")).unwrap();
+        let mut ids = Vec::new();
+        for description in ["Login", "Signup", "Search"] {
+            ids.push(
+                db.features()
+                    .insert(&Feature::new("functional", description))
+                    .unwrap(),
+            );
+        }
+        db.features().mark_passing(ids[0]).unwrap();
+        db.meta()
+            .set(
+                TOKEN_STATS_KEY,
+                r#"{"input_tokens":10,"output_tokens":5,"total_cost":1.25}"#,
+            )
+            .unwrap();
+        drop(db);
+
+        let project = RegisteredProject {
+            path: temp.path().to_string_lossy().to_string(),
+            last_seen: "2026-01-01 00:00:00".to_string(),
+            running: 1,
+        };
+        let summary = summarize_project(&project, Some(ids[1]));
+
+        assert_eq!(summary.error, None);
+        assert_eq!((summary.passing, summary.remaining), (1, 2));
+        assert_eq!(summary.current_feature.as_deref(), Some("Signup"));
+        assert_eq!(summary.cost_usd, Some(1.25));
+        assert_eq!(
+            summary.last_activity.as_deref(),
+            Some("2026-01-01 00:00:00")
+        );
+    }
+
+    #[test]
+    fn test_missing_project_is_reported() {
+        let project = RegisteredProject {
+            path: "/nonexistent/forger-project".to_string(),
+            last_seen: "2026-01-01 00:00:00".to_string(),
+            running: 0,
+        };
+        let summary = summarize_project(&project, None);
+        assert_eq!(summary.error.as_deref(), Some("project directory is gone"));
+    }
+}
@@ -0,0 +1,266 @@
+use anyhow::{bail, Result};
+use chrono::{NaiveDateTime, Utc};
+
+use crate::autonomous::parallel::isolation::{
+    kill_process, process_alive, registered_process_alive, request_stop, terminate_worker_group,
+};
+use crate::db::instances::{Instance, REGISTRY_TIME_FORMAT};
+use crate::db::InstanceRepository;
+
+/// Handles the `ps` subcommand.
+///
+/// Lists registered supervisors, coordinators and their workers across all
+/// projects, with the feature each one is working on, how long it has been
+/// running and when it last reported a heartbeat.
+///
+/// # Arguments
+///
+/// * `all` - Include stopped and errored instances.
+///
+/// # Returns
+///
+/// Result indicating success or containing an error from the registry.
+pub fn handle_ps(all: bool) -> Result<()> {
+    let repo = InstanceRepository::open()?;
+    let mut instances = repo.list(!all)?;
+    if instances.is_empty() {
+        println!("No running instances.");
+        return Ok(());
+    }
+    instances.sort_by_key(|i| i.id);
+
+    let now = Utc::now().naive_utc();
+    println!(
+        "{:<6} {:<14} {:<8} {:<8} {:<8} {:<9} {:<9} PROJECT",
+        "ID", "ROLE", "PID", "STATUS", "FEATURE", "ELAPSED", "HEARTBEAT"
+    );
+    for instance in ordered_tree(&instances) {
+        print_row(instance, now);
+    }
+    Ok(())
+}
+
+/// Handles the `kill` subcommand.
+///
+/// By default the instance is asked to stop after its current session, the
+/// same as pressing Ctrl+C in its terminal; stopping a coordinator also asks
+/// its workers to stop. With `force` the processes are killed immediately,
+/// including everything in the workers' process groups; a PID that can't be
+/// confirmed to still belong to its instance is marked as errored instead.
+///
+/// # Arguments
+///
+/// * `id` - Registry id of the instance, as shown by `ps`.
+/// * `force` - Kill immediately instead of stopping cleanly.
+///
+/// # Returns
+///
+/// Result indicating success or containing an error if the instance is unknown.
+pub fn handle_kill(id: i64, force: bool) -> Result<()> {
+    let repo = InstanceRepository::open()?;
+    let Some(instance) = repo.get(id)? else {
+        bail!(
+            "No instance #{} in the registry (see 'opencode-forger ps')",
+            id
+        );
+    };
+
+    if instance.status != "running" {
+        println!("Instance #{} is not running ({}).", id, instance.status);
+        return Ok(());
+    }
+    if !process_alive(instance.pid) {
+        repo.mark_error(id)?;
+        println!(
+            "Instance #{} (PID {}) is no longer alive; marked as error.",
+            id, instance.pid
+        );
+        return Ok(());
+    }
+
+    let workers: Vec<Instance> = repo
+        .list(true)?
+        .into_iter()
+        .filter(|i| i.parent_id == Some(id) && process_alive(i.pid))
+        .collect();
+
+    if force {
+        if !registered_process_alive(instance.pid, &instance.start_time) {
+            repo.mark_error(id)?;
+            println!(
+                "Can't confirm PID {} is still instance #{}; marked as error, nothing killed.",
+                instance.pid, id
+            );
+            return Ok(());
+        }
+        for worker in &workers {
+            if registered_process_alive(worker.pid, &worker.start_time) {
+                terminate_worker_group(worker.pid);
+                repo.mark_stopped(worker.id)?;
+            } else {
+                repo.mark_error(worker.id)?;
+            }
+        }
+        if instance.role == "worker" {
+            terminate_worker_group(instance.pid);
+        } else {
+            kill_process(instance.pid);
+        }
+        repo.mark_stopped(id)?;
+        println!(
+            "💀 Killed instance #{} ({}, PID {}){}",
+            id,
+            instance.role,
+            instance.pid,
+            workers_suffix(workers.len())
+        );
+        return Ok(());
+    }
+
+    if !request_stop(instance.pid) {
+        bail!(
+            "Could not signal instance #{} (PID {}); use --force to kill it",
+            id,
+            instance.pid
+        );
+    }
+    for worker in &workers {
+        request_stop(worker.pid);
+    }
+    println!(
+        "🛑 Stop requested for instance #{} ({}, PID {}){}",
+        id,
+        instance.role,
+        instance.pid,
+        workers_suffix(workers.len())
+    );
+    println!("   It will exit after its current session. Use --force to kill it now.");
+    Ok(())
+}
+
+fn workers_suffix(count: usize) -> String {
+    if count == 0 {
+        String::new()
+    } else {
+        format!(" and {} worker(s)", count)
+    }
+}
+
+fn print_row(instance: &Instance, now: NaiveDateTime) {
+    let role = if instance.parent_id.is_some() {
+        format!("└ {}", instance.role)
+    } else {
+        instance.role.clone()
+    };
+    let status = if instance.status == "running" && !process_alive(instance.pid) {
+        "dead"
+    } else {
+        instance.status.as_str()
+    };
+    let feature = instance
+        .feature_id
+        .map(|id| format!("#{}", id))
+        .unwrap_or_else(|| "-".to_string());
+    let elapsed = seconds_since(&instance.start_time, now)
+        .map(format_duration)
+        .unwrap_or_else(|| "?".to_string());
+    let heartbeat = seconds_since(&instance.updated_at, now)
+        .map(|secs| format!("{} ago", format_duration(secs)))
+        .unwrap_or_else(|| "?".to_string());
+    let location = match (&instance.project_path, &instance.worktree) {
+        (Some(project), Some(worktree)) => format!("{} ({})", project, worktree),
+        (Some(project), None) => project.clone(),
+        (None, Some(worktree)) => worktree.clone(),
+        (None, None) => "-".to_string(),
+    };
+
+    println!(
+        "#{:<5} {:<14} {:<8} {:<8} {:<8} {:<9} {:<9} {}",
+        instance.id, role, instance.pid, status, feature, elapsed, heartbeat, location
+    );
+}
+
+/// Order instances so each worker is listed directly below its parent
+fn ordered_tree(instances: &[Instance]) -> Vec<&Instance> {
+    let is_listed = |id: i64| instances.iter().any(|i| i.id == id);
+    let mut ordered = Vec::with_capacity(instances.len());
+
+    for root in instances
+        .iter()
+        .filter(|i| !i.parent_id.is_some_and(is_listed))
+    {
+        ordered.push(root);
+        ordered.extend(instances.iter().filter(|i| i.parent_id == Some(root.id)));
+    }
+    ordered
+}
+
+/// Seconds between a registry timestamp and `now`
+fn seconds_since(timestamp: &str, now: NaiveDateTime) -> Option<i64> {
+    let then = NaiveDateTime::parse_from_str(timestamp, REGISTRY_TIME_FORMAT).ok()?;
+    Some((now - then).num_seconds().max(0))
+}
+
+/// Compact human-readable duration, e.g. `45s`, `3m 12s`, `2h 05m`, `1d 04h`
+fn format_duration(secs: i64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m {:02}s", s / 60, s % 60),
+        s if s < 86400 => format!("{}h {:02}m", s / 3600, (s % 3600) / 60),
+        s => format!("{}d {:02}h", s / 86400, (s % 86400) / 3600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(id: i64, parent_id: Option<i64>) -> Instance {
+        Instance {
+            id,
+            pid: 1,
+            role: if parent_id.is_some() {
+                "worker"
+            } else {
+                "coordinator"
+            }
+            .to_string(),
+            start_time: "2026-01-01 00:00:00".to_string(),
+            status: "running".to_string(),
+            log_path: None,
+            project_path: None,
+            updated_at: "2026-01-01 00:00:00".to_string(),
+            parent_id,
+            feature_id: None,
+            worktree: None,
+        }
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(192), "3m 12s");
+        assert_eq!(format_duration(7500), "2h 05m");
+        assert_eq!(format_duration(100_800), "1d 04h");
+    }
+
+    #[test]
+    fn test_seconds_since() {
+        let now =
+            NaiveDateTime::parse_from_str("2026-01-01 01:00:30", REGISTRY_TIME_FORMAT).unwrap();
+        assert_eq!(seconds_since("2026-01-01 01:00:00", now), Some(30));
+        assert_eq!(seconds_since("not a time", now), None);
+    }
+
+    #[test]
+    fn test_workers_listed_under_their_parent() {
+        let instances = vec![
+            instance(1, None),
+            instance(2, None),
+            instance(3, Some(1)),
+            instance(4, Some(99)),
+        ];
+        let ids: Vec<i64> = ordered_tree(&instances).iter().map(|i| i.id).collect();
+        assert_eq!(ids, vec![1, 3, 2, 4]);
+    }
+}
@@ -15,7 +15,7 @@ use crate::theming::{accent, error, highlight, muted, primary, symbols};
 use crate::tui;
 use crate::updater;
 
-use super::commands::{db, example, init, reset, templates, vibe};
+use super::commands::{db, example, init, portfolio, ps, reset, templates, vibe};
 use super::{Cli, Commands, Mode};
 
 /// Main entry point for handling CLI commands.
@@ -75,8 +75,11 @@ pub fn run(cli: Cli) -> Result<()> {
                 cli.dry_run,
             ),
             Commands::Templates { action } => templates::handle_templates(action, &output_dir),
-            Commands::Db { action } => db::handle_db(action),
+            Commands::Db { format, action } => db::handle_db(action, *format),
             Commands::Example { topic } => example::handle_example(topic),
+            Commands::Ps { all } => ps::handle_ps(*all),
+            Commands::Portfolio { format } => portfolio::handle_portfolio(*format),
+            Commands::Kill { id, force } => ps::handle_kill(*id, *force),
             Commands::Update => match updater::update() {
                 Ok(_) => Ok(()),
                 Err(e) => {
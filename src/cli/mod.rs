@@ -2,6 +2,7 @@
 
 pub mod commands;
 pub mod handlers;
+pub mod output;
 
 use anyhow::{bail, Result};
 use clap::{Parser, Subcommand};
@@ -109,6 +110,9 @@ pub enum Commands {
     },
     /// Database management commands
     Db {
+        /// Output format for listings and query results
+        #[arg(long, global = true, value_enum, default_value_t = output::OutputFormat::Table)]
+        format: output::OutputFormat,
         #[command(subcommand)]
         action: DbAction,
     },
@@ -117,6 +121,27 @@ pub enum Commands {
         #[command(subcommand)]
         topic: ExampleTopic,
     },
+    /// List running supervisors and parallel workers across all projects
+    Ps {
+        /// Include stopped and errored instances
+        #[arg(short, long)]
+        all: bool,
+    },
+    /// Show progress, activity and cost of every registered project
+    Portfolio {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = output::OutputFormat::Table)]
+        format: output::OutputFormat,
+    },
+    /// Stop a running instance by its registry id (see `ps`)
+    Kill {
+        /// Instance id as shown by `ps`
+        id: i64,
+
+        /// Kill immediately instead of stopping after the current session
+        #[arg(long)]
+        force: bool,
+    },
     /// Update opencode-forger to the latest version
     Update,
     /// Initialize a new project (alias for interactive mode)
@@ -162,12 +187,41 @@ pub enum DbAction {
         #[arg(value_name = "FILE")]
         json_path: Option<PathBuf>,
     },
+    /// Import features from a GitHub issues export, Markdown checklist, CSV or app spec
+    Import {
+        /// File to import
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+        /// Source format (legacy, github, markdown, csv, spec); detected from the file if omitted
+        #[arg(long, value_name = "FORMAT")]
+        from: Option<String>,
+        /// What to do with features whose description already exists (skip, merge, overwrite)
+        #[arg(long, value_name = "POLICY", default_value = "skip")]
+        on_duplicate: String,
+    },
     /// Export features from database to JSON file
     Export {
         /// Output JSON file path (default: feature_list_export.json)
         #[arg(value_name = "FILE")]
         output: Option<PathBuf>,
     },
+    /// Copy the database with SQLite's online backup API
+    Backup {
+        /// Backup file (default: a timestamped file in the snapshots directory)
+        #[arg(value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// List database snapshots and backups, oldest first
+    Snapshots,
+    /// Replace the database with a snapshot, showing the feature status changes
+    Restore {
+        /// Snapshot file, or its name in the snapshots directory
+        #[arg(value_name = "SNAPSHOT")]
+        snapshot: PathBuf,
+        /// Only show what would change
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Show database statistics
     Stats,
     /// Execute a SELECT query (read-only)
@@ -175,7 +229,7 @@ pub enum DbAction {
         /// SQL SELECT query string
         sql: String,
     },
-    /// Execute a write query (INSERT, UPDATE, DELETE, CREATE)
+    /// Execute a write query (requires `allow_raw_sql` under [security])
     Exec {
         /// SQL modification query string
         sql: String,
@@ -185,6 +239,24 @@ pub enum DbAction {
         /// Path to custom feature database or JSON (legacy)
         #[arg(long, value_name = "FILE")]
         path: Option<PathBuf>,
+        /// Number of features to verify concurrently (each job gets its own port range)
+        #[arg(long, short = 'j', default_value_t = 1)]
+        jobs: usize,
+        /// Run each job in its own git worktree of HEAD (needs a clean working tree)
+        #[arg(long)]
+        isolate: bool,
+        /// Write a JUnit XML report to this file
+        #[arg(long, value_name = "FILE")]
+        junit: Option<PathBuf>,
+        /// Print the results as JSON instead of the summary
+        #[arg(long)]
+        json: bool,
+        /// Only check features whose history touches files changed since this commit
+        #[arg(long, value_name = "COMMIT")]
+        since: Option<String>,
+        /// Re-run verifications that already passed against the current tree
+        #[arg(long)]
+        no_cache: bool,
     },
     /// List all tables in the database
     Tables,
@@ -193,7 +265,13 @@ pub enum DbAction {
         /// Table name to describe
         table: String,
     },
-    /// Get the next incomplete feature
+    /// Apply pending schema migrations to the database
+    MigrateSchema {
+        /// Only show the schema version and pending migrations
+        #[arg(long)]
+        status: bool,
+    },
+    /// Get the next feature to work on (actionable status, highest priority, lowest id)
     NextFeature,
     /// Mark a feature as passing
     MarkPass {
@@ -211,6 +289,55 @@ pub enum DbAction {
         /// List only remaining (not passing) features
         #[arg(long)]
         remaining: bool,
+        /// List only features in this state (pending, in_progress, passing,
+        /// failing, blocked, quarantined, manual)
+        #[arg(long, value_name = "STATUS")]
+        status: Option<String>,
+    },
+    /// List supervisor sessions, most recent first
+    Sessions,
+    /// List registered instances (same registry as `ps`)
+    Instances {
+        /// Include stopped and errored instances
+        #[arg(long)]
+        all: bool,
+    },
+    /// Show the status timeline of a feature
+    History {
+        /// Feature ID
+        id: i64,
+    },
+    /// List recent verification runs
+    Runs {
+        /// Only runs for this feature (ID or key)
+        #[arg(long)]
+        feature: Option<String>,
+        /// Number of runs to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Show a verification run with its per-test results
+    Run {
+        /// Run ID (from `db runs`)
+        id: i64,
+    },
+    /// List features whose verification flakes (mixed outcomes across re-runs)
+    Flaky {
+        /// Include every checked feature, not only those over the flake threshold
+        #[arg(long)]
+        all: bool,
+    },
+    /// Set the lifecycle state of a feature (e.g. quarantined, manual)
+    SetStatus {
+        /// Feature ID
+        id: i64,
+        /// New state (pending, in_progress, passing, failing, blocked, quarantined, manual)
+        status: String,
+    },
+    /// Add and edit features without raw SQL
+    Feature {
+        #[command(subcommand)]
+        action: FeatureAction,
     },
     /// Manage persistent agent knowledge
     Knowledge {
@@ -235,6 +362,12 @@ pub enum KnowledgeAction {
         /// Optional description
         #[arg(short, long)]
         description: Option<String>,
+        /// Only applies to this feature (ID or key)
+        #[arg(long)]
+        feature: Option<String>,
+        /// Forget the fact after this long (e.g. 30m, 2h, 7d)
+        #[arg(long)]
+        ttl: Option<String>,
     },
     /// Get a fact by key
     Get { key: String },
@@ -243,25 +376,158 @@ pub enum KnowledgeAction {
         /// Filter by category
         #[arg(short, long)]
         category: Option<String>,
+        /// Only project-wide facts and facts for this feature (ID or key)
+        #[arg(long)]
+        feature: Option<String>,
+    },
+    /// Full-text search over keys, values and descriptions
+    Search {
+        /// Search terms (all must match)
+        #[arg(required = true)]
+        terms: Vec<String>,
+        /// Only project-wide facts and facts for this feature (ID or key)
+        #[arg(long)]
+        feature: Option<String>,
     },
     /// Delete a fact
     Delete { key: String },
+    /// Always inject a fact into session prompts
+    Pin { key: String },
+    /// Stop always injecting a fact
+    Unpin { key: String },
     /// Track a server process (saves port→PID mapping)
     TrackServer {
         /// Port the server is running on
         port: u16,
         /// PID of the server process
         pid: u32,
+        /// Parallel worker id (defaults to $FORGER_WORKER_ID)
+        #[arg(long)]
+        worker: Option<usize>,
     },
     /// Get the tracked PID for a server on a port
     GetServer {
         /// Port to look up
         port: u16,
+        /// Parallel worker id (defaults to $FORGER_WORKER_ID)
+        #[arg(long)]
+        worker: Option<usize>,
     },
     /// Remove tracking for a server (use after killing it)
     UntrackServer {
         /// Port to untrack
         port: u16,
+        /// Parallel worker id (defaults to $FORGER_WORKER_ID)
+        #[arg(long)]
+        worker: Option<usize>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FeatureAction {
+    /// Add a feature (skipped if one with the same description exists)
+    Add {
+        /// Feature category (e.g. functional, style)
+        #[arg(long)]
+        category: String,
+        /// What the feature does; must be unique
+        #[arg(long)]
+        description: String,
+        /// Command that verifies the feature
+        #[arg(long = "verify", value_name = "COMMAND")]
+        verification_command: Option<String>,
+        /// Priority (critical, high, medium, low)
+        #[arg(long, default_value = "medium")]
+        priority: String,
+        /// Verification step (repeat for several, in order)
+        #[arg(long = "step", value_name = "STEP")]
+        steps: Vec<String>,
+    },
+    /// Replace a feature's verification steps
+    SetSteps {
+        /// Feature ID
+        id: i64,
+        /// New steps, in order
+        #[arg(required = true)]
+        steps: Vec<String>,
+    },
+    /// Set the command that verifies a feature
+    SetVerify {
+        /// Feature ID
+        id: i64,
+        /// Verification command
+        command: String,
+        /// Seconds before verification is killed (0 = use the global timeout)
+        #[arg(long = "timeout", value_name = "SECS")]
+        timeout_secs: Option<u64>,
+    },
+    /// Append a step to a feature's verification pipeline
+    ///
+    /// Once a feature has steps, they run in order instead of its single
+    /// verification command and the first failing step is reported.
+    AddVerifyStep {
+        /// Feature ID or key
+        feature: String,
+        /// Step name (e.g. build, lint, unit, e2e)
+        #[arg(long)]
+        name: String,
+        /// Shell command for the step
+        command: String,
+        /// Seconds before the step is killed (default: 300)
+        #[arg(long = "timeout", value_name = "SECS")]
+        timeout_secs: Option<u64>,
+        /// Directory to run in, relative to the project root
+        #[arg(long)]
+        workdir: Option<String>,
+        /// Environment variable for the step (repeat for several)
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env: Vec<String>,
+        /// Exit code that counts as success
+        #[arg(long, default_value_t = 0)]
+        expect_exit_code: i32,
+        /// Regex the step's stdout or stderr must match
+        #[arg(long, value_name = "REGEX")]
+        expect_output: Option<String>,
+    },
+    /// Remove a feature's verification steps (its verification command applies again)
+    ClearVerifySteps {
+        /// Feature ID or key
+        feature: String,
+    },
+    /// Mark a feature as failing with an error message
+    Fail {
+        /// Feature ID
+        id: i64,
+        /// What went wrong (shown to the next fix session)
+        #[arg(long)]
+        error: String,
+    },
+    /// Move a feature to another category
+    SetCategory {
+        /// Feature ID
+        id: i64,
+        /// New category
+        category: String,
+    },
+    /// Edit a feature; its id, key and status are kept
+    Edit {
+        /// Feature ID or key
+        feature: String,
+        /// New description (must stay unique)
+        #[arg(long)]
+        description: Option<String>,
+        /// New category
+        #[arg(long)]
+        category: Option<String>,
+        /// New priority (critical, high, medium, low)
+        #[arg(long)]
+        priority: Option<String>,
+        /// New verification command
+        #[arg(long = "verify", value_name = "COMMAND")]
+        verification_command: Option<String>,
+        /// Verification timeout in seconds (0 = use the global timeout)
+        #[arg(long = "verify-timeout", value_name = "SECS")]
+        verification_timeout_secs: Option<u64>,
     },
 }
 
@@ -270,7 +536,7 @@ pub enum KnowledgeAction {
 pub enum ExampleTopic {
     /// Show database-related examples
     Db {
-        /// Show example feature INSERT statements
+        /// Show example `db feature add` commands
         #[arg(long)]
         insert: bool,
         /// Show example SQL queries for feature inspection
@@ -0,0 +1,215 @@
+//! Output formats for `db` subcommands
+//!
+//! Listing commands render through [`OutputFormat`] so scripts and dashboards
+//! can ask for JSON, CSV or Markdown instead of scraping the human-readable
+//! tables.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// How `db` subcommands print their results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable output
+    #[default]
+    Table,
+    /// JSON array of objects
+    Json,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Markdown table
+    Markdown,
+}
+
+/// Print serializable records, one row per record
+pub fn print_records<T: Serialize>(format: OutputFormat, records: &[T]) -> Result<()> {
+    print!("{}", render_records(format, records)?);
+    Ok(())
+}
+
+/// Print a result set given as column names and JSON values
+pub fn print_rows(format: OutputFormat, columns: &[String], rows: &[Vec<Value>]) -> Result<()> {
+    print!("{}", render_rows(format, columns, rows)?);
+    Ok(())
+}
+
+/// Render serializable records, one row per record
+///
+/// Columns follow the field order of the records; fields skipped by serde
+/// on some records are left empty.
+pub fn render_records<T: Serialize>(format: OutputFormat, records: &[T]) -> Result<String> {
+    let values = records
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if format == OutputFormat::Json {
+        return Ok(format!("{}\n", serde_json::to_string_pretty(&values)?));
+    }
+
+    let mut columns: Vec<String> = Vec::new();
+    for value in &values {
+        if let Value::Object(map) = value {
+            for key in map.keys() {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+    }
+    let rows: Vec<Vec<Value>> = values
+        .iter()
+        .map(|value| {
+            columns
+                .iter()
+                .map(|c| value.get(c).cloned().unwrap_or(Value::Null))
+                .collect()
+        })
+        .collect();
+
+    render_rows(format, &columns, &rows)
+}
+
+/// Render a result set given as column names and JSON values
+pub fn render_rows(
+    format: OutputFormat,
+    columns: &[String],
+    rows: &[Vec<Value>],
+) -> Result<String> {
+    match format {
+        OutputFormat::Table => {
+            let rows: Vec<Vec<String>> = rows
+                .iter()
+                .map(|row| row.iter().map(|v| cell(v, "NULL")).collect())
+                .collect();
+            Ok(crate::db::query::format_table(columns, &rows))
+        }
+        OutputFormat::Json => {
+            let objects: Vec<Value> = rows
+                .iter()
+                .map(|row| {
+                    Value::Object(
+                        columns
+                            .iter()
+                            .cloned()
+                            .zip(row.iter().cloned())
+                            .collect::<Map<_, _>>(),
+                    )
+                })
+                .collect();
+            Ok(format!("{}\n", serde_json::to_string_pretty(&objects)?))
+        }
+        OutputFormat::Csv => {
+            let mut output = csv_line(columns.iter().map(String::as_str));
+            for row in rows {
+                let cells: Vec<String> = row.iter().map(|v| cell(v, "")).collect();
+                output.push_str(&csv_line(cells.iter().map(String::as_str)));
+            }
+            Ok(output)
+        }
+        OutputFormat::Markdown => {
+            let mut output = markdown_line(columns.iter().map(String::as_str));
+            output.push_str(&markdown_line(columns.iter().map(|_| "---")));
+            for row in rows {
+                let cells: Vec<String> = row.iter().map(|v| cell(v, "")).collect();
+                output.push_str(&markdown_line(cells.iter().map(String::as_str)));
+            }
+            Ok(output)
+        }
+    }
+}
+
+/// Text of a single cell; nested arrays and objects stay JSON
+fn cell(value: &Value, null: &str) -> String {
+    match value {
+        Value::Null => null.to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// One CSV record (RFC 4180 quoting)
+fn csv_line<'a>(cells: impl Iterator<Item = &'a str>) -> String {
+    let cells: Vec<String> = cells
+        .map(|c| {
+            if c.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", c.replace('"', "\"\""))
+            } else {
+                c.to_string()
+            }
+        })
+        .collect();
+    format!("{}\n", cells.join(","))
+}
+
+/// One Markdown table row, escaping pipes and line breaks
+fn markdown_line<'a>(cells: impl Iterator<Item = &'a str>) -> String {
+    let cells: Vec<String> = cells
+        .map(|c| {
+            c.replace('|', "\\|")
+                .replace("\r\n", "<br>")
+                .replace('\n', "<br>")
+        })
+        .collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    struct Row {
+        id: i64,
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        note: Option<String>,
+    }
+
+    fn rows() -> Vec<Row> {
+        vec![
+            Row {
+                id: 1,
+                name: "plain".to_string(),
+                note: None,
+            },
+            Row {
+                id: 2,
+                name: "a, \"quoted\" | piped".to_string(),
+                note: Some("two\nlines".to_string()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_csv_quotes_and_fills_skipped_fields() {
+        let csv = render_records(OutputFormat::Csv, &rows()).unwrap();
+        assert_eq!(
+            csv,
+            "id,name,note\n1,plain,\n2,\"a, \"\"quoted\"\" | piped\",\"two\nlines\"\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_escapes_pipes_and_newlines() {
+        let markdown = render_records(OutputFormat::Markdown, &rows()).unwrap();
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!(lines[0], "| id | name | note |");
+        assert_eq!(lines[1], "| --- | --- | --- |");
+        assert_eq!(lines[3], "| 2 | a, \"quoted\" \\| piped | two<br>lines |");
+    }
+
+    #[test]
+    fn test_json_rows_keep_types_and_column_order() {
+        let columns = vec!["name".to_string(), "count".to_string(), "gone".to_string()];
+        let json_rows = vec![vec![json!("a"), json!(3), Value::Null]];
+        let output = render_rows(OutputFormat::Json, &columns, &json_rows).unwrap();
+
+        let parsed: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(parsed, json!([{ "name": "a", "count": 3, "gone": null }]));
+        assert!(output.find("name").unwrap() < output.find("count").unwrap());
+    }
+}
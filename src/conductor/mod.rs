@@ -73,6 +73,18 @@ pub fn get_active_track(config: &Config) -> Result<Option<Track>> {
     Ok(None)
 }
 
+/// Get the track planned for a feature, named after its stable key
+pub fn track_for_feature(config: &Config, key: &str) -> Option<Track> {
+    if key.is_empty() {
+        return None;
+    }
+    let path = Path::new(&config.conductor.tracks_dir).join(key);
+    path.join("plan.md").exists().then(|| Track {
+        name: key.to_string(),
+        path,
+    })
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Tests
 // ─────────────────────────────────────────────────────────────────────────────
@@ -137,4 +149,20 @@ mod tests {
         assert!(updated.contains("- [x] Task 1"));
         assert!(updated.contains("- [ ] Task 2"));
     }
+
+    #[test]
+    fn test_track_for_feature_uses_key() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.conductor.tracks_dir = temp_dir.path().to_string_lossy().to_string();
+
+        let track_dir = temp_dir.path().join("user-can-log-in");
+        fs::create_dir_all(&track_dir).unwrap();
+        fs::write(track_dir.join("plan.md"), "- [ ] Task 1\n").unwrap();
+
+        let track = track_for_feature(&config, "user-can-log-in").unwrap();
+        assert_eq!(track.path, track_dir);
+        assert!(track_for_feature(&config, "other-feature").is_none());
+        assert!(track_for_feature(&config, "").is_none());
+    }
 }
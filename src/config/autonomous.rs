@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use std::collections::HashMap;
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Autonomous Session Configuration
@@ -44,6 +45,16 @@ pub struct AutonomousConfig {
     ///
     /// Triggers warnings when the AI appears to be stuck.
     pub max_no_progress: u32,
+
+    /// Snapshot the database every N iterations (0 = disabled)
+    ///
+    /// Rolling snapshots are written to `snapshots/` next to the database.
+    pub snapshot_every: u32,
+
+    /// Number of rolling snapshots to keep
+    ///
+    /// Older rolling snapshots are deleted; manual backups are never pruned.
+    pub snapshots_kept: u32,
 }
 
 impl Default for AutonomousConfig {
@@ -56,6 +67,8 @@ impl Default for AutonomousConfig {
             idle_timeout_seconds: 600,
             auto_commit: true,
             max_no_progress: 5,
+            snapshot_every: 10,
+            snapshots_kept: 5,
         }
     }
 }
@@ -82,13 +95,23 @@ pub struct AgentConfig {
 
     /// Number of passing features to verify before new work
     ///
-    /// How many features to verify are working before starting new tasks.
+    /// The least recently verified features are picked first, boosted when the last commit touched them or they flake.
     pub verification_sample_size: u32,
 
     /// Focus on one feature at a time
     ///
     /// Whether to complete one feature fully before moving to the next.
     pub single_feature_focus: bool,
+
+    /// Approximate token budget for knowledge facts injected into prompts
+    ///
+    /// Relevant facts are added to the session prompts up to this size; 0 disables injection.
+    pub knowledge_token_budget: u32,
+
+    /// Reuse passing verification results for an unchanged git tree
+    ///
+    /// Regression samples skip features whose pipeline already passed against the same code.
+    pub verification_cache: bool,
 }
 
 impl Default for AgentConfig {
@@ -98,6 +121,93 @@ impl Default for AgentConfig {
             max_research_attempts: 3,
             verification_sample_size: 2,
             single_feature_focus: true,
+            knowledge_token_budget: 800,
+            verification_cache: true,
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Flaky Verification Configuration
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// How many verification runs must pass out of how many once a run fails
+///
+/// A quorum only tolerates failures: a first run that passes is accepted as
+/// is, so a stable feature is verified once. After a failure, runs continue
+/// until `required` of them passed or that can no longer happen.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+pub struct Quorum {
+    /// Passing runs needed for the check to pass
+    pub required: u32,
+    /// Maximum number of runs
+    pub runs: u32,
+}
+
+impl Quorum {
+    /// A single run plus up to `retries` re-runs, passing on the first success
+    pub fn retries(retries: u32) -> Self {
+        Self {
+            required: 1,
+            runs: retries + 1,
+        }
+    }
+}
+
+/// Configuration for detecting flaky verification commands
+///
+/// Failed verifications are re-run before they count as failures, and
+/// features whose outcome keeps changing between runs are labelled flaky.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FlakyConfig {
+    /// Re-runs of a failed verification before the supervisor treats it as failed
+    ///
+    /// A feature that fails and then passes on a re-run is counted as a flake.
+    pub retries: u32,
+
+    /// Flake rate at which a feature is labelled flaky
+    ///
+    /// The share of checks, between 0.0 and 1.0, whose runs disagreed.
+    pub threshold: f64,
+
+    /// Checks needed before a feature can be labelled flaky
+    ///
+    /// Avoids labelling a feature after a single unlucky check.
+    pub min_checks: u32,
+
+    /// Pass policy for regression checks whose first run fails
+    ///
+    /// Defaults to "passes if 2 of 3": `{ required = 2, runs = 3 }`.
+    pub regression_quorum: Quorum,
+
+    /// Per-category overrides of `regression_quorum`
+    ///
+    /// Keyed by feature category, e.g. `[flaky.category_quorum] e2e = { required = 1, runs = 3 }`.
+    pub category_quorum: HashMap<String, Quorum>,
+}
+
+impl FlakyConfig {
+    /// Regression pass policy for a feature category
+    pub fn quorum_for(&self, category: &str) -> Quorum {
+        self.category_quorum
+            .get(category)
+            .copied()
+            .unwrap_or(self.regression_quorum)
+    }
+}
+
+impl Default for FlakyConfig {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            threshold: 0.2,
+            min_checks: 3,
+            regression_quorum: Quorum {
+                required: 2,
+                runs: 3,
+            },
+            category_quorum: HashMap::new(),
         }
     }
 }
@@ -137,6 +247,11 @@ pub struct AlternativeApproachesConfig {
     ///
     /// Directory where cached alternative approaches are stored.
     pub cache_dir: String,
+
+    /// Number of approaches to race in parallel worktrees (0 = disabled)
+    ///
+    /// The top K approaches run concurrently; the first branch whose verification passes is merged.
+    pub speculative_attempts: u32,
 }
 
 impl Default for AlternativeApproachesConfig {
@@ -147,6 +262,139 @@ impl Default for AlternativeApproachesConfig {
             retry_threshold: 3,
             cache_results: true,
             cache_dir: ".approach-cache".to_string(),
+            speculative_attempts: 0,
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Parallel Mode Configuration
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// How finished feature branches are integrated into the base branch
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    /// Rebase the feature branch onto the base branch and fast-forward.
+    ///
+    /// Keeps a linear history with the worker's individual commits.
+    #[default]
+    Rebase,
+
+    /// Create a merge commit (`git merge --no-ff`).
+    ///
+    /// Preserves the feature branch as a distinct line of history.
+    Merge,
+
+    /// Squash the feature branch into a single commit on the base branch.
+    ///
+    /// One commit per feature, regardless of how many the worker made.
+    Squash,
+}
+
+impl MergeStrategy {
+    /// Return the lowercase representation used in config files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MergeStrategy::Rebase => "rebase",
+            MergeStrategy::Merge => "merge",
+            MergeStrategy::Squash => "squash",
+        }
+    }
+}
+
+/// What to do with feature branches left behind by an interrupted parallel run
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RecoveryPolicy {
+    /// Prompt for each recovered branch; keeps them when not attached to a terminal.
+    #[default]
+    Ask,
+
+    /// Merge recovered branches whose verification command passes.
+    ///
+    /// Branches that fail or have no verification command are kept.
+    Merge,
+
+    /// Keep all recovered branches for manual review.
+    Keep,
+}
+
+/// Configuration for parallel (worktree-based) execution
+///
+/// Controls how the coordinator schedules features across concurrent workers
+/// and how their branches are integrated afterwards.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ParallelConfig {
+    /// Avoid running features with overlapping predicted files concurrently
+    ///
+    /// Predictions come from stored implementation packets and past session diffs.
+    pub avoid_file_overlap: bool,
+
+    /// Integration branch that worktrees start from and merge back into
+    ///
+    /// Typically `main`, `master`, `develop` or a release branch.
+    pub base_branch: String,
+
+    /// How finished feature branches are integrated into the base branch
+    ///
+    /// One of `rebase` (default), `merge` or `squash`.
+    pub strategy: MergeStrategy,
+
+    /// Prefix for per-feature branch names
+    ///
+    /// Branches are named `<prefix><id>-<slug>`, e.g. `feature/12-user-login`.
+    pub branch_prefix: String,
+
+    /// Remote to push the base branch to after each successful merge
+    ///
+    /// Any git remote name or URL, including a path to a local bare repository.
+    pub push_remote: Option<String>,
+
+    /// First port handed out to parallel workers
+    ///
+    /// Worker N gets `port_range_start + N * ports_per_worker` onwards, exported as `PORT`.
+    pub port_range_start: u16,
+
+    /// Number of ports reserved for each worker
+    ///
+    /// Exported as `FORGER_PORT_RANGE_START`/`FORGER_PORT_RANGE_END`.
+    pub ports_per_worker: u16,
+
+    /// CPU time budget in seconds for a worker and everything it starts (Unix only)
+    ///
+    /// On Linux the coordinator sums the CPU time of the worker's process group
+    /// and kills the group once it is exceeded. Other Unix systems apply it as
+    /// `RLIMIT_CPU`, which each process inherits as its own budget.
+    pub worker_cpu_seconds: Option<u64>,
+
+    /// Memory budget in megabytes for a worker and everything it starts (Unix only)
+    ///
+    /// On Linux this caps the resident memory of the worker's whole process
+    /// group. Other Unix systems apply it per process as `RLIMIT_AS`, where
+    /// runtimes that reserve large virtual memory need generous values.
+    pub worker_memory_mb: Option<u64>,
+
+    /// What to do with feature branches left by an interrupted run
+    ///
+    /// One of `ask` (default), `merge` (merge branches that verify) or `keep`.
+    pub recovery: RecoveryPolicy,
+}
+
+impl Default for ParallelConfig {
+    fn default() -> Self {
+        Self {
+            avoid_file_overlap: true,
+            base_branch: "main".to_string(),
+            strategy: MergeStrategy::default(),
+            branch_prefix: "feature/".to_string(),
+            push_remote: None,
+            port_range_start: 8100,
+            ports_per_worker: 10,
+            worker_cpu_seconds: None,
+            worker_memory_mb: None,
+            recovery: RecoveryPolicy::default(),
         }
     }
 }
@@ -210,5 +458,6 @@ mod tests {
         assert_eq!(aa.num_approaches, 7);
         assert_eq!(aa.retry_threshold, 3);
         assert!(aa.cache_results);
+        assert_eq!(aa.speculative_attempts, 0);
     }
 }
@@ -24,6 +24,22 @@ pub struct SecurityConfig {
     ///
     /// List of command patterns that are always blocked for security reasons.
     pub blocked_patterns: Vec<String>,
+
+    /// Allow raw SQL writes
+    ///
+    /// Whether `db exec` may run INSERT/UPDATE/DELETE statements. Off by default;
+    /// agents use the typed `db feature` commands instead.
+    pub allow_raw_sql: bool,
+
+    /// Verification timeout in seconds
+    ///
+    /// Used for verification steps and features that don't set their own timeout.
+    pub verification_timeout_secs: u64,
+
+    /// Environment variables passed through to verification commands
+    ///
+    /// Everything else is scrubbed. A trailing `*` matches a prefix; `"*"` passes everything.
+    pub verification_env: Vec<String>,
 }
 
 impl Default for SecurityConfig {
@@ -40,6 +56,41 @@ impl Default for SecurityConfig {
                 "chmod 777".to_string(),
                 "> /dev/sda".to_string(),
             ],
+            allow_raw_sql: false,
+            verification_timeout_secs: 300,
+            verification_env: [
+                "PATH",
+                "HOME",
+                "USER",
+                "LOGNAME",
+                "SHELL",
+                "TERM",
+                "LANG",
+                "LC_*",
+                "TZ",
+                "TMPDIR",
+                "TEMP",
+                "TMP",
+                "CI",
+                "PORT",
+                "FORGER_*",
+                "CARGO_HOME",
+                "RUSTUP_HOME",
+                "GOPATH",
+                "GOCACHE",
+                "GOMODCACHE",
+                "NODE_PATH",
+                "NVM_DIR",
+                "VIRTUAL_ENV",
+                "JAVA_HOME",
+                "SYSTEMROOT",
+                "PATHEXT",
+                "COMSPEC",
+                "USERPROFILE",
+                "APPDATA",
+            ]
+            .map(String::from)
+            .to_vec(),
         }
     }
 }
@@ -166,5 +217,6 @@ mod tests {
         let security = SecurityConfig::default();
         assert!(security.enforce_allowlist);
         assert!(security.blocked_patterns.contains(&"sudo".to_string()));
+        assert!(!security.allow_raw_sql);
     }
 }
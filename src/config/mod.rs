@@ -14,7 +14,10 @@ pub mod environment;
 pub mod mcp_loader;
 pub mod project;
 
-pub use autonomous::{AgentConfig, AlternativeApproachesConfig, AutonomousConfig, ConductorConfig};
+pub use autonomous::{
+    AgentConfig, AlternativeApproachesConfig, AutonomousConfig, ConductorConfig, FlakyConfig,
+    MergeStrategy, ParallelConfig, Quorum, RecoveryPolicy,
+};
 pub use environment::{McpConfig, NotificationsConfig, SecurityConfig, UiConfig};
 pub use project::{
     ComplexityLevel, FeaturesConfig, GenerationConfig, GenerationRequirements, ModelsConfig,
@@ -37,6 +40,7 @@ pub struct Config {
     pub paths: PathsConfig,
     pub autonomous: AutonomousConfig,
     pub agent: AgentConfig,
+    pub flaky: FlakyConfig,
     pub alternative_approaches: AlternativeApproachesConfig,
     pub mcp: McpConfig,
     pub features: FeaturesConfig,
@@ -45,6 +49,7 @@ pub struct Config {
     pub ui: UiConfig,
     pub notifications: NotificationsConfig,
     pub conductor: ConductorConfig,
+    pub parallel: ParallelConfig,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -464,6 +469,60 @@ num_approaches = 5
         assert_eq!(config.ui.spec_preview_lines, 25);
     }
 
+    #[test]
+    fn test_load_parallel_config() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"
+[parallel]
+base_branch = "develop"
+strategy = "squash"
+push_remote = "../origin.git"
+"#
+        )
+        .unwrap();
+
+        let config = Config::load_from_file(file.path()).unwrap();
+        assert_eq!(config.parallel.base_branch, "develop");
+        assert_eq!(config.parallel.strategy, MergeStrategy::Squash);
+        assert_eq!(
+            config.parallel.push_remote.as_deref(),
+            Some("../origin.git")
+        );
+        // Check defaults preserved
+        assert_eq!(config.parallel.branch_prefix, "feature/");
+        assert!(config.parallel.avoid_file_overlap);
+    }
+
+    #[test]
+    fn test_load_flaky_config() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"
+[flaky]
+retries = 1
+
+[flaky.category_quorum]
+e2e = {{ required = 1, runs = 3 }}
+"#
+        )
+        .unwrap();
+
+        let config = Config::load_from_file(file.path()).unwrap();
+        assert_eq!(config.flaky.retries, 1);
+        assert_eq!(
+            config.flaky.quorum_for("e2e"),
+            Quorum {
+                required: 1,
+                runs: 3
+            }
+        );
+        // Other categories keep the default "2 of 3" policy
+        assert_eq!(config.flaky.quorum_for("functional").required, 2);
+    }
+
     #[test]
     fn test_expand_env_var() {
         std::env::set_var("TEST_VAR", "test_value");
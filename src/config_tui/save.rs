@@ -4,7 +4,7 @@ use anyhow::{Context, Result};
 use std::fs;
 use std::path::Path;
 
-use crate::config::Config;
+use crate::config::{Config, Quorum};
 
 /// Save config to forger.toml with formatted comments
 pub fn save_forger_toml(config: &Config, path: &Path) -> Result<()> {
@@ -51,6 +51,8 @@ session_timeout_minutes = {}  # 0 = no timeout
 idle_timeout_seconds = {}     # 0 = no timeout
 auto_commit = {}              # Commit on feature completion
 max_no_progress = {}          # 0 = unlimited
+snapshot_every = {}           # DB snapshot every N iterations, 0 = off
+snapshots_kept = {}           # Rolling snapshots to keep
 log_level = "{}"
 
 # ─────────────────────────────────────────────────────────────────────────────
@@ -61,6 +63,20 @@ max_retry_attempts = {}       # Before research mode
 max_research_attempts = {}    # Before giving up
 verification_sample_size = {} # Regression check sample
 single_feature_focus = {}     # One feature at a time
+knowledge_token_budget = {}   # Facts injected into prompts, 0 = off
+verification_cache = {}       # Reuse passes for an unchanged tree
+
+# ─────────────────────────────────────────────────────────────────────────────
+# Flaky Verification - Re-runs and flake detection
+# ─────────────────────────────────────────────────────────────────────────────
+[flaky]
+retries = {}                  # Re-runs of a failed verification
+threshold = {:?}              # Flake rate that labels a feature flaky
+min_checks = {}               # Checks before a feature can be labelled flaky
+regression_quorum = {}        # Re-runs after a failed regression check
+
+[flaky.category_quorum]
+{}
 
 # ─────────────────────────────────────────────────────────────────────────────
 # Stuck Recovery - Alternative approach generation
@@ -116,6 +132,9 @@ include_future_enhancements = {}
 enforce_allowlist = {}
 allowlist_file = "{}"
 blocked_patterns = [{}]
+allow_raw_sql = {}
+verification_timeout_secs = {}
+verification_env = [{}]
 
 # ─────────────────────────────────────────────────────────────────────────────
 # Paths - File locations
@@ -178,12 +197,32 @@ create_scripts_dir = {}
         config.autonomous.idle_timeout_seconds,
         config.autonomous.auto_commit,
         config.autonomous.max_no_progress,
+        config.autonomous.snapshot_every,
+        config.autonomous.snapshots_kept,
         config.autonomous.log_level,
         // Agent
         config.agent.max_retry_attempts,
         config.agent.max_research_attempts,
         config.agent.verification_sample_size,
         config.agent.single_feature_focus,
+        config.agent.knowledge_token_budget,
+        config.agent.verification_cache,
+        // Flaky
+        config.flaky.retries,
+        config.flaky.threshold, // Debug keeps the decimal point of 1.0
+        config.flaky.min_checks,
+        format_quorum(&config.flaky.regression_quorum),
+        if config.flaky.category_quorum.is_empty() {
+            "# e2e = { required = 1, runs = 3 }".to_string()
+        } else {
+            let mut categories: Vec<_> = config.flaky.category_quorum.iter().collect();
+            categories.sort_by_key(|(category, _)| category.as_str());
+            categories
+                .into_iter()
+                .map(|(category, quorum)| format!("\"{}\" = {}", category, format_quorum(quorum)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        },
         // Alternative approaches
         config.alternative_approaches.enabled,
         config.alternative_approaches.retry_threshold,
@@ -231,6 +270,15 @@ create_scripts_dir = {}
             .map(|t| format!("\"{}\"", t))
             .collect::<Vec<_>>()
             .join(", "),
+        config.security.allow_raw_sql,
+        config.security.verification_timeout_secs,
+        config
+            .security
+            .verification_env
+            .iter()
+            .map(|v| format!("\"{}\"", v))
+            .collect::<Vec<_>>()
+            .join(", "),
         // Paths
         config.paths.log_dir,
         config.paths.vs_cache_dir,
@@ -289,6 +337,13 @@ create_scripts_dir = {}
     )
 }
 
+fn format_quorum(quorum: &Quorum) -> String {
+    format!(
+        "{{ required = {}, runs = {} }}",
+        quorum.required, quorum.runs
+    )
+}
+
 fn format_opencode_json(config: &Config) -> String {
     let sequential_thinking_enabled = config.mcp.use_sequential_thinking;
     let chrome_devtools_enabled = config
@@ -333,3 +388,37 @@ fn format_opencode_json(config: &Config) -> String {
         chrome_devtools_enabled = chrome_devtools_enabled,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flaky_settings_round_trip() {
+        let mut config = Config::default();
+        config.flaky.retries = 4;
+        config.flaky.threshold = 1.0;
+        config.flaky.regression_quorum = Quorum {
+            required: 3,
+            runs: 5,
+        };
+        config.flaky.category_quorum.insert(
+            "e2e".to_string(),
+            Quorum {
+                required: 1,
+                runs: 3,
+            },
+        );
+
+        let saved: Config = toml::from_str(&format_forger_toml(&config)).unwrap();
+        assert_eq!(saved.flaky.retries, 4);
+        assert_eq!(
+            saved.flaky.regression_quorum,
+            config.flaky.regression_quorum
+        );
+        assert_eq!(saved.flaky.category_quorum, config.flaky.category_quorum);
+
+        let defaults: Config = toml::from_str(&format_forger_toml(&Config::default())).unwrap();
+        assert!(defaults.flaky.category_quorum.is_empty());
+    }
+}
@@ -0,0 +1,371 @@
+//! Backups, rolling snapshots and restore for the progress database
+//!
+//! Copies go through SQLite's online backup API, so they are consistent even
+//! while a supervisor or parallel workers are writing. Snapshots live in a
+//! `snapshots/` directory next to `progress.db`; rolling snapshots taken by
+//! the supervisor are pruned, manual backups and pre-restore copies are not.
+
+use anyhow::{bail, Context, Result};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::connection::clear_database_file;
+use super::features::FeatureStatus;
+use super::{migrations, Database};
+
+/// Label of snapshots taken by the supervisor loop (the only ones pruned)
+pub const ROLLING_LABEL: &str = "iteration";
+
+/// First schema version with the `features.status` column
+const STATUS_SCHEMA_VERSION: u32 = 3;
+
+/// A snapshot file in the snapshot directory
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotInfo {
+    pub name: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    #[serde(skip)]
+    modified: std::time::SystemTime,
+}
+
+/// A feature whose status differs between the live database and a snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusChange {
+    pub id: i64,
+    pub description: String,
+    /// Status in the live database (`None` if the snapshot predates the feature)
+    pub current: Option<FeatureStatus>,
+    /// Status after restoring (`None` if the feature is newer than the snapshot)
+    pub snapshot: Option<FeatureStatus>,
+}
+
+/// Directory holding snapshots of the database at `db_path`
+pub fn snapshot_dir(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("snapshots")
+}
+
+/// Copy a live database to `dest`, replacing any existing file
+///
+/// Uses the online backup API rather than `VACUUM INTO` (see
+/// [`Database::snapshot_to`]): [`restore`] must copy into the live database
+/// in place, which only the backup API can do, and copying page by page
+/// keeps a backup byte-for-byte what a restore writes back without rebuilding
+/// every table and index on each rolling snapshot.
+pub fn backup(db: &Database, dest: &Path) -> Result<()> {
+    clear_database_file(dest)?;
+
+    let mut target = Connection::open(dest)
+        .with_context(|| format!("Failed to create backup: {}", dest.display()))?;
+    let conn = db.connection();
+    let conn = conn.lock().unwrap();
+    copy(&conn, &mut target)
+        .with_context(|| format!("Failed to back up database to {}", dest.display()))
+}
+
+/// Write a timestamped snapshot named after `label` into `dir`
+pub fn take_snapshot(db: &Database, dir: &Path, label: &str) -> Result<PathBuf> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S%3f");
+    let mut path = dir.join(format!("{}-{}.db", stamp, label));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}-{}.db", stamp, label, n));
+        n += 1;
+    }
+    backup(db, &path)?;
+    Ok(path)
+}
+
+/// Snapshots in `dir`, oldest first
+pub fn list_snapshots(dir: &Path) -> Result<Vec<SnapshotInfo>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read snapshot directory: {}", dir.display()))?
+    {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("db") {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        snapshots.push(SnapshotInfo {
+            name: entry.file_name().to_string_lossy().to_string(),
+            size_bytes: metadata.len(),
+            modified: metadata.modified()?,
+            path,
+        });
+    }
+    snapshots.sort_by(|a, b| a.modified.cmp(&b.modified).then(a.name.cmp(&b.name)));
+    Ok(snapshots)
+}
+
+/// Delete all but the newest `keep` rolling snapshots; returns how many were removed
+pub fn prune_snapshots(dir: &Path, keep: usize) -> Result<usize> {
+    let marker = format!("-{}", ROLLING_LABEL);
+    let rolling: Vec<SnapshotInfo> = list_snapshots(dir)?
+        .into_iter()
+        .filter(|s| s.name.contains(&marker))
+        .collect();
+
+    let excess = rolling.len().saturating_sub(keep);
+    for snapshot in &rolling[..excess] {
+        std::fs::remove_file(&snapshot.path)
+            .with_context(|| format!("Failed to remove {}", snapshot.path.display()))?;
+    }
+    Ok(excess)
+}
+
+/// Find a snapshot by path, or by file name in the snapshot directory
+pub fn resolve_snapshot(db_path: &Path, snapshot: &Path) -> Result<PathBuf> {
+    if snapshot.exists() {
+        return Ok(snapshot.to_path_buf());
+    }
+    let in_dir = snapshot_dir(db_path).join(snapshot);
+    if in_dir.exists() {
+        return Ok(in_dir);
+    }
+    bail!(
+        "Snapshot not found: {} (see `opencode-forger db snapshots`)",
+        snapshot.display()
+    )
+}
+
+/// Feature status changes a restore of `snapshot` would make
+///
+/// Snapshots from before the `status` column are migrated on an in-memory
+/// copy first, so their statuses are the ones a restore would leave behind.
+pub fn diff_statuses(current: &Database, snapshot: &Path) -> Result<Vec<StatusChange>> {
+    let version = migrations::version_of(snapshot)?;
+    let mut snap = Connection::open_with_flags(snapshot, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open snapshot: {}", snapshot.display()))?;
+    if version < STATUS_SCHEMA_VERSION {
+        let mut migrated = Connection::open_in_memory()?;
+        copy(&snap, &mut migrated)?;
+        migrations::run_pending(&migrated)
+            .with_context(|| format!("Failed to migrate {}", snapshot.display()))?;
+        snap = migrated;
+    }
+    let snapshot_rows = feature_statuses(&snap)
+        .with_context(|| format!("Failed to read features from {}", snapshot.display()))?;
+    let current_rows = {
+        let conn = current.connection();
+        let conn = conn.lock().unwrap();
+        feature_statuses(&conn)?
+    };
+
+    let mut changes = Vec::new();
+    for (id, (description, status)) in &current_rows {
+        let before = snapshot_rows.get(id).map(|(_, s)| *s);
+        if before != Some(*status) {
+            changes.push(StatusChange {
+                id: *id,
+                description: description.clone(),
+                current: Some(*status),
+                snapshot: before,
+            });
+        }
+    }
+    for (id, (description, status)) in &snapshot_rows {
+        if !current_rows.contains_key(id) {
+            changes.push(StatusChange {
+                id: *id,
+                description: description.clone(),
+                current: None,
+                snapshot: Some(*status),
+            });
+        }
+    }
+    changes.sort_by_key(|c| c.id);
+    Ok(changes)
+}
+
+/// Replace the database at `db_path` with `snapshot`
+///
+/// The current contents are saved as a `pre-restore` snapshot first, and the
+/// restored database is migrated to the current schema. Returns the path of
+/// the pre-restore copy.
+pub fn restore(db_path: &Path, snapshot: &Path) -> Result<PathBuf> {
+    let version = migrations::version_of(snapshot)?;
+    if version > migrations::latest_version() {
+        bail!(
+            "Snapshot schema is version {}, newer than this opencode-forger supports ({})",
+            version,
+            migrations::latest_version()
+        );
+    }
+
+    let db = Database::open(db_path)?;
+    let safety = take_snapshot(&db, &snapshot_dir(db_path), "pre-restore")?;
+
+    let source = Connection::open_with_flags(snapshot, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open snapshot: {}", snapshot.display()))?;
+    {
+        let conn = db.connection();
+        let mut conn = conn.lock().unwrap();
+        copy(&source, &mut conn)
+            .with_context(|| format!("Failed to restore {}", snapshot.display()))?;
+    }
+    drop(db);
+
+    // Older snapshots are brought up to the current schema
+    Database::open(db_path)?;
+    Ok(safety)
+}
+
+/// Copy every page of `source` into `target`, a few at a time
+fn copy(source: &Connection, target: &mut Connection) -> Result<()> {
+    let backup = Backup::new(source, target)?;
+    backup.run_to_completion(256, Duration::from_millis(10), None)?;
+    Ok(())
+}
+
+/// Description and status of every feature, by id
+fn feature_statuses(conn: &Connection) -> Result<BTreeMap<i64, (String, FeatureStatus)>> {
+    let mut stmt = conn.prepare("SELECT id, description, status FROM features")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows
+        .into_iter()
+        .map(|(id, description, status)| (id, (description, status.parse().unwrap_or_default())))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::features::Feature;
+    use crate::db::test_utils::tests::setup_test_db;
+
+    fn insert(db: &Database, description: &str) -> i64 {
+        db.features()
+            .insert(&Feature::new("functional", description))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_restore_reports_and_reverts_status_changes() {
+        let (temp, db) = setup_test_db();
+        let db_path = temp.path().join("test.db");
+        let login = insert(&db, "Login");
+        let signup = insert(&db, "Signup");
+
+        let snapshot = take_snapshot(&db, &snapshot_dir(&db_path), "manual").unwrap();
+        db.features().mark_passing(login).unwrap();
+        let search = insert(&db, "Search");
+
+        let changes = diff_statuses(&db, &snapshot).unwrap();
+        let summary: Vec<_> = changes
+            .iter()
+            .map(|c| (c.id, c.current, c.snapshot))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    login,
+                    Some(FeatureStatus::Passing),
+                    Some(FeatureStatus::Pending)
+                ),
+                (search, Some(FeatureStatus::Pending), None),
+            ]
+        );
+        drop(db);
+
+        let safety = restore(&db_path, &snapshot).unwrap();
+        let db = Database::open(&db_path).unwrap();
+        let features = db.features().list_all().unwrap();
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0].status, FeatureStatus::Pending);
+        assert_eq!(features[1].id, Some(signup));
+
+        // The pre-restore copy still has the newer work
+        let undo = diff_statuses(&db, &safety).unwrap();
+        assert_eq!(undo.len(), 2);
+    }
+
+    #[test]
+    fn test_diff_reads_statuses_from_pre_status_snapshot() {
+        let (temp, db) = setup_test_db();
+        let login = insert(&db, "Login");
+        let signup = insert(&db, "Signup");
+        db.features().mark_passing(signup).unwrap();
+
+        // A schema version 2 snapshot only has the `passes` flag
+        let snapshot = temp.path().join("v2.db");
+        {
+            let conn = Connection::open(&snapshot).unwrap();
+            conn.execute_batch(crate::db::schema::SCHEMA).unwrap();
+            conn.execute_batch(crate::db::schema::MIGRATION_ADD_LAST_ERROR)
+                .unwrap();
+            conn.execute(
+                "INSERT INTO meta (key, value) VALUES (?1, '2')",
+                [migrations::SCHEMA_VERSION_KEY],
+            )
+            .unwrap();
+            conn.execute_batch(
+                "INSERT INTO features (id, category, description, passes, verification_command)
+                     VALUES (1, 'functional', 'Login', 1, 'npm test'),
+                            (2, 'functional', 'Signup', 0, 'npm test')",
+            )
+            .unwrap();
+        }
+        assert_eq!(migrations::version_of(&snapshot).unwrap(), 2);
+
+        let changes = diff_statuses(&db, &snapshot).unwrap();
+        let summary: Vec<_> = changes
+            .iter()
+            .map(|c| (c.id, c.current, c.snapshot))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    login,
+                    Some(FeatureStatus::Pending),
+                    Some(FeatureStatus::Passing)
+                ),
+                (
+                    signup,
+                    Some(FeatureStatus::Passing),
+                    Some(FeatureStatus::Pending)
+                ),
+            ]
+        );
+        // Reading the diff leaves the snapshot itself untouched
+        assert_eq!(migrations::version_of(&snapshot).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_rolling_snapshots() {
+        let (temp, db) = setup_test_db();
+        let dir = temp.path().join("snapshots");
+        let manual = take_snapshot(&db, &dir, "manual").unwrap();
+        let rolling: Vec<PathBuf> = (0..3)
+            .map(|_| take_snapshot(&db, &dir, ROLLING_LABEL).unwrap())
+            .collect();
+
+        assert_eq!(prune_snapshots(&dir, 2).unwrap(), 1);
+        assert!(manual.exists());
+        assert!(!rolling[0].exists());
+        assert!(rolling[1].exists() && rolling[2].exists());
+        assert_eq!(list_snapshots(&dir).unwrap().len(), 3);
+    }
+}
@@ -4,8 +4,11 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use super::schema;
-use super::{FeatureRepository, KnowledgeRepository, MetaRepository, SessionRepository};
+use super::migrations;
+use super::{
+    FeatureRepository, HistoryRepository, KnowledgeRepository, MetaRepository, SessionRepository,
+    VerificationRepository,
+};
 
 /// Database connection wrapper with thread-safe access
 #[derive(Clone)]
@@ -71,10 +74,60 @@ impl Database {
         MetaRepository::new(self.connection())
     }
 
+    /// Get feature history repository
+    pub fn history(&self) -> HistoryRepository {
+        HistoryRepository::new(self.connection())
+    }
+
     /// Get knowledge repository
     pub fn knowledge(&self) -> KnowledgeRepository {
         KnowledgeRepository::new(self.connection())
     }
+
+    /// Get verification run repository
+    pub fn verification(&self) -> VerificationRepository {
+        VerificationRepository::new(self.connection())
+    }
+
+    /// Write a consistent, self-contained copy of the database to `dest`
+    ///
+    /// Uses `VACUUM INTO`, so pending WAL content is included and the copy has
+    /// no `-wal`/`-shm` companions. Any existing file at `dest` is replaced.
+    ///
+    /// Worker snapshots are opened by another process straight away, so a
+    /// compacted single file is what they need. Backups use the page-level
+    /// backup API instead (see [`super::backup::backup`]), the same copy that
+    /// restores them into a live database.
+    pub fn snapshot_to(&self, dest: &Path) -> Result<()> {
+        clear_database_file(dest)?;
+
+        let dest_str = dest
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("Snapshot path contains invalid UTF-8"))?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute("VACUUM INTO ?1", [dest_str])
+            .with_context(|| format!("Failed to snapshot database to {}", dest.display()))?;
+        Ok(())
+    }
+}
+
+/// Make way for a new database file at `dest`
+///
+/// Creates the parent directory and removes any existing file along with its
+/// `-wal`/`-shm` companions, which SQLite would otherwise apply to the new copy.
+pub(super) fn clear_database_file(dest: &Path) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    for suffix in ["", "-wal", "-shm"] {
+        let path = PathBuf::from(format!("{}{}", dest.display(), suffix));
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to replace {}", path.display()))?;
+        }
+    }
+    Ok(())
 }
 
 /// @description Opens a SQLite connection without running migrations.
@@ -92,12 +145,8 @@ fn initialize_connection(conn: &Connection) -> Result<()> {
     // Enable WAL mode for better concurrency (multiple readers, single writer)
     conn.execute_batch("PRAGMA journal_mode=WAL;")?;
 
-    conn.execute_batch(schema::SCHEMA)
-        .context("Failed to initialize database schema")?;
-
-    // Run migrations for existing databases (safe to run multiple times)
-    // ALTER TABLE ADD COLUMN fails if column exists, which we ignore
-    let _ = conn.execute_batch(schema::MIGRATION_ADD_LAST_ERROR);
+    // Brings new and older databases up to the current schema version
+    migrations::run_pending(conn)?;
 
     Ok(())
 }
@@ -0,0 +1,556 @@
+//! Importers for feature backlogs kept outside the database
+//!
+//! Each importer turns a file into [`Feature`]s; [`FeatureRepository::import`]
+//! then writes them, resolving duplicates with a [`DuplicatePolicy`].
+//! Work that is already finished in the source (closed issues, checked
+//! boxes) is left out, and everything imported starts as pending.
+//!
+//! [`FeatureRepository::import`]: super::FeatureRepository::import
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+use super::models::{Feature, Priority};
+use crate::spec;
+
+/// Category for features whose source doesn't name one
+const DEFAULT_CATEGORY: &str = "functional";
+
+/// File formats `db import` understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// The legacy `feature_list.json` array
+    Legacy,
+    /// `gh issue list --json title,body,labels,state` or the REST API's issue list
+    GithubIssues,
+    /// `- [ ] item` checklist, with `##` headings as categories
+    Markdown,
+    /// CSV with a header row
+    Csv,
+    /// The `<core_features>` block of an `app_spec.md`
+    Spec,
+}
+
+impl ImportFormat {
+    pub const ALL: [ImportFormat; 5] = [
+        ImportFormat::Legacy,
+        ImportFormat::GithubIssues,
+        ImportFormat::Markdown,
+        ImportFormat::Csv,
+        ImportFormat::Spec,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportFormat::Legacy => "legacy",
+            ImportFormat::GithubIssues => "github",
+            ImportFormat::Markdown => "markdown",
+            ImportFormat::Csv => "csv",
+            ImportFormat::Spec => "spec",
+        }
+    }
+
+    /// Guess the format from the file extension and, where that's ambiguous, its content
+    pub fn detect(path: &Path, content: &str) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        match extension.as_str() {
+            "csv" => Ok(ImportFormat::Csv),
+            "json" => {
+                let first: Option<serde_json::Value> = serde_json::from_str::<Vec<_>>(content)
+                    .ok()
+                    .and_then(|items| items.into_iter().next());
+                if first.is_some_and(|item| item.get("title").is_some()) {
+                    Ok(ImportFormat::GithubIssues)
+                } else {
+                    Ok(ImportFormat::Legacy)
+                }
+            }
+            _ if content.contains("<core_features>") => Ok(ImportFormat::Spec),
+            "md" | "markdown" | "txt" => Ok(ImportFormat::Markdown),
+            _ => bail!(
+                "Can't tell the format of {}; pass --from ({})",
+                path.display(),
+                ImportFormat::ALL.map(|f| f.as_str()).join(", ")
+            ),
+        }
+    }
+
+    /// Parse a file's content into features
+    pub fn parse(&self, content: &str) -> Result<Vec<Feature>> {
+        match self {
+            ImportFormat::Legacy => {
+                serde_json::from_str(content).context("Invalid feature_list.json content")
+            }
+            ImportFormat::GithubIssues => parse_github_issues(content),
+            ImportFormat::Markdown => Ok(parse_markdown(content)),
+            ImportFormat::Csv => parse_csv(content),
+            ImportFormat::Spec => Ok(spec::parse_core_features(content)?
+                .into_iter()
+                .map(from_spec_feature)
+                .collect()),
+        }
+    }
+}
+
+impl std::str::FromStr for ImportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let normalized = value.trim().to_lowercase();
+        match normalized.as_str() {
+            "md" => return Ok(ImportFormat::Markdown),
+            "json" | "feature_list" => return Ok(ImportFormat::Legacy),
+            "github-issues" | "gh" => return Ok(ImportFormat::GithubIssues),
+            _ => {}
+        }
+        ImportFormat::ALL
+            .into_iter()
+            .find(|f| f.as_str() == normalized)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown import format '{}' (expected one of: {})",
+                    value,
+                    ImportFormat::ALL.map(|f| f.as_str()).join(", ")
+                )
+            })
+    }
+}
+
+/// What to do when an imported feature's description already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Leave the existing feature untouched
+    #[default]
+    Skip,
+    /// Fill in a missing verification command and append new steps
+    Merge,
+    /// Replace category and priority, and the verification command and
+    /// steps where the import has them
+    Overwrite,
+}
+
+impl DuplicatePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuplicatePolicy::Skip => "skip",
+            DuplicatePolicy::Merge => "merge",
+            DuplicatePolicy::Overwrite => "overwrite",
+        }
+    }
+}
+
+impl std::str::FromStr for DuplicatePolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "skip" => Ok(DuplicatePolicy::Skip),
+            "merge" => Ok(DuplicatePolicy::Merge),
+            "overwrite" => Ok(DuplicatePolicy::Overwrite),
+            _ => bail!(
+                "Unknown duplicate policy '{}' (expected skip, merge or overwrite)",
+                value
+            ),
+        }
+    }
+}
+
+/// Counts reported by an import
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub merged: usize,
+    pub overwritten: usize,
+    pub skipped: usize,
+}
+
+fn pending(category: &str, description: &str, priority: Priority, steps: Vec<String>) -> Feature {
+    Feature {
+        steps,
+        priority,
+        ..Feature::new(category, description)
+    }
+}
+
+/// A spec feature becomes one database feature; its sub-features are the steps
+fn from_spec_feature(feature: spec::Feature) -> Feature {
+    let description = if feature.description.is_empty() {
+        feature.name
+    } else {
+        format!("{}: {}", feature.name, feature.description)
+    };
+    pending(
+        DEFAULT_CATEGORY,
+        &description,
+        feature.priority,
+        feature.sub_features,
+    )
+}
+
+#[derive(Deserialize)]
+struct GithubIssue {
+    title: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    labels: Vec<GithubLabel>,
+    #[serde(default)]
+    state: Option<String>,
+    /// Only present on pull requests in the REST API's issue list
+    #[serde(default)]
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GithubLabel {
+    Named { name: String },
+    Plain(String),
+}
+
+impl GithubLabel {
+    fn name(&self) -> &str {
+        match self {
+            GithubLabel::Named { name } | GithubLabel::Plain(name) => name,
+        }
+    }
+}
+
+/// Open issues become features: the title is the description, checklist
+/// items in the body are the steps, a `priority:`/`P0`-`P3` label sets the
+/// priority and the first other label the category
+fn parse_github_issues(content: &str) -> Result<Vec<Feature>> {
+    let issues: Vec<GithubIssue> =
+        serde_json::from_str(content).context("Invalid GitHub issues JSON")?;
+
+    Ok(issues
+        .into_iter()
+        .filter(|issue| issue.pull_request.is_none())
+        .filter(|issue| {
+            !issue
+                .state
+                .as_deref()
+                .is_some_and(|s| s.eq_ignore_ascii_case("closed"))
+        })
+        .map(|issue| {
+            let mut priority = None;
+            let mut category = None;
+            for label in &issue.labels {
+                let name = label.name();
+                let stripped = name
+                    .to_lowercase()
+                    .trim_start_matches("priority")
+                    .trim_start_matches([':', '/', '-', ' '])
+                    .to_string();
+                match stripped.parse::<Priority>() {
+                    Ok(p) if priority.is_none() => priority = Some(p),
+                    Ok(_) => {}
+                    Err(_) if category.is_none() => category = Some(name.to_lowercase()),
+                    Err(_) => {}
+                }
+            }
+            let steps = issue
+                .body
+                .as_deref()
+                .map(|body| {
+                    body.lines()
+                        .filter_map(checklist_item)
+                        .map(|(_, s)| s)
+                        .collect()
+                })
+                .unwrap_or_default();
+            pending(
+                category.as_deref().unwrap_or(DEFAULT_CATEGORY),
+                issue.title.trim(),
+                priority.unwrap_or_default(),
+                steps,
+            )
+        })
+        .collect())
+}
+
+/// `- [ ] text` / `* [x] text` -> (checked, text)
+fn checklist_item(line: &str) -> Option<(bool, String)> {
+    let rest = line.trim().strip_prefix(['-', '*'])?.trim_start();
+    let (checked, text) = if let Some(text) = rest.strip_prefix("[ ]") {
+        (false, text)
+    } else if let Some(text) = rest
+        .strip_prefix("[x]")
+        .or_else(|| rest.strip_prefix("[X]"))
+    {
+        (true, text)
+    } else {
+        return None;
+    };
+    let text = text.trim();
+    (!text.is_empty()).then(|| (checked, text.to_string()))
+}
+
+/// Unindented unchecked items are features; indented list items below one are its steps
+fn parse_markdown(content: &str) -> Vec<Feature> {
+    let mut features: Vec<Feature> = Vec::new();
+    let mut category = DEFAULT_CATEGORY.to_string();
+    // Steps of a checked (skipped) item must not attach to the previous feature
+    let mut collecting = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(heading) = trimmed.strip_prefix('#') {
+            category = heading.trim_start_matches('#').trim().to_lowercase();
+            collecting = false;
+            continue;
+        }
+
+        let indented = line.starts_with([' ', '\t']);
+        if !indented {
+            match checklist_item(line) {
+                Some((false, text)) => {
+                    features.push(pending(&category, &text, Priority::default(), Vec::new()));
+                    collecting = true;
+                }
+                Some((true, _)) => collecting = false,
+                None => {}
+            }
+            continue;
+        }
+
+        if collecting {
+            let step = checklist_item(line).map(|(_, s)| s).or_else(|| {
+                trimmed
+                    .strip_prefix(['-', '*'])
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+            });
+            if let (Some(step), Some(feature)) = (step, features.last_mut()) {
+                feature.steps.push(step);
+            }
+        }
+    }
+
+    features
+}
+
+/// Header row names the columns: `description` (or `title`) is required;
+/// `category`, `priority`, `verification_command` (or `verify`) and `steps`
+/// (separated by `;` or newlines) are optional
+fn parse_csv(content: &str) -> Result<Vec<Feature>> {
+    let mut records = csv_records(content)?.into_iter();
+    let header: Vec<String> = records
+        .next()
+        .context("CSV file is empty")?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+
+    let description_col = column(&["description", "title"])
+        .context("CSV header needs a 'description' or 'title' column")?;
+    let category_col = column(&["category"]);
+    let priority_col = column(&["priority"]);
+    let verify_col = column(&["verification_command", "verify", "verification"]);
+    let steps_col = column(&["steps"]);
+
+    let mut features = Vec::new();
+    for (index, record) in records.enumerate() {
+        let field = |col: Option<usize>| {
+            col.and_then(|c| record.get(c))
+                .map(|v| v.trim())
+                .filter(|v| !v.is_empty())
+        };
+        let Some(description) = field(Some(description_col)) else {
+            continue;
+        };
+        let priority = match field(priority_col) {
+            Some(p) => p
+                .parse()
+                .with_context(|| format!("CSV record {}", index + 2))?,
+            None => Priority::default(),
+        };
+        let steps = field(steps_col)
+            .map(|s| {
+                s.split([';', '\n'])
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut feature = pending(
+            field(category_col).unwrap_or(DEFAULT_CATEGORY),
+            description,
+            priority,
+            steps,
+        );
+        feature.verification_command = field(verify_col).map(String::from);
+        features.push(feature);
+    }
+
+    Ok(features)
+}
+
+/// Split CSV content into records (RFC 4180: quoted fields may hold commas,
+/// doubled quotes and line breaks)
+fn csv_records(content: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if in_quotes {
+        bail!("CSV ends inside a quoted field");
+    }
+    record.push(field);
+    if record.iter().any(|f| !f.is_empty()) {
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::features::FeatureStatus;
+    use crate::db::test_utils::tests::setup_test_db;
+
+    #[test]
+    fn test_github_issues() {
+        let json = r#"[
+            {"number": 1, "title": "Login form", "state": "OPEN",
+             "body": "Steps:\n- [ ] Open /login\n- [x] Submit",
+             "labels": [{"name": "priority: high"}, {"name": "UI"}]},
+            {"number": 2, "title": "Old work", "state": "CLOSED", "body": "", "labels": []},
+            {"number": 3, "title": "A PR", "state": "open", "pull_request": {}}
+        ]"#;
+        let features = ImportFormat::GithubIssues.parse(json).unwrap();
+
+        assert_eq!(features.len(), 1);
+        assert_eq!(features[0].description, "Login form");
+        assert_eq!(features[0].category, "ui");
+        assert_eq!(features[0].priority, Priority::High);
+        assert_eq!(features[0].steps, vec!["Open /login", "Submit"]);
+    }
+
+    #[test]
+    fn test_markdown_checklist() {
+        let markdown = "# Backlog\n\n## Style\n- [ ] Dark mode\n  - Toggle in header\n  - [ ] Persists\n- [x] Done already\n  - not a step\n\n## API\n* [ ] Health endpoint\n";
+        let features = ImportFormat::Markdown.parse(markdown).unwrap();
+
+        let summary: Vec<_> = features
+            .iter()
+            .map(|f| (f.category.as_str(), f.description.as_str(), f.steps.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("style", "Dark mode", 2), ("api", "Health endpoint", 0)]
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = "Title,Category,Priority,Verify,Steps\r\n\"Search, with filters\",functional,P0,cargo test search,\"Type query; \"\"Apply\"\" filter\"\n,,,,\nExport,,,,\n";
+        let features = ImportFormat::Csv.parse(csv).unwrap();
+
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0].description, "Search, with filters");
+        assert_eq!(features[0].priority, Priority::Critical);
+        assert_eq!(
+            features[0].verification_command.as_deref(),
+            Some("cargo test search")
+        );
+        assert_eq!(features[0].steps, vec!["Type query", "\"Apply\" filter"]);
+        assert_eq!(features[1].category, DEFAULT_CATEGORY);
+        assert!(ImportFormat::Csv.parse("name\nx\n").is_err());
+    }
+
+    #[test]
+    fn test_detect() {
+        let detect = |name: &str, content: &str| ImportFormat::detect(Path::new(name), content);
+        assert_eq!(detect("a.csv", "").unwrap(), ImportFormat::Csv);
+        assert_eq!(
+            detect("issues.json", r#"[{"title": "x"}]"#).unwrap(),
+            ImportFormat::GithubIssues
+        );
+        assert_eq!(
+            detect("feature_list.json", "[]").unwrap(),
+            ImportFormat::Legacy
+        );
+        assert_eq!(
+            detect("app_spec.md", "<core_features></core_features>").unwrap(),
+            ImportFormat::Spec
+        );
+        assert_eq!(
+            detect("TODO.md", "- [ ] x").unwrap(),
+            ImportFormat::Markdown
+        );
+        assert!(detect("backlog", "").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_policies() {
+        let (_temp, db) = setup_test_db();
+        let repo = db.features();
+        let mut original = pending("functional", "Login", Priority::Low, vec!["Open".into()]);
+        original.verification_command = Some("npm test login".to_string());
+        let id = repo.insert(&original).unwrap();
+        repo.mark_passing(id).unwrap();
+
+        let incoming = vec![
+            pending(
+                "auth",
+                "Login",
+                Priority::High,
+                vec!["Open".into(), "Submit".into()],
+            ),
+            pending("functional", "Logout", Priority::Medium, vec![]),
+        ];
+
+        let summary = repo.import(&incoming, DuplicatePolicy::Skip).unwrap();
+        assert_eq!((summary.added, summary.skipped), (1, 1));
+
+        let summary = repo.import(&incoming, DuplicatePolicy::Merge).unwrap();
+        assert_eq!(summary.merged, 2);
+        let login = &repo.list_all().unwrap()[0];
+        assert_eq!(login.steps, vec!["Open", "Submit"]);
+        assert_eq!(login.category, "functional");
+
+        let summary = repo.import(&incoming, DuplicatePolicy::Overwrite).unwrap();
+        assert_eq!(summary.overwritten, 2);
+        let login = &repo.list_all().unwrap()[0];
+        assert_eq!(login.category, "auth");
+        assert_eq!(login.priority, Priority::High);
+        assert_eq!(
+            login.verification_command.as_deref(),
+            Some("npm test login")
+        );
+        assert_eq!(login.status, FeatureStatus::Passing);
+    }
+}
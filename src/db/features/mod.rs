@@ -1,14 +1,16 @@
 //! Feature repository module
 
+pub mod import;
 pub mod models;
 pub mod repo;
 
-pub use models::Feature;
+pub use models::{Feature, FeatureStatus, VerificationStep};
 pub use repo::FeatureRepository;
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::features::import::DuplicatePolicy;
     use crate::db::test_utils::tests::setup_test_db;
 
     #[test]
@@ -17,13 +19,9 @@ mod tests {
         let repo = db.features();
 
         let feature = Feature {
-            id: None,
-            category: "functional".to_string(),
-            description: "Test feature".to_string(),
             steps: vec!["Step 1".to_string(), "Step 2".to_string()],
-            passes: false,
             verification_command: Some("echo test".to_string()),
-            last_error: None,
+            ..Feature::new("functional", "Test feature")
         };
 
         let id = repo.insert(&feature).unwrap();
@@ -39,23 +37,15 @@ mod tests {
         let (_temp, db) = setup_test_db();
         let repo = db.features();
 
-        let feature = Feature {
-            id: None,
-            category: "functional".to_string(),
-            description: "Test feature".to_string(),
-            steps: vec![],
-            passes: false,
-            verification_command: None,
-            last_error: None,
-        };
+        let feature = Feature::new("functional", "Test feature");
 
-        repo.insert(&feature).unwrap();
+        let id = repo.insert(&feature).unwrap();
 
         let (passing, remaining) = repo.count().unwrap();
         assert_eq!(passing, 0);
         assert_eq!(remaining, 1);
 
-        repo.mark_passing("Test feature").unwrap();
+        repo.mark_passing(id).unwrap();
 
         let (passing, remaining) = repo.count().unwrap();
         assert_eq!(passing, 1);
@@ -70,13 +60,13 @@ mod tests {
         // Insert some features
         for i in 0..5 {
             let feature = Feature {
-                id: None,
-                category: "functional".to_string(),
-                description: format!("Feature {}", i),
-                steps: vec![],
-                passes: i % 2 == 0, // 0, 2, 4 pass; 1, 3 fail
-                verification_command: None,
-                last_error: None,
+                passes: i % 2 == 0,
+                status: if i % 2 == 0 {
+                    FeatureStatus::Passing
+                } else {
+                    FeatureStatus::Pending
+                },
+                ..Feature::new("functional", &format!("Feature {}", i))
             };
             repo.insert(&feature).unwrap();
         }
@@ -85,4 +75,227 @@ mod tests {
         assert_eq!(passing, 3);
         assert_eq!(remaining, 2);
     }
+
+    fn pending(description: &str) -> Feature {
+        Feature {
+            verification_command: Some("true".to_string()),
+            ..Feature::new("functional", description)
+        }
+    }
+
+    #[test]
+    fn test_status_and_passes_stay_in_sync() {
+        let (_temp, db) = setup_test_db();
+        let repo = db.features();
+        let id = repo.insert(&pending("Synced")).unwrap();
+
+        // Legacy writers only touch `passes`
+        db.write_query(&format!("UPDATE features SET passes = 1 WHERE id = {}", id))
+            .unwrap();
+        assert_eq!(repo.list_all().unwrap()[0].status, FeatureStatus::Passing);
+
+        repo.set_status(id, FeatureStatus::Manual).unwrap();
+        let feature = &repo.list_all().unwrap()[0];
+        assert!(feature.passes);
+        assert_eq!(feature.status, FeatureStatus::Manual);
+
+        repo.set_status(id, FeatureStatus::Quarantined).unwrap();
+        assert!(!repo.list_all().unwrap()[0].passes);
+        assert_eq!(repo.count().unwrap(), (0, 1));
+    }
+
+    #[test]
+    fn test_mark_blocked_and_count_by_status() {
+        let (_temp, db) = setup_test_db();
+        let repo = db.features();
+        let blocked = repo.insert(&pending("Blocked one")).unwrap();
+        let passing = repo.insert(&pending("Passing one")).unwrap();
+        repo.insert(&pending("Still pending")).unwrap();
+
+        repo.mark_blocked(blocked, "Security blocked: rm -rf")
+            .unwrap();
+        repo.mark_passing(passing).unwrap();
+
+        let counts = repo.count_by_status().unwrap();
+        let count = |status| counts.iter().find(|(s, _)| *s == status).unwrap().1;
+        assert_eq!(count(FeatureStatus::Blocked), 1);
+        assert_eq!(count(FeatureStatus::Passing), 1);
+        assert_eq!(count(FeatureStatus::Pending), 1);
+        assert_eq!(count(FeatureStatus::Quarantined), 0);
+
+        let blocked = repo.list_by_status(FeatureStatus::Blocked).unwrap();
+        assert_eq!(blocked[0].description, "Blocked one");
+        assert_eq!(
+            blocked[0].last_error.as_deref(),
+            Some("Security blocked: rm -rf")
+        );
+    }
+
+    #[test]
+    fn test_key_survives_rewording() {
+        let (_temp, db) = setup_test_db();
+        let repo = db.features();
+        let id = repo.insert(&pending("User can log in")).unwrap();
+        let twin = repo.insert(&pending("User can log in!")).unwrap();
+
+        repo.mark_passing(id).unwrap();
+        repo.set_description(id, "Users sign in with email and password")
+            .unwrap();
+
+        let feature = repo.get_by_key("user-can-log-in").unwrap().unwrap();
+        assert_eq!(feature.id, Some(id));
+        assert_eq!(feature.description, "Users sign in with email and password");
+        assert_eq!(feature.status, FeatureStatus::Passing);
+        assert_eq!(repo.get(twin).unwrap().unwrap().key, "user-can-log-in-2");
+        assert!(repo.set_description(twin, &feature.description).is_err());
+    }
+
+    #[test]
+    fn test_status_parse() {
+        assert_eq!(
+            "in_progress".parse::<FeatureStatus>().unwrap(),
+            FeatureStatus::InProgress
+        );
+        for status in FeatureStatus::ALL {
+            assert_eq!(status.as_str().parse::<FeatureStatus>().unwrap(), status);
+        }
+        assert!("done".parse::<FeatureStatus>().is_err());
+    }
+
+    #[test]
+    fn test_status_backfilled_from_passes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("legacy.db");
+        {
+            let conn = rusqlite::Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE features (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    category TEXT NOT NULL,
+                    description TEXT NOT NULL UNIQUE,
+                    passes INTEGER DEFAULT 0,
+                    verification_command TEXT,
+                    last_error TEXT,
+                    created_at TEXT DEFAULT (datetime('now')),
+                    updated_at TEXT DEFAULT (datetime('now'))
+                );
+                INSERT INTO features (category, description, passes, verification_command, last_error) VALUES
+                    ('f', 'verified', 1, 'cargo test', NULL),
+                    ('f', 'manual only', 1, '', NULL),
+                    ('f', 'broken', 0, 'cargo test', 'assertion failed'),
+                    ('f', 'blocked', 0, 'rm -rf /', 'Security blocked: rm'),
+                    ('f', 'untouched', 0, 'cargo test', NULL);",
+            )
+            .unwrap();
+        }
+
+        let db = crate::db::Database::open(&path).unwrap();
+        let statuses: Vec<(String, FeatureStatus)> = db
+            .features()
+            .list_all()
+            .unwrap()
+            .into_iter()
+            .map(|f| (f.description, f.status))
+            .collect();
+
+        assert_eq!(
+            statuses,
+            vec![
+                ("verified".to_string(), FeatureStatus::Passing),
+                ("manual only".to_string(), FeatureStatus::Manual),
+                ("broken".to_string(), FeatureStatus::Failing),
+                ("blocked".to_string(), FeatureStatus::Blocked),
+                ("untouched".to_string(), FeatureStatus::Pending),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edit_feature_fields() {
+        let (_temp, db) = setup_test_db();
+        let repo = db.features();
+        let id = repo.insert(&pending("Editable")).unwrap();
+
+        let steps = vec!["Open page".to_string(), "Click save".to_string()];
+        assert!(repo.replace_steps(id, &steps).unwrap());
+        assert!(repo.replace_steps(id, &steps[1..]).unwrap());
+        assert!(repo
+            .set_verification_command(id, "cargo test save")
+            .unwrap());
+        assert!(repo.set_category(id, "ui").unwrap());
+        assert!(!repo.set_category(id + 1, "ui").unwrap());
+        assert!(!repo.replace_steps(id + 1, &steps).unwrap());
+
+        let feature = repo.get(id).unwrap().unwrap();
+        assert_eq!(feature.steps, vec!["Click save".to_string()]);
+        assert_eq!(
+            feature.verification_command.as_deref(),
+            Some("cargo test save")
+        );
+        assert_eq!(feature.category, "ui");
+        assert!(repo.get(id + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_verification_steps_replace_the_command() {
+        let (_temp, db) = setup_test_db();
+        let repo = db.features();
+        let id = repo.insert(&pending("Pipelined")).unwrap();
+        repo.set_verification_command(id, "cargo test").unwrap();
+
+        let fallback = repo.get(id).unwrap().unwrap().verification_pipeline();
+        assert_eq!(
+            fallback,
+            vec![VerificationStep::new(
+                VerificationStep::DEFAULT_NAME,
+                "cargo test"
+            )]
+        );
+
+        let mut unit = VerificationStep::new("unit", "cargo test --lib");
+        unit.timeout_secs = Some(60);
+        unit.env.insert("RUST_LOG".to_string(), "debug".to_string());
+        let steps = vec![VerificationStep::new("build", "cargo build"), unit];
+        assert!(repo.set_verification_steps(id, &steps).unwrap());
+
+        let feature = repo.get(id).unwrap().unwrap();
+        assert_eq!(feature.verification_pipeline(), steps);
+
+        // The feature's timeout fills in for steps without their own
+        assert!(repo.set_verification_timeout(id, Some(90)).unwrap());
+        let timeouts: Vec<Option<u64>> = repo
+            .get(id)
+            .unwrap()
+            .unwrap()
+            .verification_pipeline()
+            .iter()
+            .map(|step| step.timeout_secs)
+            .collect();
+        assert_eq!(timeouts, vec![Some(90), Some(60)]);
+        assert_eq!(
+            feature.verification_summary().as_deref(),
+            Some("build: cargo build → unit: cargo test --lib")
+        );
+
+        assert!(repo.set_verification_steps(id, &[]).unwrap());
+        assert!(repo.set_verification_timeout(id, None).unwrap());
+        let feature = repo.get(id).unwrap().unwrap();
+        assert!(feature.verification_steps.is_empty());
+        assert_eq!(feature.verification_pipeline(), fallback);
+
+        // Broken pipelines are rejected when written, not when they run
+        let mut broken = VerificationStep::new("e2e", "npm run e2e");
+        broken.expect_output = Some("passed (".to_string());
+        let error = repo
+            .set_verification_steps(id, &[broken.clone()])
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("invalid expect_output"),
+            "{}",
+            error
+        );
+        let mut imported = pending("Imported with a broken step");
+        imported.verification_steps = vec![broken];
+        assert!(repo.import(&[imported], DuplicatePolicy::Skip).is_err());
+    }
 }
@@ -1,4 +1,112 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub use crate::spec::Priority;
+
+/// Lifecycle state of a feature
+///
+/// Stored in `features.status`. The legacy `passes` column is kept in sync by
+/// triggers: it is 1 exactly when the status counts as done (`passing` or `manual`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureStatus {
+    /// Never attempted
+    #[default]
+    Pending,
+    /// Picked up by a supervisor or worker
+    InProgress,
+    /// Automated verification passed
+    Passing,
+    /// Verification failed; `last_error` holds the details
+    Failing,
+    /// Verification command was rejected by the security policy
+    Blocked,
+    /// Parked by a human; skipped by the supervisor and regression checks
+    Quarantined,
+    /// Implemented without an automated verification command; counts as done
+    /// but is never re-verified automatically
+    Manual,
+}
+
+impl FeatureStatus {
+    /// All states, in display order
+    pub const ALL: [FeatureStatus; 7] = [
+        FeatureStatus::Pending,
+        FeatureStatus::InProgress,
+        FeatureStatus::Passing,
+        FeatureStatus::Failing,
+        FeatureStatus::Blocked,
+        FeatureStatus::Quarantined,
+        FeatureStatus::Manual,
+    ];
+
+    /// Return the representation stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeatureStatus::Pending => "pending",
+            FeatureStatus::InProgress => "in_progress",
+            FeatureStatus::Passing => "passing",
+            FeatureStatus::Failing => "failing",
+            FeatureStatus::Blocked => "blocked",
+            FeatureStatus::Quarantined => "quarantined",
+            FeatureStatus::Manual => "manual",
+        }
+    }
+
+    /// Whether the feature counts as complete (the legacy `passes = 1`)
+    pub fn is_done(&self) -> bool {
+        matches!(self, FeatureStatus::Passing | FeatureStatus::Manual)
+    }
+
+    /// Whether the supervisor should pick the feature up
+    ///
+    /// Blocked features wait for a human like quarantined ones: re-running a
+    /// command the security policy rejects can only be rejected again.
+    pub fn is_actionable(&self) -> bool {
+        matches!(
+            self,
+            FeatureStatus::Pending | FeatureStatus::InProgress | FeatureStatus::Failing
+        )
+    }
+
+    /// Single-character marker for compact listings
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            FeatureStatus::Pending => "○",
+            FeatureStatus::InProgress => "◐",
+            FeatureStatus::Passing => "✓",
+            FeatureStatus::Failing => "✗",
+            FeatureStatus::Blocked => "⊘",
+            FeatureStatus::Quarantined => "⏸",
+            FeatureStatus::Manual => "✋",
+        }
+    }
+}
+
+impl std::str::FromStr for FeatureStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let normalized = value.trim().to_lowercase().replace('-', "_");
+        FeatureStatus::ALL
+            .into_iter()
+            .find(|s| s.as_str() == normalized)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown feature status '{}' (expected one of: {})",
+                    value,
+                    FeatureStatus::ALL.map(|s| s.as_str()).join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for FeatureStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 /// Feature data structure (matches the old JSON format)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,23 +115,204 @@ pub struct Feature {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<i64>,
 
+    /// Stable identifier, derived from the first description and kept when
+    /// the description is edited (assigned on insert when empty)
+    #[serde(default)]
+    pub key: String,
+
     /// Feature category (functional, style, integration, performance)
     pub category: String,
 
-    /// Human-readable description
+    /// Human-readable description (editable; use `key` to identify the feature)
     pub description: String,
 
     /// Verification steps
     pub steps: Vec<String>,
 
-    /// Whether this feature passes all tests
+    /// Whether this feature passes all tests (mirrors `status.is_done()`)
     pub passes: bool,
 
+    /// Lifecycle state (defaults from `passes` when importing old JSON)
+    #[serde(default)]
+    pub status: FeatureStatus,
+
+    /// Priority from the spec or imported backlog
+    #[serde(default)]
+    pub priority: Priority,
+
     /// Optional shell command for automated verification
     #[serde(skip_serializing_if = "Option::is_none")]
     pub verification_command: Option<String>,
 
+    /// Ordered verification pipeline; replaces `verification_command` when set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verification_steps: Vec<VerificationStep>,
+
+    /// Verification timeout for this feature, in seconds (overrides the global one)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_timeout_secs: Option<u64>,
+
     /// Last verification error (for auto-fix context)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_error: Option<String>,
 }
+
+impl Feature {
+    /// A pending feature with no steps or verification, not yet in the database
+    pub fn new(category: &str, description: &str) -> Self {
+        Self {
+            id: None,
+            key: String::new(),
+            category: category.to_string(),
+            description: description.to_string(),
+            steps: Vec::new(),
+            passes: false,
+            status: FeatureStatus::Pending,
+            priority: Priority::default(),
+            verification_command: None,
+            verification_steps: Vec::new(),
+            verification_timeout_secs: None,
+            last_error: None,
+        }
+    }
+
+    /// Database id of a feature loaded from the database
+    pub fn db_id(&self) -> anyhow::Result<i64> {
+        self.id
+            .ok_or_else(|| anyhow::anyhow!("Feature '{}' has no database id", self.description))
+    }
+
+    /// Steps that verify the feature: the pipeline, or the single command as one step
+    ///
+    /// Steps without a timeout of their own get the feature's timeout.
+    pub fn verification_pipeline(&self) -> Vec<VerificationStep> {
+        let mut steps = if self.verification_steps.is_empty() {
+            self.verification_command
+                .iter()
+                .filter(|cmd| !cmd.trim().is_empty())
+                .map(|cmd| VerificationStep::new(VerificationStep::DEFAULT_NAME, cmd))
+                .collect()
+        } else {
+            self.verification_steps.clone()
+        };
+        for step in &mut steps {
+            step.timeout_secs = step.timeout_secs.or(self.verification_timeout_secs);
+        }
+        steps
+    }
+
+    /// Whether the feature can be verified automatically
+    pub fn has_verification(&self) -> bool {
+        !self.verification_pipeline().is_empty()
+    }
+
+    /// One-line description of how the feature is verified, for prompts and logs
+    pub fn verification_summary(&self) -> Option<String> {
+        if self.verification_steps.is_empty() {
+            return self.verification_command.clone();
+        }
+        Some(
+            self.verification_steps
+                .iter()
+                .map(|step| format!("{}: {}", step.name, step.command))
+                .collect::<Vec<_>>()
+                .join(" → "),
+        )
+    }
+}
+
+/// One step of a feature's verification pipeline (build, lint, unit, e2e, ...)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationStep {
+    /// Short name reported when the step fails
+    pub name: String,
+
+    /// Shell command, checked against the security policy on its own
+    pub command: String,
+
+    /// Seconds before the step is killed (default: the feature's, then the global timeout)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+
+    /// Directory to run in, relative to the project root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workdir: Option<String>,
+
+    /// Extra environment variables for the step
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+
+    /// Exit code that counts as success
+    #[serde(default)]
+    pub expect_exit_code: i32,
+
+    /// Regex the step's stdout or stderr must match to pass
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expect_output: Option<String>,
+}
+
+impl VerificationStep {
+    /// Name given to a bare `verification_command`
+    pub const DEFAULT_NAME: &'static str = "verify";
+
+    /// A step with default timeout, directory, environment and expectations
+    pub fn new(name: &str, command: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            timeout_secs: None,
+            workdir: None,
+            env: BTreeMap::new(),
+            expect_exit_code: 0,
+            expect_output: None,
+        }
+    }
+
+    /// Reject a step that could only fail at verification time
+    ///
+    /// Whether the command is allowed is up to the security policy in force
+    /// when it runs, so only the step's own definition is checked here.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.command.trim().is_empty() {
+            anyhow::bail!("Step '{}' has no command", self.name);
+        }
+        if let Some(pattern) = &self.expect_output {
+            regex::Regex::new(pattern).map_err(|e| {
+                anyhow::anyhow!(
+                    "Step '{}' has an invalid expect_output pattern: {}",
+                    self.name,
+                    e
+                )
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Convert a description to a URL-safe slug
+pub fn slugify(s: &str) -> String {
+    s.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .take(5) // Limit length
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("User authentication"), "user-authentication");
+        assert_eq!(slugify("API: Login endpoint"), "api-login-endpoint");
+        assert_eq!(
+            slugify("very long feature description that goes on and on"),
+            "very-long-feature-description-that"
+        );
+    }
+}
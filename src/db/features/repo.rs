@@ -1,69 +1,166 @@
 use anyhow::{Context, Result};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 
-use super::models::Feature;
+use super::import::{DuplicatePolicy, ImportSummary};
+use super::models::{slugify, Feature, FeatureStatus, Priority, VerificationStep};
+use crate::db::history::{attributed, ChangeSource};
+
+/// Columns selected for `Feature` rows, in `query_features` order
+const FEATURE_COLUMNS: &str = "id, key, category, description, passes, verification_command, \
+                               last_error, status, priority, verification_steps, \
+                               verification_timeout_secs";
 
 /// Repository for feature CRUD operations
 pub struct FeatureRepository {
     conn: Arc<Mutex<Connection>>,
+    source: ChangeSource,
 }
 
 impl FeatureRepository {
     /// Create a new repository with the given connection
     pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
-        Self { conn }
+        Self {
+            conn,
+            source: ChangeSource::default(),
+        }
+    }
+
+    /// Attribute status changes made through this repository to `source`
+    pub fn with_source(mut self, source: ChangeSource) -> Self {
+        self.source = source;
+        self
     }
 
     /// Insert a new feature
     pub fn insert(&self, feature: &Feature) -> Result<i64> {
         let conn = self.conn.lock().unwrap();
 
+        // Old JSON only carries `passes`; a passing feature must not land as pending
+        let status = if feature.passes && !feature.status.is_done() {
+            FeatureStatus::Passing
+        } else {
+            feature.status
+        };
+
+        let key = unique_key(&conn, &feature.key, &feature.description)?;
         conn.execute(
-            "INSERT INTO features (category, description, passes, verification_command)
-             VALUES (?1, ?2, ?3, ?4)",
+            "INSERT INTO features (key, category, description, passes, status, verification_command,
+                                   priority, verification_steps, verification_timeout_secs)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             params![
+                key,
                 feature.category,
                 feature.description,
-                feature.passes as i32,
+                status.is_done() as i32,
+                status.as_str(),
                 feature.verification_command,
+                feature.priority.as_str(),
+                steps_json(&feature.verification_steps)?,
+                feature.verification_timeout_secs.map(|secs| secs as i64),
             ],
         )
         .context("Failed to insert feature")?;
 
         let feature_id = conn.last_insert_rowid();
 
-        // Insert steps
-        for (order, step) in feature.steps.iter().enumerate() {
-            conn.execute(
-                "INSERT INTO feature_steps (feature_id, step_order, step_text)
-                 VALUES (?1, ?2, ?3)",
-                params![feature_id, order as i32, step],
-            )
-            .context("Failed to insert feature step")?;
-        }
+        insert_steps(&conn, feature_id, &feature.steps, 0)?;
 
         Ok(feature_id)
     }
 
+    /// Get a feature by its stable key
+    pub fn get_by_key(&self, key: &str) -> Result<Option<Feature>> {
+        let conn = self.conn.lock().unwrap();
+        let id: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM features WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to look up feature key")?;
+        match id {
+            Some(id) => Ok(self
+                .query_features(&conn, &format!("WHERE id = {}", id))?
+                .into_iter()
+                .next()),
+            None => Ok(None),
+        }
+    }
+
     /// Get all features
     pub fn list_all(&self) -> Result<Vec<Feature>> {
         let conn = self.conn.lock().unwrap();
-        self.query_features(&conn, "SELECT * FROM features ORDER BY id")
+        self.query_features(&conn, "")
+    }
+
+    /// Get a feature by id
+    pub fn get(&self, id: i64) -> Result<Option<Feature>> {
+        let conn = self.conn.lock().unwrap();
+        Ok(self
+            .query_features(&conn, &format!("WHERE id = {}", id))?
+            .into_iter()
+            .next())
     }
 
     pub fn list_passing(&self) -> Result<Vec<Feature>> {
         let conn = self.conn.lock().unwrap();
-        self.query_features(
-            &conn,
-            "SELECT * FROM features WHERE passes != 0 ORDER BY id",
-        )
+        self.query_features(&conn, "WHERE passes != 0")
     }
 
     pub fn list_remaining(&self) -> Result<Vec<Feature>> {
         let conn = self.conn.lock().unwrap();
-        self.query_features(&conn, "SELECT * FROM features WHERE passes = 0 ORDER BY id")
+        self.query_features(&conn, "WHERE passes = 0")
+    }
+
+    /// List features in a given lifecycle state
+    pub fn list_by_status(&self, status: FeatureStatus) -> Result<Vec<Feature>> {
+        let conn = self.conn.lock().unwrap();
+        self.query_features(&conn, &format!("WHERE status = '{}'", status.as_str()))
+    }
+
+    /// List features the supervisor can work on, in the order it picks them
+    ///
+    /// Most important priority first, then by id.
+    pub fn list_actionable(&self) -> Result<Vec<Feature>> {
+        let statuses: Vec<String> = FeatureStatus::ALL
+            .iter()
+            .filter(|s| s.is_actionable())
+            .map(|s| format!("'{}'", s.as_str()))
+            .collect();
+        let conn = self.conn.lock().unwrap();
+        let mut features =
+            self.query_features(&conn, &format!("WHERE status IN ({})", statuses.join(", ")))?;
+        features.sort_by_key(|f| f.priority);
+        Ok(features)
+    }
+
+    /// Count features in every lifecycle state (zero counts included, in display order)
+    pub fn count_by_status(&self) -> Result<Vec<(FeatureStatus, usize)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT status, COUNT(*) FROM features GROUP BY status")
+            .context("Failed to prepare status count")?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })
+            .context("Failed to count features by status")?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(FeatureStatus::ALL
+            .into_iter()
+            .map(|status| {
+                let count = rows
+                    .iter()
+                    .filter(|(s, _)| s == status.as_str())
+                    .map(|(_, n)| *n as usize)
+                    .sum();
+                (status, count)
+            })
+            .collect())
     }
 
     pub fn count(&self) -> Result<(usize, usize)> {
@@ -88,35 +185,190 @@ impl FeatureRepository {
         Ok((passing as usize, remaining as usize))
     }
 
-    /// Mark a feature as passing by description (clears last_error)
-    pub fn mark_passing(&self, description: &str) -> Result<bool> {
+    /// Mark a feature as passing (clears last_error)
+    pub fn mark_passing(&self, id: i64) -> Result<bool> {
         let conn = self.conn.lock().unwrap();
 
+        let rows = attributed(&conn, self.source, |conn| {
+            conn.execute(
+                "UPDATE features SET passes = 1, status = 'passing', last_error = NULL
+                 WHERE id = ?1",
+                params![id],
+            )
+            .context("Failed to mark feature as passing")
+        })?;
+
+        Ok(rows > 0)
+    }
+
+    /// Mark a feature as failing
+    pub fn mark_failing(&self, id: i64) -> Result<bool> {
+        self.mark_failing_with_error(id, None)
+    }
+
+    /// Mark a feature as failing with an error message for auto-fix context
+    pub fn mark_failing_with_error(&self, id: i64, error: Option<&str>) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let rows = attributed(&conn, self.source, |conn| {
+            conn.execute(
+                "UPDATE features SET passes = 0, status = 'failing', last_error = ?2
+                 WHERE id = ?1",
+                params![id, error],
+            )
+            .context("Failed to mark feature as failing")
+        })?;
+
+        Ok(rows > 0)
+    }
+
+    /// Mark a feature as blocked by the security policy
+    pub fn mark_blocked(&self, id: i64, reason: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let rows = attributed(&conn, self.source, |conn| {
+            conn.execute(
+                "UPDATE features SET passes = 0, status = 'blocked', last_error = ?2
+                 WHERE id = ?1",
+                params![id, reason],
+            )
+            .context("Failed to mark feature as blocked")
+        })?;
+
+        Ok(rows > 0)
+    }
+
+    /// Set a feature's lifecycle state (`passes` follows via trigger)
+    pub fn set_status(&self, id: i64, status: FeatureStatus) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let rows = attributed(&conn, self.source, |conn| {
+            conn.execute(
+                "UPDATE features SET status = ?2 WHERE id = ?1",
+                params![id, status.as_str()],
+            )
+            .context("Failed to update feature status")
+        })?;
+
+        Ok(rows > 0)
+    }
+
+    /// Reword a feature; its id and key stay the same
+    pub fn set_description(&self, id: i64, description: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let taken: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM features WHERE description = ?1 AND id != ?2",
+                params![description, id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(other) = taken {
+            anyhow::bail!("Feature {} already has that description", other);
+        }
+
         let rows = conn
             .execute(
-                "UPDATE features SET passes = 1, last_error = NULL WHERE description = ?1",
-                params![description],
+                "UPDATE features SET description = ?2 WHERE id = ?1",
+                params![id, description],
+            )
+            .context("Failed to update feature description")?;
+
+        Ok(rows > 0)
+    }
+
+    /// Change a feature's priority
+    pub fn set_priority(&self, id: i64, priority: Priority) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let rows = conn
+            .execute(
+                "UPDATE features SET priority = ?2 WHERE id = ?1",
+                params![id, priority.as_str()],
             )
-            .context("Failed to mark feature as passing")?;
+            .context("Failed to update feature priority")?;
 
         Ok(rows > 0)
     }
 
-    /// Mark a feature as failing by description
-    pub fn mark_failing(&self, description: &str) -> Result<bool> {
-        self.mark_failing_with_error(description, None)
+    /// Replace a feature's verification steps
+    pub fn replace_steps(&self, id: i64, steps: &[String]) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+
+        let exists: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM features WHERE id = ?1)",
+            params![id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Ok(false);
+        }
+
+        tx.execute(
+            "DELETE FROM feature_steps WHERE feature_id = ?1",
+            params![id],
+        )
+        .context("Failed to remove feature steps")?;
+        insert_steps(&tx, id, steps, 0)?;
+        tx.commit()?;
+
+        Ok(true)
     }
 
-    /// Mark a feature as failing with an error message for auto-fix context
-    pub fn mark_failing_with_error(&self, description: &str, error: Option<&str>) -> Result<bool> {
+    /// Set the command that verifies a feature
+    pub fn set_verification_command(&self, id: i64, command: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let rows = conn
+            .execute(
+                "UPDATE features SET verification_command = ?2 WHERE id = ?1",
+                params![id, command],
+            )
+            .context("Failed to update verification command")?;
+
+        Ok(rows > 0)
+    }
+
+    /// Replace a feature's verification pipeline (empty falls back to the command)
+    pub fn set_verification_steps(&self, id: i64, steps: &[VerificationStep]) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let rows = conn
+            .execute(
+                "UPDATE features SET verification_steps = ?2 WHERE id = ?1",
+                params![id, steps_json(steps)?],
+            )
+            .context("Failed to update verification steps")?;
+
+        Ok(rows > 0)
+    }
+
+    /// Set or clear a feature's verification timeout
+    pub fn set_verification_timeout(&self, id: i64, timeout_secs: Option<u64>) -> Result<bool> {
         let conn = self.conn.lock().unwrap();
 
         let rows = conn
             .execute(
-                "UPDATE features SET passes = 0, last_error = ?2 WHERE description = ?1",
-                params![description, error],
+                "UPDATE features SET verification_timeout_secs = ?2 WHERE id = ?1",
+                params![id, timeout_secs.map(|secs| secs as i64)],
             )
-            .context("Failed to mark feature as failing")?;
+            .context("Failed to update verification timeout")?;
+
+        Ok(rows > 0)
+    }
+
+    /// Move a feature to another category
+    pub fn set_category(&self, id: i64, category: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+
+        let rows = conn
+            .execute(
+                "UPDATE features SET category = ?2 WHERE id = ?1",
+                params![id, category],
+            )
+            .context("Failed to update feature category")?;
 
         Ok(rows > 0)
     }
@@ -129,17 +381,126 @@ impl FeatureRepository {
         let features: Vec<Feature> = serde_json::from_str(&content)
             .with_context(|| format!("Failed to parse {}", path.display()))?;
 
-        let mut count = 0;
+        Ok(self.import(&features, DuplicatePolicy::Skip)?.added)
+    }
+
+    /// Import features in one transaction, resolving duplicates with `policy`
+    ///
+    /// Duplicates are found through the `description UNIQUE` constraint.
+    /// Existing features keep their status whatever the policy, so an import
+    /// never undoes verified work.
+    pub fn import(&self, features: &[Feature], policy: DuplicatePolicy) -> Result<ImportSummary> {
+        let conn = self.conn.lock().unwrap();
+        let tx = conn
+            .unchecked_transaction()
+            .context("Failed to start import")?;
+        let mut summary = ImportSummary::default();
+
         for feature in features {
-            // Skip if already exists (by description)
-            if self.exists_by_description(&feature.description)? {
+            let status = if feature.passes && !feature.status.is_done() {
+                FeatureStatus::Passing
+            } else {
+                feature.status
+            };
+            let exists: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM features WHERE description = ?1)",
+                params![feature.description],
+                |row| row.get(0),
+            )?;
+            let verification_steps = steps_json(&feature.verification_steps)?;
+            let key = if exists {
+                None
+            } else {
+                Some(unique_key(&tx, &feature.key, &feature.description)?)
+            };
+            let inserted = tx
+                .execute(
+                    "INSERT INTO features (key, category, description, passes, status, verification_command,
+                                           priority, verification_steps, verification_timeout_secs)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                     ON CONFLICT(description) DO NOTHING",
+                    params![
+                        key,
+                        feature.category,
+                        feature.description,
+                        status.is_done() as i32,
+                        status.as_str(),
+                        feature.verification_command,
+                        feature.priority.as_str(),
+                        verification_steps,
+                        feature.verification_timeout_secs.map(|secs| secs as i64),
+                    ],
+                )
+                .with_context(|| format!("Failed to import feature: {}", feature.description))?;
+
+            if inserted > 0 {
+                let id = tx.last_insert_rowid();
+                insert_steps(&tx, id, &feature.steps, 0)?;
+                summary.added += 1;
                 continue;
             }
-            self.insert(&feature)?;
-            count += 1;
+
+            let id: i64 = tx.query_row(
+                "SELECT id FROM features WHERE description = ?1",
+                params![feature.description],
+                |row| row.get(0),
+            )?;
+            match policy {
+                DuplicatePolicy::Skip => summary.skipped += 1,
+                DuplicatePolicy::Merge => {
+                    // Fill in what's missing, keep everything that's there
+                    tx.execute(
+                        "UPDATE features SET verification_command = ?2
+                         WHERE id = ?1 AND COALESCE(TRIM(verification_command), '') = ''",
+                        params![id, feature.verification_command],
+                    )?;
+                    tx.execute(
+                        "UPDATE features SET verification_steps = ?2
+                         WHERE id = ?1 AND verification_steps IS NULL",
+                        params![id, verification_steps],
+                    )?;
+                    let existing: Vec<String> = tx
+                        .prepare("SELECT step_text FROM feature_steps WHERE feature_id = ?1")?
+                        .query_map(params![id], |row| row.get(0))?
+                        .collect::<Result<_, _>>()?;
+                    let new_steps: Vec<String> = feature
+                        .steps
+                        .iter()
+                        .filter(|s| !existing.contains(s))
+                        .cloned()
+                        .collect();
+                    insert_steps(&tx, id, &new_steps, existing.len())?;
+                    summary.merged += 1;
+                }
+                DuplicatePolicy::Overwrite => {
+                    // Imported values win wherever the import provides them
+                    tx.execute(
+                        "UPDATE features SET category = ?2, priority = ?3,
+                             verification_command = COALESCE(?4, verification_command),
+                             verification_steps = COALESCE(?5, verification_steps)
+                         WHERE id = ?1",
+                        params![
+                            id,
+                            feature.category,
+                            feature.priority.as_str(),
+                            feature.verification_command,
+                            verification_steps,
+                        ],
+                    )?;
+                    if !feature.steps.is_empty() {
+                        tx.execute(
+                            "DELETE FROM feature_steps WHERE feature_id = ?1",
+                            params![id],
+                        )?;
+                        insert_steps(&tx, id, &feature.steps, 0)?;
+                    }
+                    summary.overwritten += 1;
+                }
+            }
         }
 
-        Ok(count)
+        tx.commit().context("Failed to commit import")?;
+        Ok(summary)
     }
 
     /// Export features to JSON format
@@ -166,19 +527,28 @@ impl FeatureRepository {
         Ok(count > 0)
     }
 
-    /// Helper to query features and load their steps
-    fn query_features(&self, conn: &Connection, sql: &str) -> Result<Vec<Feature>> {
-        let mut stmt = conn.prepare(sql).context("Failed to prepare query")?;
+    /// Helper to query features (with an optional `WHERE` clause) and load their steps
+    fn query_features(&self, conn: &Connection, filter: &str) -> Result<Vec<Feature>> {
+        let sql = format!(
+            "SELECT {} FROM features {} ORDER BY id",
+            FEATURE_COLUMNS, filter
+        );
+        let mut stmt = conn.prepare(&sql).context("Failed to prepare query")?;
 
         let feature_iter = stmt
             .query_map([], |row| {
                 Ok((
                     row.get::<_, i64>(0)?,            // id
-                    row.get::<_, String>(1)?,         // category
-                    row.get::<_, String>(2)?,         // description
-                    row.get::<_, i32>(3)? != 0,       // passes
-                    row.get::<_, Option<String>>(4)?, // verification_command
-                    row.get::<_, Option<String>>(5)?, // last_error
+                    row.get::<_, String>(1)?,         // key
+                    row.get::<_, String>(2)?,         // category
+                    row.get::<_, String>(3)?,         // description
+                    row.get::<_, i32>(4)? != 0,       // passes
+                    row.get::<_, Option<String>>(5)?, // verification_command
+                    row.get::<_, Option<String>>(6)?, // last_error
+                    row.get::<_, String>(7)?,         // status
+                    row.get::<_, String>(8)?,         // priority
+                    row.get::<_, Option<String>>(9)?, // verification_steps (JSON)
+                    row.get::<_, Option<i64>>(10)?,   // verification_timeout_secs
                 ))
             })
             .context("Failed to query features")?;
@@ -192,7 +562,20 @@ impl FeatureRepository {
         }
 
         // Now load steps for each feature
-        for (id, category, description, passes, verification_command, last_error) in feature_data {
+        for (
+            id,
+            key,
+            category,
+            description,
+            passes,
+            verification_command,
+            last_error,
+            status,
+            priority,
+            verification_steps,
+            verification_timeout_secs,
+        ) in feature_data
+        {
             // Load steps for this feature
             let mut step_stmt = conn
                 .prepare(
@@ -208,11 +591,21 @@ impl FeatureRepository {
 
             features.push(Feature {
                 id: Some(id),
+                key,
                 category,
                 description,
                 steps,
                 passes,
+                status: status.parse().unwrap_or_default(),
+                priority: priority.parse().unwrap_or_default(),
                 verification_command,
+                verification_steps: match verification_steps {
+                    Some(json) => serde_json::from_str(&json).with_context(|| {
+                        format!("Invalid verification steps for feature {}", id)
+                    })?,
+                    None => Vec::new(),
+                },
+                verification_timeout_secs: verification_timeout_secs.map(|secs| secs as u64),
                 last_error,
             });
         }
@@ -220,3 +613,66 @@ impl FeatureRepository {
         Ok(features)
     }
 }
+
+/// Key for a new feature: `preferred` if free, otherwise a free slug
+///
+/// Slugs of the description get a numeric suffix on collision, so two
+/// features that slugify alike still get distinct keys.
+pub(crate) fn unique_key(conn: &Connection, preferred: &str, description: &str) -> Result<String> {
+    let taken = |key: &str| -> Result<bool> {
+        conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM features WHERE key = ?1)",
+            params![key],
+            |row| row.get(0),
+        )
+        .context("Failed to check feature key")
+    };
+
+    if !preferred.is_empty() && !taken(preferred)? {
+        return Ok(preferred.to_string());
+    }
+
+    let base = match slugify(description) {
+        slug if slug.is_empty() => "feature".to_string(),
+        slug => slug,
+    };
+    let mut key = base.clone();
+    let mut suffix = 2;
+    while taken(&key)? {
+        key = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+    Ok(key)
+}
+
+/// Verification steps as stored in `features.verification_steps` (NULL when empty)
+///
+/// Every step is validated first, so a broken pipeline is rejected when it
+/// is written rather than when it runs.
+fn steps_json(steps: &[VerificationStep]) -> Result<Option<String>> {
+    if steps.is_empty() {
+        return Ok(None);
+    }
+    for step in steps {
+        step.validate()?;
+    }
+    Ok(Some(serde_json::to_string(steps)?))
+}
+
+/// Append steps to a feature, numbering them from `first_order`
+fn insert_steps(
+    conn: &Connection,
+    feature_id: i64,
+    steps: &[String],
+    first_order: usize,
+) -> Result<()> {
+    for (offset, step) in steps.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO feature_steps (feature_id, step_order, step_text)
+             VALUES (?1, ?2, ?3)",
+            params![feature_id, (first_order + offset) as i32, step],
+        )
+        .context("Failed to insert feature step")?;
+    }
+    Ok(())
+}
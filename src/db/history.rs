@@ -0,0 +1,236 @@
+//! Feature history repository
+//!
+//! Every feature status transition is recorded by a trigger (see
+//! `schema::FEATURE_HISTORY`), so the timeline also covers changes made with
+//! `db exec`. This module reads that timeline and lets code paths attribute
+//! the changes they make.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use std::sync::{Arc, Mutex};
+
+use super::features::FeatureStatus;
+
+/// Who caused a feature status transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChangeSource {
+    /// Verification run by the supervisor loop
+    #[default]
+    Supervisor,
+    /// Regression check of previously passing features
+    Regression,
+    /// Parallel coordinator merging a worker's result
+    Parallel,
+    /// A `db` subcommand such as `mark-pass` or `set-status`
+    Cli,
+    /// Raw SQL, e.g. `db exec` from an agent
+    Sql,
+}
+
+impl ChangeSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeSource::Supervisor => "supervisor",
+            ChangeSource::Regression => "regression",
+            ChangeSource::Parallel => "parallel",
+            ChangeSource::Cli => "cli",
+            ChangeSource::Sql => "sql",
+        }
+    }
+}
+
+/// One recorded status transition
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub feature_id: i64,
+    pub from_status: Option<FeatureStatus>,
+    pub to_status: FeatureStatus,
+    pub source: String,
+    pub session_id: Option<i64>,
+    pub commit_hash: Option<String>,
+    pub error: Option<String>,
+    pub created_at: String,
+}
+
+/// Run `change` with its status transitions attributed to `source`
+///
+/// The attribution and the change share one transaction, and the context is
+/// reset before commit, so other connections only ever see the `sql` default.
+pub(crate) fn attributed<T>(
+    conn: &Connection,
+    source: ChangeSource,
+    change: impl FnOnce(&Connection) -> Result<T>,
+) -> Result<T> {
+    let tx = conn
+        .unchecked_transaction()
+        .context("Failed to start attributed change")?;
+    set_source(&tx, source)?;
+    let result = change(&tx)?;
+    set_source(&tx, ChangeSource::Sql)?;
+    tx.commit()?;
+    Ok(result)
+}
+
+/// Attribute transitions made on this connection until the source is reset
+///
+/// Only call inside a transaction and reset to [`ChangeSource::Sql`] before
+/// committing; [`attributed`] does both.
+pub(crate) fn set_source(conn: &Connection, source: ChangeSource) -> Result<()> {
+    conn.execute(
+        "UPDATE feature_history_context SET source = ?1 WHERE id = 1",
+        params![source.as_str()],
+    )
+    .context("Failed to set feature history source")?;
+    Ok(())
+}
+
+/// Repository for the feature status timeline
+pub struct HistoryRepository {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl HistoryRepository {
+    /// Create a new repository with the given connection
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+
+    /// Status transitions of a feature, oldest first
+    pub fn list_for_feature(&self, feature_id: i64) -> Result<Vec<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, feature_id, from_status, to_status, source, session_id,
+                    commit_hash, error, created_at
+             FROM feature_history WHERE feature_id = ?1 ORDER BY id",
+        )?;
+
+        let entries = stmt
+            .query_map(params![feature_id], |row| {
+                Ok(HistoryEntry {
+                    id: row.get(0)?,
+                    feature_id: row.get(1)?,
+                    from_status: row
+                        .get::<_, Option<String>>(2)?
+                        .and_then(|s| s.parse().ok()),
+                    to_status: row.get::<_, String>(3)?.parse().unwrap_or_default(),
+                    source: row.get(4)?,
+                    session_id: row.get(5)?,
+                    commit_hash: row.get(6)?,
+                    error: row.get(7)?,
+                    created_at: row.get(8)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to load feature history")?;
+
+        Ok(entries)
+    }
+
+    /// Record the commit behind a feature's latest transition
+    ///
+    /// Leaves entries that already carry a commit untouched.
+    pub fn attach_commit(&self, feature_id: i64, commit_hash: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let rows = conn
+            .execute(
+                "UPDATE feature_history SET commit_hash = ?2
+                 WHERE id = (SELECT MAX(id) FROM feature_history WHERE feature_id = ?1)
+                   AND commit_hash IS NULL",
+                params![feature_id, commit_hash],
+            )
+            .context("Failed to attach commit to feature history")?;
+        Ok(rows > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::features::Feature;
+    use crate::db::test_utils::tests::setup_test_db;
+
+    fn insert_feature(db: &crate::db::Database) -> i64 {
+        db.features()
+            .insert(&Feature {
+                verification_command: Some("true".to_string()),
+                ..Feature::new("functional", "Login form")
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_transitions_are_attributed() {
+        let (_temp, db) = setup_test_db();
+        let id = insert_feature(&db);
+        let session_id = db.sessions().start_session(1, 0).unwrap();
+
+        db.features().mark_passing(id).unwrap();
+        db.features()
+            .with_source(ChangeSource::Regression)
+            .mark_failing_with_error(id, Some("assertion failed"))
+            .unwrap();
+        db.write_query(&format!("UPDATE features SET passes = 1 WHERE id = {}", id))
+            .unwrap();
+
+        let history = db.history().list_for_feature(id).unwrap();
+        let summary: Vec<_> = history
+            .iter()
+            .map(|e| (e.from_status, e.to_status, e.source.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    Some(FeatureStatus::Pending),
+                    FeatureStatus::Passing,
+                    "supervisor"
+                ),
+                (
+                    Some(FeatureStatus::Passing),
+                    FeatureStatus::Failing,
+                    "regression"
+                ),
+                (Some(FeatureStatus::Failing), FeatureStatus::Passing, "sql"),
+            ]
+        );
+        assert_eq!(history[0].session_id, Some(session_id));
+        assert_eq!(history[1].error.as_deref(), Some("assertion failed"));
+        assert_eq!(history[2].error, None);
+    }
+
+    #[test]
+    fn test_attach_commit_to_latest_transition() {
+        let (_temp, db) = setup_test_db();
+        let id = insert_feature(&db);
+        let history = db.history();
+
+        assert!(!history.attach_commit(id, "abc123").unwrap());
+
+        db.features().mark_passing(id).unwrap();
+        assert!(history.attach_commit(id, "abc123").unwrap());
+        assert!(!history.attach_commit(id, "def456").unwrap());
+
+        let entries = history.list_for_feature(id).unwrap();
+        assert_eq!(entries[0].commit_hash.as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn test_failed_change_resets_source() {
+        let (_temp, db) = setup_test_db();
+        let conn = db.connection();
+        let conn = conn.lock().unwrap();
+
+        let result: Result<()> = attributed(&conn, ChangeSource::Cli, |_| anyhow::bail!("boom"));
+        assert!(result.is_err());
+
+        let source: String = conn
+            .query_row(
+                "SELECT source FROM feature_history_context WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(source, "sql");
+    }
+}
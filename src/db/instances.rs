@@ -6,6 +6,9 @@ use anyhow::{Context, Result};
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 
+/// Registry timestamps are written by SQLite's `datetime('now')` (UTC)
+pub const REGISTRY_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
 /// Represents a running instance of the application
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
@@ -18,6 +21,22 @@ pub struct Instance {
     pub log_path: Option<String>,
     pub project_path: Option<String>,
     pub updated_at: String,
+    /// Instance that spawned this one (e.g. the coordinator of a parallel worker)
+    pub parent_id: Option<i64>,
+    /// Feature currently being worked on, refreshed on each heartbeat
+    pub feature_id: Option<i64>,
+    /// Git worktree a parallel worker runs in
+    pub worktree: Option<String>,
+}
+
+/// A project directory that has registered instances
+#[derive(Debug, Clone)]
+pub struct RegisteredProject {
+    pub path: String,
+    /// Most recent heartbeat of any instance in the project
+    pub last_seen: String,
+    /// Instances still marked as running
+    pub running: usize,
 }
 
 /// Repository for instance operations
@@ -47,6 +66,41 @@ BEGIN
 END;
 "#;
 
+/// Migration for existing registries - adds parent_id column if missing
+const MIGRATION_ADD_PARENT_ID: &str = r#"
+ALTER TABLE instances ADD COLUMN parent_id INTEGER;
+"#;
+
+/// Migration for existing registries - adds feature_id column if missing
+const MIGRATION_ADD_FEATURE_ID: &str = r#"
+ALTER TABLE instances ADD COLUMN feature_id INTEGER;
+"#;
+
+/// Migration for existing registries - adds worktree column if missing
+const MIGRATION_ADD_WORKTREE: &str = r#"
+ALTER TABLE instances ADD COLUMN worktree TEXT;
+"#;
+
+/// Columns selected for `Instance` rows, in `map_instance` order
+const INSTANCE_COLUMNS: &str = "id, pid, role, start_time, status, log_path, updated_at, \
+     project_path, parent_id, feature_id, worktree";
+
+fn map_instance(row: &rusqlite::Row) -> rusqlite::Result<Instance> {
+    Ok(Instance {
+        id: row.get(0)?,
+        pid: row.get(1)?,
+        role: row.get(2)?,
+        start_time: row.get(3)?,
+        status: row.get(4)?,
+        log_path: row.get(5)?,
+        updated_at: row.get(6)?,
+        project_path: row.get(7)?,
+        parent_id: row.get(8)?,
+        feature_id: row.get(9)?,
+        worktree: row.get(10)?,
+    })
+}
+
 impl InstanceRepository {
     /// Open the global instance registry
     pub fn open() -> Result<Self> {
@@ -57,8 +111,12 @@ impl InstanceRepository {
             std::fs::create_dir_all(&db_dir).context("Failed to create global config directory")?;
         }
 
-        let db_path = db_dir.join("registry.db");
-        let conn = Connection::open(&db_path)
+        Self::open_at(&db_dir.join("registry.db"))
+    }
+
+    /// Open a registry database at an explicit path
+    fn open_at(db_path: &std::path::Path) -> Result<Self> {
+        let conn = Connection::open(db_path)
             .with_context(|| format!("Failed to open global registry: {}", db_path.display()))?;
 
         // Enable WAL for concurrency
@@ -67,30 +125,69 @@ impl InstanceRepository {
         // Init schema
         conn.execute_batch(INSTANCE_SCHEMA)?;
 
+        // ALTER TABLE ADD COLUMN fails if column exists, which we ignore
+        let _ = conn.execute_batch(MIGRATION_ADD_PARENT_ID);
+        let _ = conn.execute_batch(MIGRATION_ADD_FEATURE_ID);
+        let _ = conn.execute_batch(MIGRATION_ADD_WORKTREE);
+
         Ok(Self { conn })
     }
 
     /// Register a new instance
     pub fn register(&self, pid: u32, role: &str, log_path: Option<&str>) -> Result<i64> {
+        self.register_child(pid, role, None, log_path)
+    }
+
+    /// Register a new instance spawned by another registered instance
+    pub fn register_child(
+        &self,
+        pid: u32,
+        role: &str,
+        parent_id: Option<i64>,
+        log_path: Option<&str>,
+    ) -> Result<i64> {
         let project_path = std::env::current_dir()
             .map(|p| p.display().to_string())
             .unwrap_or_default();
 
         self.conn.execute(
-            "INSERT INTO instances (pid, role, status, log_path, project_path) VALUES (?1, ?2, 'running', ?3, ?4)",
-            params![pid, role, log_path, project_path],
+            "INSERT INTO instances (pid, role, status, log_path, project_path, parent_id) VALUES (?1, ?2, 'running', ?3, ?4, ?5)",
+            params![pid, role, log_path, project_path, parent_id],
         )
         .context("Failed to register instance")?;
 
         Ok(self.conn.last_insert_rowid())
     }
 
-    /// Update the heartbeat/status of an instance
-    #[allow(dead_code)]
-    pub fn heartbeat(&self, id: i64) -> Result<()> {
+    /// Register a parallel worker under its coordinator
+    ///
+    /// Workers run inside a worktree, so the project path is passed explicitly
+    /// rather than taken from the current directory.
+    pub fn register_worker(
+        &self,
+        pid: u32,
+        parent_id: Option<i64>,
+        project_path: &str,
+        worktree: &str,
+        feature_id: Option<i64>,
+        log_path: Option<&str>,
+    ) -> Result<i64> {
         self.conn.execute(
-            "UPDATE instances SET updated_at = datetime('now') WHERE id = ?1",
-            params![id],
+            "INSERT INTO instances (pid, role, status, log_path, project_path, parent_id, feature_id, worktree)
+             VALUES (?1, 'worker', 'running', ?2, ?3, ?4, ?5, ?6)",
+            params![pid, log_path, project_path, parent_id, feature_id, worktree],
+        )
+        .context("Failed to register worker instance")?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Update the heartbeat of an instance, recording its current feature if known
+    pub fn heartbeat(&self, id: i64, feature_id: Option<i64>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE instances SET updated_at = datetime('now'), feature_id = COALESCE(?2, feature_id)
+             WHERE id = ?1",
+            params![id, feature_id],
         )?;
         Ok(())
     }
@@ -104,12 +201,31 @@ impl InstanceRepository {
         Ok(())
     }
 
+    /// Mark an instance as ended abnormally (e.g. its process disappeared)
+    pub fn mark_error(&self, id: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE instances SET status = 'error' WHERE id = ?1",
+            params![id],
+        )?;
+        Ok(())
+    }
+
+    /// List running instances registered from a project directory
+    pub fn list_running_for_project(&self, project_path: &str) -> Result<Vec<Instance>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM instances WHERE status = 'running' AND project_path = ?1 ORDER BY id",
+            INSTANCE_COLUMNS
+        ))?;
+        let instances = stmt
+            .query_map(params![project_path], map_instance)?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(instances)
+    }
+
     /// List all instances (optionally filtering by status)
-    #[allow(dead_code)]
     pub fn list(&self, active_only: bool) -> Result<Vec<Instance>> {
-        let mut sql =
-            "SELECT id, pid, role, start_time, status, log_path, updated_at, project_path FROM instances"
-                .to_string();
+        let mut sql = format!("SELECT {} FROM instances", INSTANCE_COLUMNS);
 
         if active_only {
             sql.push_str(" WHERE status = 'running'");
@@ -118,18 +234,7 @@ impl InstanceRepository {
 
         let mut stmt = self.conn.prepare(&sql)?;
         let instances = stmt
-            .query_map([], |row| {
-                Ok(Instance {
-                    id: row.get(0)?,
-                    pid: row.get(1)?,
-                    role: row.get(2)?,
-                    start_time: row.get(3)?,
-                    status: row.get(4)?,
-                    log_path: row.get(5)?,
-                    updated_at: row.get(6)?,
-                    project_path: row.get(7)?,
-                })
-            })?
+            .query_map([], map_instance)?
             .filter_map(|r| r.ok())
             .collect();
 
@@ -137,29 +242,40 @@ impl InstanceRepository {
     }
 
     /// Get a specific instance
-    #[allow(dead_code)]
     pub fn get(&self, id: i64) -> Result<Option<Instance>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, pid, role, start_time, status, log_path, updated_at, project_path FROM instances WHERE id = ?1"
-        )?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM instances WHERE id = ?1",
+            INSTANCE_COLUMNS
+        ))?;
 
         let mut rows = stmt.query(params![id])?;
         if let Some(row) = rows.next()? {
-            Ok(Some(Instance {
-                id: row.get(0)?,
-                pid: row.get(1)?,
-                role: row.get(2)?,
-                start_time: row.get(3)?,
-                status: row.get(4)?,
-                log_path: row.get(5)?,
-                updated_at: row.get(6)?,
-                project_path: row.get(7)?,
-            }))
+            Ok(Some(map_instance(row)?))
         } else {
             Ok(None)
         }
     }
 
+    /// Projects that have ever registered an instance, by path
+    pub fn projects(&self) -> Result<Vec<RegisteredProject>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT project_path, MAX(updated_at), SUM(status = 'running')
+             FROM instances WHERE project_path IS NOT NULL
+             GROUP BY project_path ORDER BY project_path",
+        )?;
+        let projects = stmt
+            .query_map([], |row| {
+                Ok(RegisteredProject {
+                    path: row.get(0)?,
+                    last_seen: row.get(1)?,
+                    running: row.get::<_, i64>(2)? as usize,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to list registered projects")?;
+        Ok(projects)
+    }
+
     /// Prune old/stale instances (optional cleanup utility)
     #[allow(dead_code)]
     pub fn prune_stale(&self, hours: u32) -> Result<usize> {
@@ -170,3 +286,67 @@ impl InstanceRepository {
         Ok(affected)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup_registry() -> (TempDir, InstanceRepository) {
+        let dir = TempDir::new().unwrap();
+        let repo = InstanceRepository::open_at(&dir.path().join("registry.db")).unwrap();
+        (dir, repo)
+    }
+
+    #[test]
+    fn test_worker_registration_and_heartbeat() {
+        let (_dir, repo) = setup_registry();
+        let coordinator = repo.register(100, "coordinator", None).unwrap();
+        let worker = repo
+            .register_worker(
+                101,
+                Some(coordinator),
+                "/work/app",
+                "/work/app/.forger-worktrees/feature-7",
+                Some(7),
+                None,
+            )
+            .unwrap();
+
+        repo.heartbeat(worker, None).unwrap();
+        let instance = repo.get(worker).unwrap().unwrap();
+        assert_eq!(instance.role, "worker");
+        assert_eq!(instance.parent_id, Some(coordinator));
+        assert_eq!(instance.feature_id, Some(7));
+        assert_eq!(instance.project_path.as_deref(), Some("/work/app"));
+
+        repo.heartbeat(coordinator, Some(3)).unwrap();
+        assert_eq!(repo.get(coordinator).unwrap().unwrap().feature_id, Some(3));
+
+        repo.mark_stopped(worker).unwrap();
+        assert_eq!(repo.list(true).unwrap().len(), 1);
+        assert_eq!(repo.list(false).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_projects_are_grouped_by_path() {
+        let (_dir, repo) = setup_registry();
+        let coordinator = repo.register(100, "coordinator", None).unwrap();
+        repo.register_worker(101, Some(coordinator), "/work/app", "/wt/1", None, None)
+            .unwrap();
+        let stopped = repo
+            .register_worker(102, None, "/work/app", "/wt/2", None, None)
+            .unwrap();
+        repo.register_worker(103, None, "/work/api", "/wt/3", None, None)
+            .unwrap();
+        repo.mark_stopped(stopped).unwrap();
+
+        let projects = repo.projects().unwrap();
+        let summary: Vec<_> = projects
+            .iter()
+            .filter(|p| p.path.starts_with("/work/"))
+            .map(|p| (p.path.as_str(), p.running))
+            .collect();
+        assert_eq!(summary, vec![("/work/api", 1), ("/work/app", 1)]);
+    }
+}
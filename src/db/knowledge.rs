@@ -1,11 +1,29 @@
 //! Knowledge repository for persistent agent facts
 //!
 //! Allows agents to store and retrieve discovered information (e.g. ports, paths).
+//! Facts can be scoped to a feature and given a time to live; expired facts
+//! are never returned, and tracked servers whose process has exited are
+//! dropped before they can be handed back to an agent.
 
-use anyhow::{Context, Result};
-use rusqlite::{params, Connection};
+use anyhow::{bail, Context, Result};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::features::Feature;
+use crate::autonomous::parallel::isolation::process_alive;
+
+/// Category used for tracked dev servers
+const SERVERS_CATEGORY: &str = "servers";
+
+/// Columns selected for a [`Knowledge`], from the `knowledge` table aliased as `k`
+const KNOWLEDGE_COLUMNS: &str =
+    "k.key, k.value, k.category, k.description, k.feature_id, k.session_id, k.expires_at, k.pinned";
+
+/// Condition excluding expired facts
+const NOT_EXPIRED: &str = "(k.expires_at IS NULL OR k.expires_at > datetime('now'))";
 
 /// A single unit of knowledge
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +32,55 @@ pub struct Knowledge {
     pub value: String,
     pub category: String,
     pub description: Option<String>,
+    /// Feature the fact applies to (`None` for project-wide facts)
+    #[serde(default)]
+    pub feature_id: Option<i64>,
+    /// Session that was running when the fact was written
+    #[serde(default)]
+    pub session_id: Option<i64>,
+    /// When the fact expires (UTC), if it has a time to live
+    #[serde(default)]
+    pub expires_at: Option<String>,
+    /// Always injected into prompts, whatever the feature
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Why a fact is relevant to a feature, most relevant first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relevance {
+    Pinned,
+    ThisFeature,
+    /// Written for another passing feature in the same category; features
+    /// have no dependency model, so this stands in for "features it builds on"
+    SameCategoryFeature,
+    Category,
+}
+
+impl Relevance {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Relevance::Pinned => "pinned",
+            Relevance::ThisFeature => "this feature",
+            Relevance::SameCategoryFeature => "same-category feature",
+            Relevance::Category => "category",
+        }
+    }
+}
+
+/// A fact selected for a feature's prompt
+#[derive(Debug, Clone, Serialize)]
+pub struct RelevantFact {
+    pub fact: Knowledge,
+    pub relevance: Relevance,
+}
+
+/// Where a fact applies and how long it stays valid
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FactScope {
+    pub feature_id: Option<i64>,
+    pub ttl: Option<Duration>,
 }
 
 /// Repository for knowledge operations
@@ -26,19 +93,46 @@ impl KnowledgeRepository {
         Self { conn }
     }
 
-    /// Set a fact (insert or replace)
+    /// Set a project-wide fact that never expires (insert or replace)
     pub fn set(
         &self,
         key: &str,
         value: &str,
         category: &str,
         description: Option<&str>,
+    ) -> Result<()> {
+        self.set_scoped(key, value, category, description, FactScope::default())
+    }
+
+    /// Set a fact with a feature scope and/or time to live (insert or replace)
+    ///
+    /// The fact is attributed to the session currently running, if any, and
+    /// keeps its pin when it is replaced.
+    pub fn set_scoped(
+        &self,
+        key: &str,
+        value: &str,
+        category: &str,
+        description: Option<&str>,
+        scope: FactScope,
     ) -> Result<()> {
         let conn = self.conn.lock().unwrap();
+        // Expired facts are only ever hidden on read, so clear them out on write
         conn.execute(
-            "INSERT OR REPLACE INTO knowledge (key, value, category, description, updated_at) 
-             VALUES (?1, ?2, ?3, ?4, datetime('now'))",
-            params![key, value, category, description],
+            "DELETE FROM knowledge WHERE expires_at <= datetime('now')",
+            [],
+        )?;
+        let expires = scope.ttl.map(|ttl| format!("+{} seconds", ttl.as_secs()));
+        conn.execute(
+            "INSERT OR REPLACE INTO knowledge
+                 (key, value, category, description, feature_id, expires_at, pinned, session_id,
+                  updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5,
+                     CASE WHEN ?6 IS NULL THEN NULL ELSE datetime('now', ?6) END,
+                     COALESCE((SELECT pinned FROM knowledge WHERE key = ?1), 0),
+                     (SELECT id FROM sessions WHERE status = 'running' ORDER BY id DESC LIMIT 1),
+                     datetime('now'))",
+            params![key, value, category, description, scope.feature_id, expires],
         )
         .context("Failed to set knowledge")?;
         Ok(())
@@ -47,56 +141,145 @@ impl KnowledgeRepository {
     /// Get a fact by key
     pub fn get(&self, key: &str) -> Result<Option<Knowledge>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt =
-            conn.prepare("SELECT key, value, category, description FROM knowledge WHERE key = ?1")?;
+        drop_dead_servers(&conn)?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM knowledge k WHERE k.key = ?1 AND {}",
+            KNOWLEDGE_COLUMNS, NOT_EXPIRED
+        ))?;
 
         let mut rows = stmt.query(params![key])?;
         if let Some(row) = rows.next()? {
-            Ok(Some(Knowledge {
-                key: row.get(0)?,
-                value: row.get(1)?,
-                category: row.get(2)?,
-                description: row.get(3)?,
-            }))
+            Ok(Some(map_knowledge(row)?))
         } else {
             Ok(None)
         }
     }
 
     /// List all knowledge, optionally filtered by category
-    pub fn list(&self, category_filter: Option<&str>) -> Result<Vec<Knowledge>> {
+    ///
+    /// With a feature filter, only project-wide facts and facts scoped to
+    /// that feature are returned.
+    pub fn list(
+        &self,
+        category_filter: Option<&str>,
+        feature_filter: Option<i64>,
+    ) -> Result<Vec<Knowledge>> {
         let conn = self.conn.lock().unwrap();
-        let mut sql = "SELECT key, value, category, description FROM knowledge".to_string();
+        drop_dead_servers(&conn)?;
 
-        if category_filter.is_some() {
-            sql.push_str(" WHERE category = ?1");
+        let mut sql = format!(
+            "SELECT {} FROM knowledge k WHERE {}",
+            KNOWLEDGE_COLUMNS, NOT_EXPIRED
+        );
+        let mut values = Vec::new();
+        if let Some(category) = category_filter {
+            values.push(Value::from(category.to_string()));
+            sql.push_str(&format!(" AND k.category = ?{}", values.len()));
+        }
+        if let Some(feature_id) = feature_filter {
+            values.push(Value::from(feature_id));
+            sql.push_str(&format!(
+                " AND (k.feature_id IS NULL OR k.feature_id = ?{})",
+                values.len()
+            ));
         }
-        sql.push_str(" ORDER BY category, key");
+        sql.push_str(" ORDER BY k.category, k.key");
 
         let mut stmt = conn.prepare(&sql)?;
+        let facts = stmt
+            .query_map(params_from_iter(values), map_knowledge)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(facts)
+    }
 
-        // Use a helper closure to map rows
-        let mapper = |row: &rusqlite::Row| -> rusqlite::Result<Knowledge> {
-            Ok(Knowledge {
-                key: row.get(0)?,
-                value: row.get(1)?,
-                category: row.get(2)?,
-                description: row.get(3)?,
-            })
-        };
+    /// Full-text search over keys, values, categories and descriptions
+    ///
+    /// Every term must match (as a prefix); best matches come first.
+    pub fn search(&self, terms: &str, feature_filter: Option<i64>) -> Result<Vec<Knowledge>> {
+        let query = fts_query(terms);
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        // Execute query
-        let iter = if let Some(cat) = category_filter {
-            stmt.query_map(params![cat], mapper)?
-        } else {
-            stmt.query_map([], mapper)?
-        };
+        let conn = self.conn.lock().unwrap();
+        drop_dead_servers(&conn)?;
 
-        let mut result = Vec::new();
-        for item in iter {
-            result.push(item?);
+        let mut sql = format!(
+            "SELECT {} FROM knowledge_fts JOIN knowledge k ON k.key = knowledge_fts.key
+             WHERE knowledge_fts MATCH ?1 AND {}",
+            KNOWLEDGE_COLUMNS, NOT_EXPIRED
+        );
+        if feature_filter.is_some() {
+            sql.push_str(" AND (k.feature_id IS NULL OR k.feature_id = ?2)");
         }
-        Ok(result)
+        sql.push_str(" ORDER BY knowledge_fts.rank");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = match feature_filter {
+            Some(feature_id) => stmt.query_map(params![query, feature_id], map_knowledge)?,
+            None => stmt.query_map(params![query], map_knowledge)?,
+        };
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to search knowledge")
+    }
+
+    /// Facts worth injecting into the prompt for `feature`, most relevant first
+    ///
+    /// That is pinned facts, facts scoped to the feature, facts written for the
+    /// other passing features in its category, and project-wide facts in its
+    /// category. Features carry no explicit dependencies, so same-category
+    /// passing features are only an approximation of what it builds on.
+    pub fn relevant_to(&self, feature: &Feature) -> Result<Vec<RelevantFact>> {
+        let feature_id = feature.db_id()?;
+        let conn = self.conn.lock().unwrap();
+        drop_dead_servers(&conn)?;
+
+        let same_category = "SELECT id FROM features
+                             WHERE category = ?2 AND status = 'passing' AND id != ?1";
+        let sql = format!(
+            "SELECT {columns},
+                    CASE WHEN k.pinned THEN 0
+                         WHEN k.feature_id = ?1 THEN 1
+                         WHEN k.feature_id IN ({same_category}) THEN 2
+                         ELSE 3 END AS relevance
+             FROM knowledge k
+             WHERE {not_expired}
+               AND ((k.pinned AND (k.feature_id IS NULL OR k.feature_id = ?1))
+                    OR k.feature_id = ?1
+                    OR k.feature_id IN ({same_category})
+                    OR (k.feature_id IS NULL AND k.category = ?2))
+             ORDER BY relevance, k.updated_at DESC, k.key",
+            columns = KNOWLEDGE_COLUMNS,
+            not_expired = NOT_EXPIRED,
+            same_category = same_category,
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let facts = stmt
+            .query_map(params![feature_id, feature.category], |row| {
+                let relevance = match row.get::<_, i64>(8)? {
+                    0 => Relevance::Pinned,
+                    1 => Relevance::ThisFeature,
+                    2 => Relevance::SameCategoryFeature,
+                    _ => Relevance::Category,
+                };
+                Ok(RelevantFact {
+                    fact: map_knowledge(row)?,
+                    relevance,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(facts)
+    }
+
+    /// Pin or unpin a fact; returns false if no such fact exists
+    pub fn set_pinned(&self, key: &str, pinned: bool) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE knowledge SET pinned = ?2 WHERE key = ?1",
+            params![key, pinned],
+        )?;
+        Ok(updated > 0)
     }
 
     /// Delete a fact
@@ -108,16 +291,22 @@ impl KnowledgeRepository {
 
     /// Track a server process by port and PID
     /// Convenience wrapper for common use case of tracking started servers
-    pub fn track_server(&self, port: u16, pid: u32) -> Result<()> {
-        let key = format!("server_port_{}_pid", port);
-        let description = format!("Dev server on port {}", port);
-        self.set(&key, &pid.to_string(), "servers", Some(&description))
+    ///
+    /// Parallel workers pass their worker id so servers are recorded per worker.
+    pub fn track_server(&self, port: u16, pid: u32, worker_id: Option<usize>) -> Result<()> {
+        let key = server_key(port, worker_id);
+        let description = match worker_id {
+            Some(id) => format!("Dev server on port {} (worker {})", port, id),
+            None => format!("Dev server on port {}", port),
+        };
+        self.set(&key, &pid.to_string(), SERVERS_CATEGORY, Some(&description))
     }
 
     /// Get the tracked PID for a server on a given port
-    pub fn get_tracked_server(&self, port: u16) -> Result<Option<u32>> {
-        let key = format!("server_port_{}_pid", port);
-        if let Some(knowledge) = self.get(&key)? {
+    ///
+    /// Returns `None` (and forgets the server) if the process is no longer running.
+    pub fn get_tracked_server(&self, port: u16, worker_id: Option<usize>) -> Result<Option<u32>> {
+        if let Some(knowledge) = self.get(&server_key(port, worker_id))? {
             Ok(knowledge.value.parse().ok())
         } else {
             Ok(None)
@@ -125,8 +314,250 @@ impl KnowledgeRepository {
     }
 
     /// Remove tracking for a server (call after killing it)
-    pub fn untrack_server(&self, port: u16) -> Result<()> {
-        let key = format!("server_port_{}_pid", port);
-        self.delete(&key)
+    pub fn untrack_server(&self, port: u16, worker_id: Option<usize>) -> Result<()> {
+        self.delete(&server_key(port, worker_id))
+    }
+}
+
+/// Parse a time to live such as `90s`, `30m`, `2h` or `7d`
+pub fn parse_ttl(ttl: &str) -> Result<Duration> {
+    let ttl = ttl.trim();
+    let split = ttl.find(|c: char| !c.is_ascii_digit()).unwrap_or(ttl.len());
+    let (amount, unit) = ttl.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .with_context(|| format!("Invalid time to live '{}' (e.g. 30m, 2h, 7d)", ttl))?;
+    let seconds = match unit {
+        "s" | "" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => bail!(
+            "Unknown time unit '{}' in '{}' (use s, m, h or d)",
+            unit,
+            ttl
+        ),
+    };
+    Ok(Duration::from_secs(amount * seconds))
+}
+
+fn map_knowledge(row: &rusqlite::Row) -> rusqlite::Result<Knowledge> {
+    Ok(Knowledge {
+        key: row.get(0)?,
+        value: row.get(1)?,
+        category: row.get(2)?,
+        description: row.get(3)?,
+        feature_id: row.get(4)?,
+        session_id: row.get(5)?,
+        expires_at: row.get(6)?,
+        pinned: row.get(7)?,
+    })
+}
+
+/// Turn free-form search terms into an FTS5 query of quoted prefix terms
+fn fts_query(terms: &str) -> String {
+    terms
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"*", term))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Forget tracked servers whose process has exited
+fn drop_dead_servers(conn: &Connection) -> Result<()> {
+    let tracked: Vec<(String, String)> = conn
+        .prepare("SELECT key, value FROM knowledge WHERE category = ?1")?
+        .query_map(params![SERVERS_CATEGORY], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    for (key, value) in tracked {
+        let alive = matches!(value.parse::<i32>(), Ok(pid) if pid > 0 && process_alive(pid as u32));
+        if !alive {
+            conn.execute("DELETE FROM knowledge WHERE key = ?1", params![key])?;
+        }
+    }
+    Ok(())
+}
+
+/// Knowledge key for a tracked server, scoped to a parallel worker if given
+fn server_key(port: u16, worker_id: Option<usize>) -> String {
+    match worker_id {
+        Some(id) => format!("worker_{}_server_port_{}_pid", id, port),
+        None => format!("server_port_{}_pid", port),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::tests::setup_test_db;
+
+    #[test]
+    fn test_tracked_servers_are_scoped_per_worker() {
+        let (_dir, db) = setup_test_db();
+        let repo = db.knowledge();
+        let pid = std::process::id();
+
+        repo.track_server(8100, pid, Some(0)).unwrap();
+        repo.track_server(8101, pid, Some(1)).unwrap();
+        repo.track_server(8100, pid, None).unwrap();
+
+        assert_eq!(repo.get_tracked_server(8100, Some(0)).unwrap(), Some(pid));
+        assert_eq!(repo.get_tracked_server(8100, Some(1)).unwrap(), None);
+        assert_eq!(repo.get_tracked_server(8101, Some(1)).unwrap(), Some(pid));
+        assert_eq!(repo.get_tracked_server(8100, None).unwrap(), Some(pid));
+
+        repo.untrack_server(8100, Some(0)).unwrap();
+        assert_eq!(repo.get_tracked_server(8100, Some(0)).unwrap(), None);
+        assert_eq!(repo.get_tracked_server(8101, Some(1)).unwrap(), Some(pid));
+    }
+
+    #[test]
+    fn test_dead_servers_are_forgotten() {
+        let (_dir, db) = setup_test_db();
+        let repo = db.knowledge();
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let dead_pid = child.id();
+        child.wait().unwrap();
+
+        repo.track_server(8200, dead_pid, None).unwrap();
+        assert_eq!(repo.get_tracked_server(8200, None).unwrap(), None);
+        assert!(repo.list(Some(SERVERS_CATEGORY), None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scoped_expiring_and_searchable_facts() {
+        let (_dir, db) = setup_test_db();
+        let repo = db.knowledge();
+
+        repo.set(
+            "dev_port",
+            "3001",
+            "network",
+            Some("Frontend dev server port"),
+        )
+        .unwrap();
+        repo.set_scoped(
+            "login_fixture",
+            "tests/fixtures/users.json",
+            "testing",
+            Some("Seed users for the login flow"),
+            FactScope {
+                feature_id: Some(7),
+                ttl: None,
+            },
+        )
+        .unwrap();
+        repo.set_scoped(
+            "old_port",
+            "4000",
+            "network",
+            None,
+            FactScope {
+                feature_id: None,
+                ttl: Some(Duration::ZERO),
+            },
+        )
+        .unwrap();
+
+        // Expired facts are hidden
+        assert!(repo.get("old_port").unwrap().is_none());
+
+        let keys =
+            |facts: Vec<Knowledge>| -> Vec<String> { facts.into_iter().map(|f| f.key).collect() };
+        assert_eq!(
+            keys(repo.list(None, Some(7)).unwrap()),
+            vec!["dev_port", "login_fixture"]
+        );
+        assert_eq!(keys(repo.list(None, Some(8)).unwrap()), vec!["dev_port"]);
+
+        assert_eq!(
+            keys(repo.search("front port", None).unwrap()),
+            vec!["dev_port"]
+        );
+        assert_eq!(
+            keys(repo.search("login", Some(8)).unwrap()),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            keys(repo.search("fixture", None).unwrap()),
+            vec!["login_fixture"]
+        );
+
+        // Replaced and deleted facts leave the index
+        repo.set("dev_port", "3002", "network", None).unwrap();
+        assert!(repo.search("frontend", None).unwrap().is_empty());
+        repo.delete("login_fixture").unwrap();
+        assert!(repo.search("fixture", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_relevant_facts_for_a_feature() {
+        let (_dir, db) = setup_test_db();
+        let mut ids = Vec::new();
+        for (category, description) in [("auth", "Login"), ("auth", "Logout"), ("ui", "Theme")] {
+            ids.push(
+                db.features()
+                    .insert(&Feature::new(category, description))
+                    .unwrap(),
+            );
+        }
+        let (login, logout, theme) = (ids[0], ids[1], ids[2]);
+        db.features().mark_passing(login).unwrap();
+
+        let repo = db.knowledge();
+        let scoped = |feature_id| FactScope {
+            feature_id: Some(feature_id),
+            ttl: None,
+        };
+        repo.set("package_manager", "bun", "tooling", None).unwrap();
+        repo.set_pinned("package_manager", true).unwrap();
+        repo.set("session_cookie", "sid", "auth", None).unwrap();
+        repo.set("palette", "dark", "ui", None).unwrap();
+        repo.set_scoped(
+            "login_fixture",
+            "users.json",
+            "testing",
+            None,
+            scoped(login),
+        )
+        .unwrap();
+        repo.set_scoped("logout_route", "/bye", "routes", None, scoped(logout))
+            .unwrap();
+        repo.set_scoped("theme_token", "x", "ui", None, scoped(theme))
+            .unwrap();
+        // Re-setting a fact keeps its pin
+        repo.set("package_manager", "pnpm", "tooling", None)
+            .unwrap();
+
+        let feature = db.features().get(logout).unwrap().unwrap();
+        let relevant: Vec<(String, Relevance)> = repo
+            .relevant_to(&feature)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.fact.key, r.relevance))
+            .collect();
+        assert_eq!(
+            relevant,
+            vec![
+                ("package_manager".to_string(), Relevance::Pinned),
+                ("logout_route".to_string(), Relevance::ThisFeature),
+                ("login_fixture".to_string(), Relevance::SameCategoryFeature),
+                ("session_cookie".to_string(), Relevance::Category),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ttl() {
+        assert_eq!(parse_ttl("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_ttl("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_ttl("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_ttl("7d").unwrap(), Duration::from_secs(604800));
+        assert!(parse_ttl("soon").is_err());
+        assert!(parse_ttl("3w").is_err());
     }
 }
@@ -0,0 +1,384 @@
+//! Numbered schema migrations for the progress database
+//!
+//! `schema::SCHEMA` is migration 1, the baseline every database starts from.
+//! Each later schema change is appended to [`MIGRATIONS`] with the next
+//! version number; the highest applied version is stored in the `meta` table
+//! under `schema_version`. Released migrations must never be edited - add a
+//! new one instead.
+//!
+//! Databases created before versioning report version 0. Their schema may
+//! already contain some of the later columns, so column migrations check for
+//! the column before adding it.
+
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use std::path::Path;
+
+use super::features::repo::unique_key;
+use super::schema;
+
+/// `meta` key holding the applied schema version
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// A single numbered schema change
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+}
+
+/// All migrations, in the order they are applied
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Initial schema",
+        apply: initial_schema,
+    },
+    Migration {
+        version: 2,
+        description: "Add features.last_error",
+        apply: add_last_error,
+    },
+    Migration {
+        version: 3,
+        description: "Add features.status lifecycle column",
+        apply: add_feature_status,
+    },
+    Migration {
+        version: 4,
+        description: "Keep features.passes and features.status in sync",
+        apply: feature_status_sync,
+    },
+    Migration {
+        version: 5,
+        description: "Record feature status transitions in feature_history",
+        apply: feature_history,
+    },
+    Migration {
+        version: 6,
+        description: "Add features.priority",
+        apply: add_feature_priority,
+    },
+    Migration {
+        version: 7,
+        description: "Add stable features.key",
+        apply: add_feature_key,
+    },
+    Migration {
+        version: 8,
+        description: "Scope, expire and full-text index knowledge",
+        apply: knowledge_scope,
+    },
+    Migration {
+        version: 9,
+        description: "Add knowledge.pinned",
+        apply: add_knowledge_pinned,
+    },
+    Migration {
+        version: 10,
+        description: "Record verification runs and per-test results",
+        apply: verification_runs,
+    },
+    Migration {
+        version: 11,
+        description: "Add verification step pipelines",
+        apply: add_verification_steps,
+    },
+    Migration {
+        version: 12,
+        description: "Add features.verification_timeout_secs",
+        apply: add_verification_timeout,
+    },
+    Migration {
+        version: 13,
+        description: "Track per-feature flake statistics",
+        apply: feature_flakiness,
+    },
+    Migration {
+        version: 14,
+        description: "Add features.last_verified_at",
+        apply: add_last_verified,
+    },
+    Migration {
+        version: 15,
+        description: "Cache passing verification results by tree state",
+        apply: verification_cache,
+    },
+];
+
+/// Highest schema version this build knows about
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// Schema version recorded in the database, or 0 for unversioned databases
+pub fn current_version(conn: &Connection) -> Result<u32> {
+    let has_meta: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'meta')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_meta {
+        return Ok(0);
+    }
+
+    let value: Option<String> = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = ?1",
+            params![SCHEMA_VERSION_KEY],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match value {
+        Some(v) => v
+            .parse()
+            .with_context(|| format!("Invalid schema version in meta table: {}", v)),
+        None => Ok(0),
+    }
+}
+
+/// Schema version of a database file, read without migrating it
+pub fn version_of(path: &Path) -> Result<u32> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open database: {}", path.display()))?;
+    current_version(&conn)
+}
+
+/// Apply every pending migration in a single transaction
+///
+/// Refuses databases written by a newer build, since this one cannot know
+/// what their schema looks like. Returns the migrations that were applied.
+pub fn run_pending(conn: &Connection) -> Result<Vec<&'static Migration>> {
+    let current = current_version(conn)?;
+    let latest = latest_version();
+    if current > latest {
+        bail!(
+            "Database schema is version {}, but this opencode-forger only supports up to version {}. \
+             It was written by a newer release; upgrade opencode-forger to open it.",
+            current,
+            latest
+        );
+    }
+
+    let pending: Vec<&Migration> = MIGRATIONS.iter().filter(|m| m.version > current).collect();
+    if pending.is_empty() {
+        return Ok(pending);
+    }
+
+    let tx = conn
+        .unchecked_transaction()
+        .context("Failed to start schema migration")?;
+    for migration in &pending {
+        (migration.apply)(&tx).with_context(|| {
+            format!(
+                "Schema migration {} ({}) failed",
+                migration.version, migration.description
+            )
+        })?;
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO meta (key, value, updated_at) VALUES (?1, ?2, datetime('now'))",
+        params![SCHEMA_VERSION_KEY, latest.to_string()],
+    )?;
+    tx.commit().context("Failed to commit schema migration")?;
+
+    Ok(pending)
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    Ok(conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+        params![table, column],
+        |row| row.get(0),
+    )?)
+}
+
+fn initial_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(schema::SCHEMA)?;
+    Ok(())
+}
+
+fn add_last_error(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "features", "last_error")? {
+        conn.execute_batch(schema::MIGRATION_ADD_LAST_ERROR)?;
+    }
+    Ok(())
+}
+
+fn add_feature_status(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "features", "status")? {
+        conn.execute_batch(schema::MIGRATION_ADD_STATUS)?;
+        conn.execute_batch(schema::MIGRATION_BACKFILL_STATUS)?;
+    }
+    Ok(())
+}
+
+fn feature_status_sync(conn: &Connection) -> Result<()> {
+    conn.execute_batch(schema::FEATURE_STATUS_SYNC)?;
+    Ok(())
+}
+
+fn feature_history(conn: &Connection) -> Result<()> {
+    conn.execute_batch(schema::FEATURE_HISTORY)?;
+    Ok(())
+}
+
+fn add_feature_priority(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "features", "priority")? {
+        conn.execute_batch(schema::MIGRATION_ADD_PRIORITY)?;
+    }
+    Ok(())
+}
+
+fn add_feature_key(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "features", "key")? {
+        conn.execute_batch(schema::MIGRATION_ADD_KEY)?;
+    }
+
+    // Oldest features get the plain slug, later look-alikes a numeric suffix
+    let unkeyed: Vec<(i64, String)> = conn
+        .prepare("SELECT id, description FROM features WHERE key IS NULL ORDER BY id")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    for (id, description) in unkeyed {
+        let key = unique_key(conn, "", &description)?;
+        conn.execute(
+            "UPDATE features SET key = ?2 WHERE id = ?1",
+            params![id, key],
+        )?;
+    }
+
+    conn.execute_batch(schema::FEATURE_KEY_INDEX)?;
+    Ok(())
+}
+
+fn knowledge_scope(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "knowledge", "feature_id")? {
+        conn.execute_batch(schema::MIGRATION_KNOWLEDGE_SCOPE)?;
+    }
+    conn.execute_batch(schema::KNOWLEDGE_FTS)?;
+    Ok(())
+}
+
+fn verification_runs(conn: &Connection) -> Result<()> {
+    conn.execute_batch(schema::VERIFICATION_RUNS)?;
+    Ok(())
+}
+
+fn add_knowledge_pinned(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "knowledge", "pinned")? {
+        conn.execute_batch(schema::MIGRATION_KNOWLEDGE_PINNED)?;
+    }
+    Ok(())
+}
+
+fn add_verification_steps(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "features", "verification_steps")? {
+        conn.execute_batch(schema::MIGRATION_VERIFICATION_STEPS)?;
+    }
+    Ok(())
+}
+
+fn add_verification_timeout(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "features", "verification_timeout_secs")? {
+        conn.execute_batch(schema::MIGRATION_VERIFICATION_TIMEOUT)?;
+    }
+    Ok(())
+}
+
+fn feature_flakiness(conn: &Connection) -> Result<()> {
+    conn.execute_batch(schema::FEATURE_FLAKINESS)?;
+    Ok(())
+}
+
+fn add_last_verified(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "features", "last_verified_at")? {
+        conn.execute_batch(schema::MIGRATION_LAST_VERIFIED)?;
+    }
+    Ok(())
+}
+
+fn verification_cache(conn: &Connection) -> Result<()> {
+    conn.execute_batch(schema::VERIFICATION_CACHE)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::tests::setup_test_db;
+
+    #[test]
+    fn test_versions_are_sequential() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, index + 1);
+        }
+    }
+
+    #[test]
+    fn test_new_database_is_at_latest_version() {
+        let (_temp, db) = setup_test_db();
+        let conn = db.connection();
+        let conn = conn.lock().unwrap();
+
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert!(has_column(&conn, "features", "last_error").unwrap());
+        assert!(has_column(&conn, "features", "status").unwrap());
+        assert!(has_column(&conn, "features", "priority").unwrap());
+        assert!(has_column(&conn, "features", "key").unwrap());
+        assert!(has_column(&conn, "knowledge", "expires_at").unwrap());
+        assert!(has_column(&conn, "knowledge", "pinned").unwrap());
+        assert!(has_column(&conn, "features", "verification_steps").unwrap());
+        assert!(has_column(&conn, "verification_runs", "step").unwrap());
+        assert!(has_column(&conn, "features", "verification_timeout_secs").unwrap());
+        assert!(has_column(&conn, "feature_flakiness", "flaky_checks").unwrap());
+        assert!(has_column(&conn, "features", "last_verified_at").unwrap());
+        assert!(has_column(&conn, "verification_cache", "tree_hash").unwrap());
+        assert!(run_pending(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unversioned_database_is_upgraded() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("legacy.db");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(schema::SCHEMA).unwrap();
+            conn.execute_batch(schema::MIGRATION_ADD_LAST_ERROR)
+                .unwrap();
+            conn.execute_batch(
+                "INSERT INTO features (category, description) VALUES
+                     ('functional', 'User can log in'),
+                     ('functional', 'User can log in!')",
+            )
+            .unwrap();
+        }
+        assert_eq!(version_of(&path).unwrap(), 0);
+
+        let db = crate::db::Database::open(&path).unwrap();
+        assert_eq!(version_of(&path).unwrap(), latest_version());
+
+        let keys: Vec<String> = db
+            .features()
+            .list_all()
+            .unwrap()
+            .into_iter()
+            .map(|f| f.key)
+            .collect();
+        assert_eq!(keys, vec!["user-can-log-in", "user-can-log-in-2"]);
+    }
+
+    #[test]
+    fn test_newer_database_is_refused() {
+        let (temp, db) = setup_test_db();
+        db.meta()
+            .set(SCHEMA_VERSION_KEY, &(latest_version() + 1).to_string())
+            .unwrap();
+        drop(db);
+
+        let err = crate::db::Database::open(&temp.path().join("test.db"))
+            .err()
+            .expect("newer schema must be refused");
+        assert!(format!("{:#}", err).contains("newer release"));
+    }
+}
@@ -5,30 +5,42 @@
 //!
 //! # Modules
 //!
+//! - `backup`: Online backups, rolling snapshots and restore
 //! - `connection`: Database connection management
 //! - `features`: Feature repository and models
+//! - `history`: Feature status transition history
 //! - `knowledge`: Knowledge base storage
 //! - `meta`: Metadata storage
+//! - `migrations`: Numbered schema migrations
 //! - `query`: Database query utilities
+//! - `reconcile`: Per-worker snapshots and merge-back for parallel mode
 //! - `sessions`: Session tracking and management
+//! - `verification`: Verification runs and per-test results
 //! - `instances`: Instance management
 
+pub mod backup;
 pub mod connection;
 pub mod features;
+pub mod history;
 pub mod knowledge;
 pub mod meta;
+pub mod migrations;
 pub mod query;
+pub mod reconcile;
 mod schema;
 pub mod sessions;
 #[cfg(test)]
 pub mod test_utils;
+pub mod verification;
 
 // Re-export types used by main.rs
 pub use connection::Database;
 pub use features::FeatureRepository;
+pub use history::HistoryRepository;
 pub use knowledge::KnowledgeRepository;
 pub use meta::MetaRepository;
 pub use sessions::SessionRepository;
+pub use verification::VerificationRepository;
 
 pub mod instances;
 pub use instances::InstanceRepository;
@@ -1,58 +1,55 @@
 use anyhow::Result;
+use serde_json::Value;
 
 use super::connection::Database;
 
+/// Result set of a read-only query, with SQLite values typed as JSON
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct QueryRows {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
 // ============================================================
 // MCP-equivalent operations (replaces SQLite MCP server)
 // ============================================================
 
 impl Database {
-    /// Execute a read-only SELECT query, returns formatted output.
-    /// This function enforces read-only mode by rejecting any non-SELECT statements.
-    ///
-    /// PRAGMA statements are restricted to a safe subset of read-only introspection
-    /// commands, since some PRAGMAs can mutate database state.
+    /// Execute a read-only query, returns formatted output.
     pub fn read_query(&self, sql: &str) -> Result<String> {
-        let sql_upper = sql.trim().to_uppercase();
+        let result = self.query_rows(sql)?;
+        let rows: Vec<Vec<String>> = result
+            .rows
+            .iter()
+            .map(|row| row.iter().map(format_value).collect())
+            .collect();
+        Ok(format_table(&result.columns, &rows))
+    }
 
-        // Allowlist: SELECT queries and safe read-only PRAGMAs for introspection
-        let is_safe = sql_upper.starts_with("SELECT")
-            || sql_upper.starts_with("PRAGMA TABLE_INFO")
-            || sql_upper.starts_with("PRAGMA DATABASE_LIST")
-            || sql_upper.starts_with("PRAGMA INDEX_LIST")
-            || sql_upper.starts_with("PRAGMA INDEX_INFO")
-            || sql_upper.starts_with("PRAGMA FOREIGN_KEY_LIST")
-            || sql_upper.starts_with("PRAGMA TABLE_LIST");
+    /// Execute a read-only query, returns typed rows.
+    ///
+    /// The statement runs with `PRAGMA query_only` enabled, and SQLite must
+    /// report it as read-only, so a crafted statement can't write even if it
+    /// looks like a SELECT. PRAGMA statements are further restricted to a safe
+    /// subset of introspection commands, since some PRAGMAs change connection
+    /// settings.
+    pub fn query_rows(&self, sql: &str) -> Result<QueryRows> {
+        let sql_upper = sql.trim().to_uppercase();
 
-        if !is_safe {
+        if sql_upper.starts_with("PRAGMA") && !SAFE_PRAGMAS.iter().any(|p| sql_upper.starts_with(p))
+        {
             anyhow::bail!(
-                "read_query only allows SELECT and safe PRAGMA statements. Use 'db exec' for modifications."
+                "read_query only allows safe introspection PRAGMAs ({})",
+                SAFE_PRAGMAS.join(", ")
             );
         }
 
         let conn = self.connection();
         let conn = conn.lock().unwrap();
-        let mut stmt = conn.prepare(sql)?;
-        let column_count = stmt.column_count();
-        let column_names: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
-
-        let rows: Vec<Vec<String>> = stmt
-            .query_map([], |row| {
-                let mut values = Vec::new();
-                for i in 0..column_count {
-                    let value: String = row
-                        .get::<_, rusqlite::types::Value>(i)
-                        .map(|v| format_value(&v))
-                        .unwrap_or_else(|_| "NULL".to_string());
-                    values.push(value);
-                }
-                Ok(values)
-            })?
-            .filter_map(|r| r.ok())
-            .collect();
-
-        // Format as table
-        Ok(format_table(&column_names, &rows))
+        conn.pragma_update(None, "query_only", true)?;
+        let result = run_read_query(&conn, sql);
+        conn.pragma_update(None, "query_only", false)?;
+        result
     }
 
     /// Execute a write query (INSERT, UPDATE, DELETE, CREATE), returns rows affected
@@ -125,19 +122,70 @@ impl Database {
     }
 }
 
-/// Format a SQLite value as a string (DRY helper)
-fn format_value(value: &rusqlite::types::Value) -> String {
+/// PRAGMA statements `read_query` accepts
+const SAFE_PRAGMAS: &[&str] = &[
+    "PRAGMA TABLE_INFO",
+    "PRAGMA DATABASE_LIST",
+    "PRAGMA INDEX_LIST",
+    "PRAGMA INDEX_INFO",
+    "PRAGMA FOREIGN_KEY_LIST",
+    "PRAGMA TABLE_LIST",
+];
+
+/// Run a single read-only statement and collect its rows
+fn run_read_query(conn: &rusqlite::Connection, sql: &str) -> Result<QueryRows> {
+    let mut stmt = conn.prepare(sql)?;
+    if !stmt.readonly() {
+        anyhow::bail!(
+            "db query only runs read-only statements. Use the typed 'db feature' commands to make changes."
+        );
+    }
+    let column_count = stmt.column_count();
+    let column_names: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+
+    let rows: Vec<Vec<Value>> = stmt
+        .query_map([], |row| {
+            let mut values = Vec::new();
+            for i in 0..column_count {
+                let value = row
+                    .get::<_, rusqlite::types::Value>(i)
+                    .map(|v| json_value(&v))
+                    .unwrap_or(Value::Null);
+                values.push(value);
+            }
+            Ok(values)
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(QueryRows {
+        columns: column_names,
+        rows,
+    })
+}
+
+/// Convert a SQLite value to JSON (blobs are not exported)
+fn json_value(value: &rusqlite::types::Value) -> Value {
     match value {
-        rusqlite::types::Value::Null => "NULL".to_string(),
-        rusqlite::types::Value::Integer(i) => i.to_string(),
-        rusqlite::types::Value::Real(f) => f.to_string(),
-        rusqlite::types::Value::Text(s) => s.clone(),
-        rusqlite::types::Value::Blob(_) => "[BLOB]".to_string(),
+        rusqlite::types::Value::Null => Value::Null,
+        rusqlite::types::Value::Integer(i) => Value::from(*i),
+        rusqlite::types::Value::Real(f) => Value::from(*f),
+        rusqlite::types::Value::Text(s) => Value::from(s.as_str()),
+        rusqlite::types::Value::Blob(_) => Value::from("[BLOB]"),
+    }
+}
+
+/// Format a typed value as a string (DRY helper)
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
     }
 }
 
 /// Format rows as a simple table (DRY helper)
-fn format_table(headers: &[String], rows: &[Vec<String>]) -> String {
+pub fn format_table(headers: &[String], rows: &[Vec<String>]) -> String {
     if rows.is_empty() {
         return "(no rows)\n".to_string();
     }
@@ -179,3 +227,53 @@ fn format_table(headers: &[String], rows: &[Vec<String>]) -> String {
 
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::db::test_utils::tests::setup_test_db;
+
+    #[test]
+    fn test_read_query_rejects_writes() {
+        let (_temp, db) = setup_test_db();
+
+        assert!(db
+            .read_query("INSERT INTO features (category, description) VALUES ('a', 'b')")
+            .is_err());
+        assert!(db
+            .read_query("WITH x AS (SELECT 1) DELETE FROM features")
+            .is_err());
+        assert!(db.read_query("PRAGMA journal_mode = DELETE").is_err());
+
+        // The connection is writable again afterwards
+        db.write_query("INSERT INTO features (category, description) VALUES ('a', 'b')")
+            .unwrap();
+        let output = db
+            .read_query("WITH x AS (SELECT COUNT(*) AS n FROM features) SELECT n FROM x")
+            .unwrap();
+        assert!(output.contains('1'));
+    }
+
+    #[test]
+    fn test_query_rows_are_typed() {
+        let (_temp, db) = setup_test_db();
+        db.write_query("INSERT INTO features (category, description) VALUES ('a', 'b')")
+            .unwrap();
+
+        let result = db
+            .query_rows("SELECT id, description, verification_command, 1.5 AS score FROM features")
+            .unwrap();
+        assert_eq!(
+            result.columns,
+            vec!["id", "description", "verification_command", "score"]
+        );
+        assert_eq!(
+            result.rows,
+            vec![vec![
+                serde_json::json!(1),
+                serde_json::json!("b"),
+                serde_json::Value::Null,
+                serde_json::json!(1.5),
+            ]]
+        );
+    }
+}
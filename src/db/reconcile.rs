@@ -0,0 +1,545 @@
+//! Per-worker database snapshots and merge-back
+//!
+//! Parallel workers run against a private copy of `progress.db` so they never
+//! contend with each other for SQLite locks. When a worker finishes, the
+//! coordinator reconciles the worker's progress back into the main database
+//! in a single transaction.
+
+use anyhow::{Context, Result};
+use rusqlite::{params, OptionalExtension};
+use std::path::Path;
+
+use super::features::FeatureStatus;
+use super::history::{self, ChangeSource};
+use super::Database;
+
+/// Meta key recording when a worker snapshot was taken (`datetime('now')` format)
+const SNAPSHOT_AT_KEY: &str = "worker_snapshot_at";
+
+/// Meta key recording the highest session id present when the snapshot was taken
+const SNAPSHOT_SESSION_KEY: &str = "worker_snapshot_session_id";
+
+/// Meta key recording the highest verification run id present when the snapshot was taken
+const SNAPSHOT_RUN_KEY: &str = "worker_snapshot_run_id";
+
+/// Meta key recording the highest feature history id present when the snapshot was taken
+const SNAPSHOT_HISTORY_KEY: &str = "worker_snapshot_history_id";
+
+/// Meta key recording the knowledge keys present when the snapshot was taken (JSON array)
+const SNAPSHOT_KNOWLEDGE_KEY: &str = "worker_snapshot_knowledge_keys";
+
+/// What happened to the worker's branch, which decides the feature's final status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BranchOutcome {
+    /// Branch was integrated into the base branch; trust the worker's status
+    Merged,
+    /// Worker succeeded but integration failed; feature stays failing with this reason
+    MergeFailed(String),
+    /// Worker failed, so no merge was attempted; keep the worker's last error
+    NotAttempted,
+}
+
+/// Counts of what was carried back from a worker database
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReconcileSummary {
+    pub feature_passes: bool,
+    pub knowledge_updated: usize,
+    pub knowledge_deleted: usize,
+    pub sessions_copied: usize,
+    pub history_copied: usize,
+    pub verification_runs_copied: usize,
+}
+
+/// Create a private database snapshot for a worker
+///
+/// Records the snapshot time, the session, feature history and verification
+/// run high-water marks, and the knowledge keys inside the copy so the later
+/// merge-back can tell the worker's changes from inherited rows.
+/// Flake statistics start empty, so the worker only counts its own checks.
+pub fn create_worker_snapshot(main: &Database, dest: &Path) -> Result<()> {
+    main.snapshot_to(dest)?;
+
+    let conn = rusqlite::Connection::open(dest)
+        .with_context(|| format!("Failed to open worker snapshot: {}", dest.display()))?;
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value, updated_at)
+         VALUES (?1, datetime('now'), datetime('now'))",
+        params![SNAPSHOT_AT_KEY],
+    )?;
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value, updated_at)
+         VALUES (?1, (SELECT COALESCE(MAX(id), 0) FROM sessions), datetime('now'))",
+        params![SNAPSHOT_SESSION_KEY],
+    )?;
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value, updated_at)
+         VALUES (?1, (SELECT COALESCE(MAX(id), 0) FROM verification_runs), datetime('now'))",
+        params![SNAPSHOT_RUN_KEY],
+    )?;
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value, updated_at)
+         VALUES (?1, (SELECT COALESCE(MAX(id), 0) FROM feature_history), datetime('now'))",
+        params![SNAPSHOT_HISTORY_KEY],
+    )?;
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value, updated_at)
+         VALUES (?1, (SELECT json_group_array(key) FROM knowledge), datetime('now'))",
+        params![SNAPSHOT_KNOWLEDGE_KEY],
+    )?;
+    conn.execute("DELETE FROM feature_flakiness", [])?;
+    Ok(())
+}
+
+/// Merge a worker's progress back into the main database
+///
+/// Runs in one transaction on the main database:
+/// - sessions (and their events) started by the worker are appended
+/// - status transitions the worker recorded, with their commits, are appended
+/// - the worker's feature gets its status and `last_error` per `outcome`
+/// - knowledge facts written by the worker are upserted, and facts it deleted are removed
+/// - verification runs (and their tests) recorded by the worker are appended,
+///   and its verification checks are added to the flake statistics and
+///   `last_verified_at`
+/// - implementation packet file predictions are copied for the scheduler
+pub fn reconcile_worker_db(
+    main: &Database,
+    worker_path: &Path,
+    feature_id: i64,
+    outcome: &BranchOutcome,
+) -> Result<ReconcileSummary> {
+    let worker_str = worker_path
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Worker database path contains invalid UTF-8"))?;
+
+    let conn = main.connection();
+    let mut conn = conn.lock().unwrap();
+    conn.execute("ATTACH DATABASE ?1 AS worker", params![worker_str])
+        .with_context(|| {
+            format!(
+                "Failed to attach worker database: {}",
+                worker_path.display()
+            )
+        })?;
+
+    let result = (|| -> Result<ReconcileSummary> {
+        let tx = conn.transaction()?;
+        let mut summary = ReconcileSummary::default();
+
+        let snapshot_at: String = tx
+            .query_row(
+                "SELECT value FROM worker.meta WHERE key = ?1",
+                params![SNAPSHOT_AT_KEY],
+                |row| row.get(0),
+            )
+            .optional()?
+            .context("Worker database has no snapshot marker")?;
+        let snapshot_session_id: i64 = tx
+            .query_row(
+                "SELECT value FROM worker.meta WHERE key = ?1",
+                params![SNAPSHOT_SESSION_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        let snapshot_run_id: i64 = tx
+            .query_row(
+                "SELECT value FROM worker.meta WHERE key = ?1",
+                params![SNAPSHOT_RUN_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        // Without a marker every inherited transition would be copied again
+        let snapshot_history_id: Option<i64> = tx
+            .query_row(
+                "SELECT value FROM worker.meta WHERE key = ?1",
+                params![SNAPSHOT_HISTORY_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .and_then(|v| v.parse().ok());
+
+        // Sessions started by the worker, with their events
+        let new_sessions: Vec<i64> = {
+            let mut stmt =
+                tx.prepare("SELECT id FROM worker.sessions WHERE id > ?1 ORDER BY id")?;
+            let ids = stmt
+                .query_map(params![snapshot_session_id], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<i64>>>()?;
+            ids
+        };
+        // Worker session ids become new ids in main; history and knowledge refer to them
+        tx.execute_batch(
+            "CREATE TEMP TABLE IF NOT EXISTS reconciled_sessions (worker_id INTEGER PRIMARY KEY, main_id INTEGER);
+             DELETE FROM temp.reconciled_sessions;",
+        )?;
+        for worker_session_id in new_sessions {
+            tx.execute(
+                "INSERT INTO main.sessions
+                     (session_number, started_at, completed_at, features_before, features_after, status)
+                 SELECT session_number, started_at, completed_at, features_before, features_after, status
+                 FROM worker.sessions WHERE id = ?1",
+                params![worker_session_id],
+            )?;
+            let main_session_id = tx.last_insert_rowid();
+            tx.execute(
+                "INSERT INTO main.session_events (session_id, event_type, message, timestamp)
+                 SELECT ?1, event_type, message, timestamp
+                 FROM worker.session_events WHERE session_id = ?2 ORDER BY id",
+                params![main_session_id, worker_session_id],
+            )?;
+            tx.execute(
+                "INSERT INTO temp.reconciled_sessions (worker_id, main_id) VALUES (?1, ?2)",
+                params![worker_session_id, main_session_id],
+            )?;
+            summary.sessions_copied += 1;
+        }
+
+        // Status transitions the worker recorded, with their commits; the
+        // merge-back's own transition below is recorded after them
+        if let Some(snapshot_history_id) = snapshot_history_id {
+            summary.history_copied = tx.execute(
+                "INSERT INTO main.feature_history
+                     (feature_id, from_status, to_status, source, session_id, commit_hash, error,
+                      created_at)
+                 SELECT h.feature_id, h.from_status, h.to_status, h.source,
+                        CASE WHEN h.session_id <= ?2 THEN h.session_id
+                             ELSE (SELECT main_id FROM temp.reconciled_sessions
+                                   WHERE worker_id = h.session_id) END,
+                        h.commit_hash, h.error, h.created_at
+                 FROM worker.feature_history h
+                 WHERE h.id > ?1 AND h.feature_id IN (SELECT id FROM main.features)
+                 ORDER BY h.id",
+                params![snapshot_history_id, snapshot_session_id],
+            )?;
+        }
+
+        // Feature status
+        let worker_feature: Option<(FeatureStatus, Option<String>)> = tx
+            .query_row(
+                "SELECT status, last_error FROM worker.features WHERE id = ?1",
+                params![feature_id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?.parse().unwrap_or_default(),
+                        row.get(1)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let (worker_status, worker_error) = worker_feature.unwrap_or_default();
+
+        let (status, last_error) = match outcome {
+            BranchOutcome::Merged => (worker_status, worker_error),
+            BranchOutcome::MergeFailed(reason) => (FeatureStatus::Failing, Some(reason.clone())),
+            // An unmerged branch can't count as done; keep e.g. `blocked` as reported
+            BranchOutcome::NotAttempted if worker_status.is_done() => {
+                (FeatureStatus::Failing, worker_error)
+            }
+            BranchOutcome::NotAttempted => (worker_status, worker_error),
+        };
+        history::set_source(&tx, ChangeSource::Parallel)?;
+        tx.execute(
+            "UPDATE features SET passes = ?1, status = ?2, last_error = ?3 WHERE id = ?4",
+            params![status.is_done(), status.as_str(), last_error, feature_id],
+        )?;
+        history::set_source(&tx, ChangeSource::Sql)?;
+        summary.feature_passes = status.is_done();
+
+        // Verification runs recorded by the worker, with their tests
+        let new_runs: Vec<i64> = {
+            let mut stmt =
+                tx.prepare("SELECT id FROM worker.verification_runs WHERE id > ?1 ORDER BY id")?;
+            let ids = stmt
+                .query_map(params![snapshot_run_id], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<i64>>>()?;
+            ids
+        };
+        for worker_run_id in new_runs {
+            tx.execute(
+                "INSERT INTO main.verification_runs
+                     (feature_id, command, passed, exit_code, reporter, tests_passed, tests_failed,
+                      tests_skipped, duration_ms, summary, step, session_id, created_at)
+                 SELECT r.feature_id, r.command, r.passed, r.exit_code, r.reporter, r.tests_passed,
+                        r.tests_failed, r.tests_skipped, r.duration_ms, r.summary, r.step,
+                        CASE WHEN r.session_id <= ?2 THEN r.session_id
+                             ELSE (SELECT main_id FROM temp.reconciled_sessions
+                                   WHERE worker_id = r.session_id) END,
+                        r.created_at
+                 FROM worker.verification_runs r WHERE r.id = ?1",
+                params![worker_run_id, snapshot_session_id],
+            )?;
+            let main_run_id = tx.last_insert_rowid();
+            tx.execute(
+                "INSERT INTO main.verification_tests (run_id, name, status, duration_ms, message)
+                 SELECT ?1, name, status, duration_ms, message
+                 FROM worker.verification_tests WHERE run_id = ?2 ORDER BY id",
+                params![main_run_id, worker_run_id],
+            )?;
+            summary.verification_runs_copied += 1;
+        }
+
+        // Verification checks counted by the worker
+        tx.execute(
+            "INSERT INTO main.feature_flakiness (feature_id, checks, flaky_checks, last_flake_at)
+             SELECT feature_id, checks, flaky_checks, last_flake_at FROM worker.feature_flakiness
+             WHERE feature_id IN (SELECT id FROM main.features)
+             ON CONFLICT(feature_id) DO UPDATE SET
+                 checks = checks + excluded.checks,
+                 flaky_checks = flaky_checks + excluded.flaky_checks,
+                 last_flake_at = COALESCE(excluded.last_flake_at, last_flake_at)",
+            [],
+        )?;
+
+        tx.execute(
+            "UPDATE main.features SET last_verified_at = w.last_verified_at
+             FROM worker.features w
+             WHERE w.id = main.features.id
+               AND w.last_verified_at > COALESCE(main.features.last_verified_at, '')",
+            [],
+        )?;
+
+        // Knowledge written by the worker (newest write wins)
+        summary.knowledge_updated = tx.execute(
+            "INSERT OR REPLACE INTO main.knowledge
+                 (key, value, category, description, feature_id, expires_at, pinned, session_id,
+                  created_at, updated_at)
+             SELECT w.key, w.value, w.category, w.description, w.feature_id, w.expires_at, w.pinned,
+                    CASE WHEN w.session_id <= ?2 THEN w.session_id
+                         ELSE (SELECT main_id FROM temp.reconciled_sessions
+                               WHERE worker_id = w.session_id) END,
+                    w.created_at, w.updated_at
+             FROM worker.knowledge w
+             LEFT JOIN main.knowledge m ON m.key = w.key
+             WHERE w.updated_at >= ?1 AND (m.key IS NULL OR w.updated_at >= m.updated_at)",
+            params![snapshot_at, snapshot_session_id],
+        )?;
+
+        // Knowledge the worker deleted: keys it inherited but no longer has. Facts
+        // another worker merged after this snapshot were never inherited, so they stay.
+        // Deletions are skipped if main has touched the fact since the snapshot.
+        summary.knowledge_deleted = tx.execute(
+            "DELETE FROM main.knowledge
+             WHERE updated_at < ?1
+               AND key IN (SELECT j.value FROM worker.meta m, json_each(m.value) j
+                           WHERE m.key = ?2)
+               AND key NOT IN (SELECT key FROM worker.knowledge)",
+            params![snapshot_at, SNAPSHOT_KNOWLEDGE_KEY],
+        )?;
+
+        // Implementation packet predictions for the overlap scheduler
+        tx.execute(
+            "INSERT OR REPLACE INTO main.meta (key, value, updated_at)
+             SELECT key, value, updated_at FROM worker.meta
+             WHERE key LIKE 'packet_files:%' AND updated_at >= ?1",
+            params![snapshot_at],
+        )?;
+
+        tx.commit()?;
+        Ok(summary)
+    })();
+
+    let _ = conn.execute("DETACH DATABASE worker", []);
+    result.context("Failed to reconcile worker database")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::features::Feature;
+    use crate::db::test_utils::tests::setup_test_db;
+
+    fn insert_feature(db: &Database, description: &str) -> i64 {
+        db.features()
+            .insert(&Feature::new("functional", description))
+            .unwrap()
+    }
+
+    fn feature_state(db: &Database, id: i64) -> (bool, Option<String>) {
+        let conn = db.connection();
+        let conn = conn.lock().unwrap();
+        conn.query_row(
+            "SELECT passes, last_error FROM features WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get::<_, i32>(0)? != 0, row.get(1)?)),
+        )
+        .unwrap()
+    }
+
+    /// Insert a fact dated before the snapshot (the update trigger would reset `updated_at`)
+    fn insert_old_knowledge(db: &Database, key: &str) {
+        let conn = db.connection();
+        let conn = conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO knowledge (key, value, updated_at)
+             VALUES (?1, '1', datetime('now', '-1 hour'))",
+            params![key],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_merged_worker_progress_is_reconciled() {
+        let (dir, main) = setup_test_db();
+        let id = insert_feature(&main, "Login form");
+        main.verification().record_check(id, false).unwrap();
+        insert_old_knowledge(&main, "stale");
+        insert_old_knowledge(&main, "kept");
+
+        let worker_path = dir.path().join("worker.db");
+        create_worker_snapshot(&main, &worker_path).unwrap();
+        let worker = Database::open(&worker_path).unwrap();
+        worker.features().mark_passing(id).unwrap();
+        worker.history().attach_commit(id, "abc123").unwrap();
+        worker.knowledge().delete("stale").unwrap();
+        worker
+            .knowledge()
+            .set("api_port", "8080", "network", None)
+            .unwrap();
+        {
+            let conn = worker.connection();
+            let conn = conn.lock().unwrap();
+            conn.execute_batch(
+                "INSERT INTO sessions (session_number, status) VALUES (1, 'completed');
+                 INSERT INTO session_events (session_id, event_type, message)
+                     VALUES (last_insert_rowid(), 'info', 'worker ran');",
+            )
+            .unwrap();
+        }
+        worker
+            .meta()
+            .set("packet_files:1", "[\"src/a.rs\"]")
+            .unwrap();
+        worker
+            .verification()
+            .record(&crate::db::verification::NewVerificationRun {
+                feature_id: id,
+                step: None,
+                command: "cargo test login",
+                passed: true,
+                exit_code: Some(0),
+                reporter: None,
+                duration_ms: 10,
+                summary: None,
+                tests: &[],
+            })
+            .unwrap();
+        worker.verification().record_check(id, true).unwrap();
+        drop(worker);
+
+        let summary = reconcile_worker_db(&main, &worker_path, id, &BranchOutcome::Merged).unwrap();
+
+        assert!(summary.feature_passes);
+        assert_eq!(summary.knowledge_updated, 1);
+        assert_eq!(summary.knowledge_deleted, 1);
+        assert_eq!(summary.sessions_copied, 1);
+        assert_eq!(summary.history_copied, 1);
+        assert_eq!(summary.verification_runs_copied, 1);
+        assert_eq!(feature_state(&main, id), (true, None));
+        // The worker's transition keeps its commit and precedes the merge-back's own
+        let history = main.history().list_for_feature(id).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].commit_hash.as_deref(), Some("abc123"));
+        assert_eq!(history[1].source, "parallel");
+        assert!(main.knowledge().get("stale").unwrap().is_none());
+        assert!(main.knowledge().get("kept").unwrap().is_some());
+        assert_eq!(
+            main.knowledge().get("api_port").unwrap().unwrap().value,
+            "8080"
+        );
+        assert_eq!(main.sessions().list_sessions().unwrap().len(), 1);
+        assert!(main.meta().get("packet_files:1").unwrap().is_some());
+        assert!(main.meta().get(SNAPSHOT_AT_KEY).unwrap().is_none());
+        assert!(main.verification().latest(id).unwrap().unwrap().passed);
+        let stats = &main.verification().flake_stats().unwrap()[0];
+        assert_eq!((stats.checks, stats.flaky_checks), (2, 1));
+        assert!(main
+            .verification()
+            .hours_since_verified()
+            .unwrap()
+            .contains_key(&id));
+    }
+
+    #[test]
+    fn test_fact_merged_by_another_worker_is_not_deleted() {
+        let (dir, main) = setup_test_db();
+        let first = insert_feature(&main, "Login form");
+        let second = insert_feature(&main, "Signup form");
+
+        // Worker A started an hour ago and wrote its fact before worker B started
+        let a_path = dir.path().join("worker-a.db");
+        create_worker_snapshot(&main, &a_path).unwrap();
+        {
+            let worker = Database::open(&a_path).unwrap();
+            let conn = worker.connection();
+            let conn = conn.lock().unwrap();
+            conn.execute(
+                "UPDATE meta SET value = datetime('now', '-1 hour') WHERE key = ?1",
+                params![SNAPSHOT_AT_KEY],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO knowledge (key, value, updated_at)
+                 VALUES ('api_port', '8080', datetime('now', '-30 minutes'))",
+                [],
+            )
+            .unwrap();
+        }
+        let b_path = dir.path().join("worker-b.db");
+        create_worker_snapshot(&main, &b_path).unwrap();
+
+        reconcile_worker_db(&main, &a_path, first, &BranchOutcome::Merged).unwrap();
+        let summary = reconcile_worker_db(&main, &b_path, second, &BranchOutcome::Merged).unwrap();
+
+        assert_eq!(summary.knowledge_deleted, 0);
+        assert_eq!(
+            main.knowledge().get("api_port").unwrap().unwrap().value,
+            "8080"
+        );
+    }
+
+    #[test]
+    fn test_failed_merge_keeps_feature_failing() {
+        let (dir, main) = setup_test_db();
+        let id = insert_feature(&main, "Login form");
+
+        let worker_path = dir.path().join("worker.db");
+        create_worker_snapshot(&main, &worker_path).unwrap();
+        let worker = Database::open(&worker_path).unwrap();
+        worker.features().mark_passing(id).unwrap();
+        drop(worker);
+
+        let outcome = BranchOutcome::MergeFailed("rebase onto main failed".to_string());
+        let summary = reconcile_worker_db(&main, &worker_path, id, &outcome).unwrap();
+
+        assert!(!summary.feature_passes);
+        assert_eq!(
+            feature_state(&main, id),
+            (false, Some("rebase onto main failed".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_failed_worker_error_is_carried_back() {
+        let (dir, main) = setup_test_db();
+        let id = insert_feature(&main, "Login form");
+
+        let worker_path = dir.path().join("worker.db");
+        create_worker_snapshot(&main, &worker_path).unwrap();
+        let worker = Database::open(&worker_path).unwrap();
+        worker
+            .features()
+            .mark_failing_with_error(id, Some("assertion failed"))
+            .unwrap();
+        drop(worker);
+
+        reconcile_worker_db(&main, &worker_path, id, &BranchOutcome::NotAttempted).unwrap();
+
+        assert_eq!(
+            feature_state(&main, id),
+            (false, Some("assertion failed".to_string()))
+        );
+    }
+}
@@ -1,6 +1,9 @@
 //! Database schema definition
+//!
+//! `SCHEMA` is the baseline (migration 1); later changes are applied by the
+//! numbered migrations in `migrations.rs`.
 
-/// SQL schema for all tables
+/// SQL schema for all tables, as of schema version 1
 pub const SCHEMA: &str = r#"
 -- Features table (replaces feature_list.json)
 CREATE TABLE IF NOT EXISTS features (
@@ -9,7 +12,6 @@ CREATE TABLE IF NOT EXISTS features (
     description TEXT NOT NULL UNIQUE,
     passes INTEGER DEFAULT 0,
     verification_command TEXT,
-    last_error TEXT,
     created_at TEXT DEFAULT (datetime('now')),
     updated_at TEXT DEFAULT (datetime('now'))
 );
@@ -103,7 +105,259 @@ BEGIN
 END;
 "#;
 
-/// Migration for existing databases - adds last_error column if missing
+/// Migration 2 - adds the last_error column
 pub const MIGRATION_ADD_LAST_ERROR: &str = r#"
 ALTER TABLE features ADD COLUMN last_error TEXT;
 "#;
+
+/// Migration 3 - adds the feature lifecycle status column
+pub const MIGRATION_ADD_STATUS: &str = r#"
+ALTER TABLE features ADD COLUMN status TEXT NOT NULL DEFAULT 'pending';
+"#;
+
+/// Migration 3 - backfill `status` from the old boolean once the column is added
+///
+/// Passing features without a verification command were only ever checked by
+/// hand, and security-blocked failures are recognisable by their error prefix.
+pub const MIGRATION_BACKFILL_STATUS: &str = r#"
+UPDATE features SET status = CASE
+    WHEN passes != 0 AND COALESCE(TRIM(verification_command), '') = '' THEN 'manual'
+    WHEN passes != 0 THEN 'passing'
+    WHEN last_error LIKE 'Security blocked%' THEN 'blocked'
+    WHEN last_error IS NOT NULL THEN 'failing'
+    ELSE 'pending'
+END;
+"#;
+
+/// Migration 4 - index and triggers keeping `passes` and `status` consistent
+///
+/// Writers that only
+/// touch `passes` (older agents using `db exec`) still get a sensible status,
+/// and writers that only set `status` keep `passes` correct for old readers.
+pub const FEATURE_STATUS_SYNC: &str = r#"
+CREATE INDEX IF NOT EXISTS idx_features_status ON features(status);
+
+CREATE TRIGGER IF NOT EXISTS sync_feature_status_from_passes
+    AFTER UPDATE OF passes ON features
+    FOR EACH ROW
+    WHEN (NEW.passes != 0) != (NEW.status IN ('passing', 'manual'))
+BEGIN
+    UPDATE features
+    SET status = CASE WHEN NEW.passes != 0 THEN 'passing' ELSE 'failing' END
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_feature_passes_from_status
+    AFTER UPDATE OF status ON features
+    FOR EACH ROW
+    WHEN (NEW.passes != 0) != (NEW.status IN ('passing', 'manual'))
+BEGIN
+    UPDATE features
+    SET passes = CASE WHEN NEW.status IN ('passing', 'manual') THEN 1 ELSE 0 END
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS sync_feature_status_on_insert
+    AFTER INSERT ON features
+    FOR EACH ROW
+    WHEN (NEW.passes != 0) != (NEW.status IN ('passing', 'manual'))
+BEGIN
+    UPDATE features
+    SET status = CASE WHEN NEW.passes != 0 THEN 'passing' ELSE 'pending' END
+    WHERE id = NEW.id;
+END;
+"#;
+
+/// Migration 5 - audit trail of feature status transitions
+///
+/// The trigger records every status change, including ones made with raw SQL.
+/// Code paths that know who they are write their name into the single-row
+/// `feature_history_context` table for the duration of the change; anything
+/// else is attributed to `sql`.
+pub const FEATURE_HISTORY: &str = r#"
+CREATE TABLE IF NOT EXISTS feature_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    feature_id INTEGER NOT NULL,
+    from_status TEXT,
+    to_status TEXT NOT NULL,
+    source TEXT NOT NULL,
+    session_id INTEGER,
+    commit_hash TEXT,
+    error TEXT,
+    created_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (feature_id) REFERENCES features(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_feature_history_feature ON feature_history(feature_id);
+
+CREATE TABLE IF NOT EXISTS feature_history_context (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    source TEXT NOT NULL DEFAULT 'sql'
+);
+
+INSERT OR IGNORE INTO feature_history_context (id) VALUES (1);
+
+CREATE TRIGGER IF NOT EXISTS record_feature_status_change
+    AFTER UPDATE OF status ON features
+    FOR EACH ROW
+    WHEN OLD.status IS NOT NEW.status
+BEGIN
+    INSERT INTO feature_history (feature_id, from_status, to_status, source, session_id, error)
+    VALUES (
+        NEW.id,
+        OLD.status,
+        NEW.status,
+        COALESCE((SELECT source FROM feature_history_context WHERE id = 1), 'sql'),
+        (SELECT id FROM sessions WHERE status = 'running' ORDER BY id DESC LIMIT 1),
+        CASE WHEN NEW.status IN ('passing', 'manual') THEN NULL
+             ELSE substr(NEW.last_error, 1, 500) END
+    );
+END;
+"#;
+
+/// Migration 6 - feature priority, as given by the spec or an imported backlog
+pub const MIGRATION_ADD_PRIORITY: &str = r#"
+ALTER TABLE features ADD COLUMN priority TEXT NOT NULL DEFAULT 'medium';
+"#;
+
+/// Migration 7 - stable feature keys
+///
+/// Existing rows are backfilled in Rust (see `migrations::add_feature_key`)
+/// before the unique index is created. Rows inserted with raw SQL and no key
+/// fall back to `feature-<id>`.
+pub const MIGRATION_ADD_KEY: &str = r#"
+ALTER TABLE features ADD COLUMN key TEXT;
+"#;
+
+pub const FEATURE_KEY_INDEX: &str = r#"
+CREATE UNIQUE INDEX IF NOT EXISTS idx_features_key ON features(key);
+
+CREATE TRIGGER IF NOT EXISTS assign_feature_key_on_insert
+    AFTER INSERT ON features
+    FOR EACH ROW
+    WHEN NEW.key IS NULL
+BEGIN
+    UPDATE features SET key = 'feature-' || NEW.id WHERE id = NEW.id;
+END;
+"#;
+
+/// Migration 8 - knowledge scope, expiry and the session that wrote each fact
+pub const MIGRATION_KNOWLEDGE_SCOPE: &str = r#"
+ALTER TABLE knowledge ADD COLUMN feature_id INTEGER;
+ALTER TABLE knowledge ADD COLUMN expires_at TEXT;
+ALTER TABLE knowledge ADD COLUMN session_id INTEGER;
+"#;
+
+/// Full-text index over knowledge, kept in sync by triggers
+///
+/// The insert trigger clears any previous entry for the key first, because
+/// `INSERT OR REPLACE` does not fire delete triggers.
+pub const KNOWLEDGE_FTS: &str = r#"
+CREATE VIRTUAL TABLE IF NOT EXISTS knowledge_fts USING fts5(key, value, category, description);
+
+CREATE TRIGGER IF NOT EXISTS knowledge_fts_insert
+    AFTER INSERT ON knowledge
+    FOR EACH ROW
+BEGIN
+    DELETE FROM knowledge_fts WHERE key = NEW.key;
+    INSERT INTO knowledge_fts (key, value, category, description)
+    VALUES (NEW.key, NEW.value, NEW.category, COALESCE(NEW.description, ''));
+END;
+
+CREATE TRIGGER IF NOT EXISTS knowledge_fts_update
+    AFTER UPDATE OF key, value, category, description ON knowledge
+    FOR EACH ROW
+BEGIN
+    DELETE FROM knowledge_fts WHERE key = OLD.key;
+    INSERT INTO knowledge_fts (key, value, category, description)
+    VALUES (NEW.key, NEW.value, NEW.category, COALESCE(NEW.description, ''));
+END;
+
+CREATE TRIGGER IF NOT EXISTS knowledge_fts_delete
+    AFTER DELETE ON knowledge
+    FOR EACH ROW
+BEGIN
+    DELETE FROM knowledge_fts WHERE key = OLD.key;
+END;
+
+DELETE FROM knowledge_fts;
+INSERT INTO knowledge_fts (key, value, category, description)
+SELECT key, value, category, COALESCE(description, '') FROM knowledge;
+"#;
+
+/// Migration 9 - pinned knowledge facts, always injected into prompts
+pub const MIGRATION_KNOWLEDGE_PINNED: &str = r#"
+ALTER TABLE knowledge ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+"#;
+
+/// Migration 10 - verification runs with per-test results
+pub const VERIFICATION_RUNS: &str = r#"
+CREATE TABLE IF NOT EXISTS verification_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    feature_id INTEGER NOT NULL,
+    command TEXT NOT NULL,
+    passed INTEGER NOT NULL,
+    exit_code INTEGER,
+    reporter TEXT, -- detected test reporter, NULL if the output wasn't recognized
+    tests_passed INTEGER NOT NULL DEFAULT 0,
+    tests_failed INTEGER NOT NULL DEFAULT 0,
+    tests_skipped INTEGER NOT NULL DEFAULT 0,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    summary TEXT,
+    session_id INTEGER,
+    created_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (feature_id) REFERENCES features(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_verification_runs_feature ON verification_runs(feature_id);
+
+CREATE TABLE IF NOT EXISTS verification_tests (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    status TEXT NOT NULL, -- 'passed', 'failed', 'skipped'
+    duration_ms INTEGER,
+    message TEXT,
+    FOREIGN KEY (run_id) REFERENCES verification_runs(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_verification_tests_run ON verification_tests(run_id);
+"#;
+
+/// Migration 11 - multi-step verification pipelines
+pub const MIGRATION_VERIFICATION_STEPS: &str = r#"
+ALTER TABLE features ADD COLUMN verification_steps TEXT; -- JSON array, NULL = use verification_command
+ALTER TABLE verification_runs ADD COLUMN step TEXT; -- pipeline step the run belongs to
+"#;
+
+/// Migration 12 - per-feature verification timeout
+pub const MIGRATION_VERIFICATION_TIMEOUT: &str = r#"
+ALTER TABLE features ADD COLUMN verification_timeout_secs INTEGER; -- NULL = [security] verification_timeout_secs
+"#;
+
+/// Migration 13 - per-feature flake statistics
+pub const FEATURE_FLAKINESS: &str = r#"
+CREATE TABLE IF NOT EXISTS feature_flakiness (
+    feature_id INTEGER PRIMARY KEY,
+    checks INTEGER NOT NULL DEFAULT 0, -- verification checks, each possibly several runs
+    flaky_checks INTEGER NOT NULL DEFAULT 0, -- checks whose runs disagreed
+    last_flake_at TEXT,
+    FOREIGN KEY (feature_id) REFERENCES features(id) ON DELETE CASCADE
+);
+"#;
+
+/// Migration 14 - when each feature was last verified
+pub const MIGRATION_LAST_VERIFIED: &str = r#"
+ALTER TABLE features ADD COLUMN last_verified_at TEXT; -- last verification check, NULL = never
+"#;
+
+/// Migration 15 - passing verification results keyed by tree state
+pub const VERIFICATION_CACHE: &str = r#"
+CREATE TABLE IF NOT EXISTS verification_cache (
+    tree_hash TEXT NOT NULL, -- git tree of the working directory
+    pipeline TEXT NOT NULL, -- JSON of the verification steps
+    feature_id INTEGER,
+    created_at TEXT DEFAULT (datetime('now')),
+    PRIMARY KEY (tree_hash, pipeline)
+);
+"#;
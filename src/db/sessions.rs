@@ -8,7 +8,10 @@ use std::sync::{Arc, Mutex};
 
 /// Session status values
 pub mod status {
+    pub const RUNNING: &str = "running";
     pub const COMPLETED: &str = "completed";
+    /// Left running by a supervisor that exited without closing it
+    pub const INTERRUPTED: &str = "interrupted";
 }
 
 /// Repository for session operations
@@ -47,7 +50,6 @@ impl SessionRepository {
     }
 
     /// List all sessions, ordered by most recent first
-    #[allow(dead_code)]
     pub fn list_sessions(&self) -> Result<Vec<Session>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
@@ -127,6 +129,44 @@ impl SessionRepository {
         Ok(Some((session, events)))
     }
 
+    /// Record the start of a supervisor session, returning its id
+    ///
+    /// Sessions still marked running belong to a supervisor that crashed or was
+    /// killed, so they are closed as interrupted first.
+    pub fn start_session(&self, session_number: usize, features_before: usize) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "UPDATE sessions SET status = ?1, completed_at = datetime('now') WHERE status = ?2",
+            params![status::INTERRUPTED, status::RUNNING],
+        )
+        .context("Failed to close interrupted sessions")?;
+        conn.execute(
+            "INSERT INTO sessions (session_number, features_before, features_after, status)
+             VALUES (?1, ?2, ?2, ?3)",
+            params![
+                session_number as i64,
+                features_before as i64,
+                status::RUNNING
+            ],
+        )
+        .context("Failed to start session")?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Mark a session as completed with the passing count it ended on
+    pub fn complete_session(&self, session_id: i64, features_after: usize) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE sessions SET status = ?2, features_after = ?3, completed_at = datetime('now')
+             WHERE id = ?1",
+            params![session_id, status::COMPLETED, features_after as i64],
+        )
+        .context("Failed to complete session")?;
+        Ok(())
+    }
+
     /// Get session statistics
     pub fn get_stats(&self) -> Result<SessionStats> {
         let conn = self.conn.lock().unwrap();
@@ -161,7 +201,7 @@ impl SessionRepository {
 }
 
 /// Session statistics summary
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, serde::Serialize)]
 pub struct SessionStats {
     pub total_sessions: usize,
     pub completed_sessions: usize,
@@ -182,4 +222,22 @@ mod tests {
         assert_eq!(stats.completed_sessions, 0);
         assert_eq!(stats.total_features_completed, 0);
     }
+
+    #[test]
+    fn test_session_lifecycle() {
+        let (_temp, db) = setup_test_db();
+        let repo = db.sessions();
+
+        let crashed = repo.start_session(1, 0).unwrap();
+        let current = repo.start_session(1, 0).unwrap();
+        repo.complete_session(current, 2).unwrap();
+
+        let (session, _) = repo.get_session_with_events(crashed).unwrap().unwrap();
+        assert_eq!(session.status, "interrupted");
+
+        let stats = repo.get_stats().unwrap();
+        assert_eq!(stats.total_sessions, 2);
+        assert_eq!(stats.completed_sessions, 1);
+        assert_eq!(stats.total_features_completed, 2);
+    }
 }
@@ -0,0 +1,440 @@
+//! Verification run repository
+//!
+//! Every verification command the supervisor or `db check` runs is recorded
+//! with its outcome and, when the output came from a recognized test reporter,
+//! the result of each individual test. Passing results are also cached by
+//! the git tree they ran against, so unchanged code isn't verified twice.
+
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// Outcome of a single test within a verification run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+impl TestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TestStatus::Passed => "passed",
+            TestStatus::Failed => "failed",
+            TestStatus::Skipped => "skipped",
+        }
+    }
+}
+
+impl FromStr for TestStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "passed" => Ok(TestStatus::Passed),
+            "failed" => Ok(TestStatus::Failed),
+            "skipped" => Ok(TestStatus::Skipped),
+            other => bail!("Unknown test status: {}", other),
+        }
+    }
+}
+
+/// A single test reported by a verification command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TestCase {
+    pub name: String,
+    pub status: TestStatus,
+    pub duration_ms: Option<u64>,
+    /// Failure message, for failed tests
+    pub message: Option<String>,
+}
+
+/// A verification run to record
+#[derive(Debug, Clone)]
+pub struct NewVerificationRun<'a> {
+    pub feature_id: i64,
+    /// Pipeline step name, for features verified in several steps
+    pub step: Option<&'a str>,
+    pub command: &'a str,
+    pub passed: bool,
+    pub exit_code: Option<i32>,
+    /// Test reporter the output was parsed with, if any
+    pub reporter: Option<&'a str>,
+    pub duration_ms: u64,
+    /// Concise description of what failed
+    pub summary: Option<&'a str>,
+    pub tests: &'a [TestCase],
+}
+
+/// A recorded verification run
+#[derive(Debug, Clone, Serialize)]
+pub struct VerificationRun {
+    pub id: i64,
+    pub feature_id: i64,
+    pub step: Option<String>,
+    pub command: String,
+    pub passed: bool,
+    pub exit_code: Option<i32>,
+    pub reporter: Option<String>,
+    pub tests_passed: usize,
+    pub tests_failed: usize,
+    pub tests_skipped: usize,
+    pub duration_ms: u64,
+    pub summary: Option<String>,
+    pub session_id: Option<i64>,
+    pub created_at: String,
+}
+
+/// How often a feature's verification has flaked
+#[derive(Debug, Clone, Serialize)]
+pub struct FlakeStats {
+    pub feature_id: i64,
+    pub key: String,
+    pub description: String,
+    pub category: String,
+    /// Verification checks recorded, each possibly several runs
+    pub checks: u32,
+    /// Checks whose runs had mixed outcomes
+    pub flaky_checks: u32,
+    pub last_flake_at: Option<String>,
+}
+
+impl FlakeStats {
+    /// Share of checks that flaked, between 0.0 and 1.0
+    pub fn rate(&self) -> f64 {
+        if self.checks == 0 {
+            0.0
+        } else {
+            self.flaky_checks as f64 / self.checks as f64
+        }
+    }
+
+    /// Whether the feature is flaky under the given threshold and minimum check count
+    pub fn is_flaky(&self, threshold: f64, min_checks: u32) -> bool {
+        self.checks >= min_checks && self.flaky_checks > 0 && self.rate() >= threshold
+    }
+}
+
+/// Days a cached verification result is kept
+pub const CACHE_MAX_AGE_DAYS: u32 = 30;
+
+const RUN_COLUMNS: &str = "id, feature_id, command, passed, exit_code, reporter, tests_passed, \
+                           tests_failed, tests_skipped, duration_ms, summary, session_id, created_at, \
+                           step";
+
+/// Repository for verification runs
+pub struct VerificationRepository {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl VerificationRepository {
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Self {
+        Self { conn }
+    }
+
+    /// Record a run and its tests; returns the run id
+    ///
+    /// The run is attributed to the session currently running, if any.
+    pub fn record(&self, run: &NewVerificationRun) -> Result<i64> {
+        let count = |status| run.tests.iter().filter(|t| t.status == status).count() as i64;
+
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO verification_runs
+                 (feature_id, command, passed, exit_code, reporter, tests_passed, tests_failed,
+                  tests_skipped, duration_ms, summary, step, session_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11,
+                     (SELECT id FROM sessions WHERE status = 'running' ORDER BY id DESC LIMIT 1))",
+            params![
+                run.feature_id,
+                run.command,
+                run.passed,
+                run.exit_code,
+                run.reporter,
+                count(TestStatus::Passed),
+                count(TestStatus::Failed),
+                count(TestStatus::Skipped),
+                run.duration_ms as i64,
+                run.summary,
+                run.step,
+            ],
+        )
+        .context("Failed to record verification run")?;
+        let run_id = tx.last_insert_rowid();
+
+        for test in run.tests {
+            tx.execute(
+                "INSERT INTO verification_tests (run_id, name, status, duration_ms, message)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    run_id,
+                    test.name,
+                    test.status.as_str(),
+                    test.duration_ms.map(|d| d as i64),
+                    test.message,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// Most recent runs, newest first, optionally for one feature
+    pub fn list(&self, feature_id: Option<i64>, limit: usize) -> Result<Vec<VerificationRun>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM verification_runs
+             WHERE ?1 IS NULL OR feature_id = ?1
+             ORDER BY id DESC LIMIT ?2",
+            RUN_COLUMNS
+        ))?;
+        let runs = stmt
+            .query_map(params![feature_id, limit as i64], map_run)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(runs)
+    }
+
+    /// A run by id
+    pub fn get(&self, id: i64) -> Result<Option<VerificationRun>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM verification_runs WHERE id = ?1",
+            RUN_COLUMNS
+        ))?;
+        Ok(stmt.query_row(params![id], map_run).optional()?)
+    }
+
+    /// The latest run for a feature
+    pub fn latest(&self, feature_id: i64) -> Result<Option<VerificationRun>> {
+        Ok(self.list(Some(feature_id), 1)?.into_iter().next())
+    }
+
+    /// Count a verification check towards the feature's flake rate
+    ///
+    /// Also stamps the feature's `last_verified_at`.
+    pub fn record_check(&self, feature_id: i64, flaky: bool) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO feature_flakiness (feature_id, checks, flaky_checks, last_flake_at)
+             VALUES (?1, 1, ?2, CASE WHEN ?2 THEN datetime('now') END)
+             ON CONFLICT(feature_id) DO UPDATE SET
+                 checks = checks + 1,
+                 flaky_checks = flaky_checks + excluded.flaky_checks,
+                 last_flake_at = COALESCE(excluded.last_flake_at, last_flake_at)",
+            params![feature_id, flaky],
+        )
+        .context("Failed to record verification check")?;
+        tx.execute(
+            "UPDATE features SET last_verified_at = datetime('now') WHERE id = ?1",
+            params![feature_id],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Stamp a feature's `last_verified_at` without counting a check
+    ///
+    /// Used when a cached result stands in for running the verification.
+    pub fn mark_verified(&self, feature_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE features SET last_verified_at = datetime('now') WHERE id = ?1",
+            params![feature_id],
+        )?;
+        Ok(())
+    }
+
+    /// When `pipeline` last passed against `tree_hash`, if it did
+    pub fn cached_pass(&self, tree_hash: &str, pipeline: &str) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn
+            .query_row(
+                "SELECT created_at FROM verification_cache WHERE tree_hash = ?1 AND pipeline = ?2",
+                params![tree_hash, pipeline],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Remember that `pipeline` passed against `tree_hash`
+    ///
+    /// Entries older than [`CACHE_MAX_AGE_DAYS`] are dropped at the same time.
+    pub fn cache_pass(&self, tree_hash: &str, pipeline: &str, feature_id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO verification_cache (tree_hash, pipeline, feature_id)
+             VALUES (?1, ?2, ?3)",
+            params![tree_hash, pipeline, feature_id],
+        )
+        .context("Failed to cache verification result")?;
+        conn.execute(
+            "DELETE FROM verification_cache WHERE created_at < datetime('now', ?1)",
+            params![format!("-{} days", CACHE_MAX_AGE_DAYS)],
+        )?;
+        Ok(())
+    }
+
+    /// Hours since each verified feature was last checked
+    ///
+    /// Features that were never verified are absent.
+    pub fn hours_since_verified(&self) -> Result<HashMap<i64, f64>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, (julianday('now') - julianday(last_verified_at)) * 24 FROM features
+             WHERE last_verified_at IS NOT NULL",
+        )?;
+        let ages = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+        Ok(ages)
+    }
+
+    /// Flake statistics of every checked feature, highest flake rate first
+    pub fn flake_stats(&self) -> Result<Vec<FlakeStats>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT f.id, f.key, f.description, f.category, s.checks, s.flaky_checks, s.last_flake_at
+             FROM feature_flakiness s JOIN features f ON f.id = s.feature_id
+             ORDER BY CAST(s.flaky_checks AS REAL) / MAX(s.checks, 1) DESC, s.flaky_checks DESC, f.id",
+        )?;
+        let stats = stmt
+            .query_map([], |row| {
+                Ok(FlakeStats {
+                    feature_id: row.get(0)?,
+                    key: row.get(1)?,
+                    description: row.get(2)?,
+                    category: row.get(3)?,
+                    checks: row.get(4)?,
+                    flaky_checks: row.get(5)?,
+                    last_flake_at: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(stats)
+    }
+
+    /// Tests recorded for a run, in reported order
+    pub fn tests(&self, run_id: i64) -> Result<Vec<TestCase>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT name, status, duration_ms, message FROM verification_tests
+             WHERE run_id = ?1 ORDER BY id",
+        )?;
+        let rows = stmt
+            .query_map(params![run_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.into_iter()
+            .map(|(name, status, duration_ms, message)| {
+                Ok(TestCase {
+                    name,
+                    status: status.parse()?,
+                    duration_ms: duration_ms.map(|d| d as u64),
+                    message,
+                })
+            })
+            .collect()
+    }
+}
+
+fn map_run(row: &rusqlite::Row) -> rusqlite::Result<VerificationRun> {
+    Ok(VerificationRun {
+        id: row.get(0)?,
+        feature_id: row.get(1)?,
+        step: row.get(13)?,
+        command: row.get(2)?,
+        passed: row.get(3)?,
+        exit_code: row.get(4)?,
+        reporter: row.get(5)?,
+        tests_passed: row.get::<_, i64>(6)? as usize,
+        tests_failed: row.get::<_, i64>(7)? as usize,
+        tests_skipped: row.get::<_, i64>(8)? as usize,
+        duration_ms: row.get::<_, i64>(9)? as u64,
+        summary: row.get(10)?,
+        session_id: row.get(11)?,
+        created_at: row.get(12)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::features::Feature;
+    use crate::db::test_utils::tests::setup_test_db;
+
+    #[test]
+    fn test_runs_are_recorded_with_their_tests() {
+        let (_dir, db) = setup_test_db();
+        let feature_id = db
+            .features()
+            .insert(&Feature::new("functional", "Login"))
+            .unwrap();
+
+        let tests = vec![
+            TestCase {
+                name: "login_ok".to_string(),
+                status: TestStatus::Passed,
+                duration_ms: Some(12),
+                message: None,
+            },
+            TestCase {
+                name: "login_bad_password".to_string(),
+                status: TestStatus::Failed,
+                duration_ms: None,
+                message: Some("expected 401, got 200".to_string()),
+            },
+        ];
+        let repo = db.verification();
+        for passed in [true, false] {
+            repo.record(&NewVerificationRun {
+                feature_id,
+                step: None,
+                command: "cargo test login",
+                passed,
+                exit_code: Some(if passed { 0 } else { 101 }),
+                reporter: Some("cargo"),
+                duration_ms: 1500,
+                summary: None,
+                tests: if passed { &[] } else { &tests },
+            })
+            .unwrap();
+        }
+
+        let latest = repo.latest(feature_id).unwrap().unwrap();
+        assert!(!latest.passed);
+        assert_eq!((latest.tests_passed, latest.tests_failed), (1, 1));
+        assert_eq!(repo.tests(latest.id).unwrap(), tests);
+        assert_eq!(repo.list(None, 10).unwrap().len(), 2);
+        assert!(repo.list(Some(feature_id + 1), 10).unwrap().is_empty());
+
+        for flaky in [false, true, false, true] {
+            repo.record_check(feature_id, flaky).unwrap();
+        }
+        let stats = repo.flake_stats().unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!((stats[0].checks, stats[0].flaky_checks), (4, 2));
+        assert!(stats[0].last_flake_at.is_some());
+        assert!(stats[0].is_flaky(0.5, 3));
+        assert!(!stats[0].is_flaky(0.5, 5));
+        assert!(repo.hours_since_verified().unwrap()[&feature_id] < 1.0);
+
+        assert!(repo.cached_pass("tree", "[]").unwrap().is_none());
+        repo.cache_pass("tree", "[]", feature_id).unwrap();
+        assert!(repo.cached_pass("tree", "[]").unwrap().is_some());
+        assert!(repo.cached_pass("other-tree", "[]").unwrap().is_none());
+    }
+}
@@ -3,139 +3,597 @@
 //! This module provides functionality to parse feature_list.json and run
 //! regression checks on features marked as passing.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
-use crate::autonomous::security;
-use crate::config::SecurityConfig;
+use crate::autonomous::git;
+use crate::autonomous::parallel::isolation::WorkerSlot;
+use crate::autonomous::parallel::worktree;
+use crate::autonomous::verification::{self, Verdict, VerificationCache};
+use crate::config::{Config, FlakyConfig, ParallelConfig, SecurityConfig};
 use crate::db::features::Feature;
+use crate::db::Database;
 
 /// Result of a single feature check
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CheckResult {
+    pub feature_id: Option<i64>,
+    pub category: String,
     pub description: String,
     pub passed: bool,
     pub error_message: Option<String>,
     pub was_automated: bool,
+    /// Verification runs the check took (more than one when a run failed)
+    pub runs: usize,
+    pub flaky: bool,
+    /// Passed against the same tree before, so the verification wasn't run
+    pub cached: bool,
+    pub duration_ms: u64,
+    /// The verification pipeline's runs, if they could be started
+    #[serde(skip)]
+    pub verdict: Option<Verdict>,
 }
 
 /// Summary of regression check execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RegressionSummary {
     pub total_features: usize,
     pub passing_features: usize,
     pub automated_passed: usize,
     pub automated_failed: usize,
+    /// Automated checks whose runs disagreed
+    pub flaky: usize,
+    /// Automated checks answered from the verification cache
+    pub cached: usize,
     pub manual_required: usize,
     pub results: Vec<CheckResult>,
 }
 
+/// How a regression check selects and runs features
+#[derive(Clone)]
+pub struct CheckOptions<'a> {
+    pub category_filter: Option<&'a str>,
+    pub sample_size: Option<usize>,
+    pub verbose: bool,
+    /// Features verified concurrently
+    pub jobs: usize,
+    /// Give each job its own git worktree of HEAD (needs a clean working tree)
+    pub isolate: bool,
+    /// Security policy, timeout and environment for verification commands
+    pub security: &'a SecurityConfig,
+    pub flaky: &'a FlakyConfig,
+    /// Port ranges handed to concurrent jobs
+    pub parallel: &'a ParallelConfig,
+    /// Reuse and record passes for the current tree state
+    pub cache: Option<&'a VerificationCache<'a>>,
+}
+
+impl<'a> CheckOptions<'a> {
+    /// Sequential check of every passing feature under the project's configuration
+    pub fn new(config: &'a Config) -> Self {
+        Self {
+            category_filter: None,
+            sample_size: None,
+            verbose: false,
+            jobs: 1,
+            isolate: false,
+            security: &config.security,
+            flaky: &config.flaky,
+            parallel: &config.parallel,
+            cache: None,
+        }
+    }
+}
+
+/// Where one job runs its verification commands
+struct Job {
+    root: PathBuf,
+    env: Vec<(&'static str, String)>,
+    /// Worktree created for the job, removed when the job is dropped
+    worktree: Option<PathBuf>,
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        // Also runs when a later job's setup fails or a check panics
+        if let Some(path) = &self.worktree {
+            let _ = worktree::remove_worktree(path, "");
+        }
+    }
+}
+
 /// Run regression checks on all passing features
+///
+/// Each feature passes when its first run passes, when its category's
+/// `[flaky]` quorum of re-runs passes after a failure, or, with a cache,
+/// when its pipeline already passed against the same tree.
+/// With several jobs, features are verified concurrently; every job gets its
+/// own port range and, with `isolate`, its own worktree.
 pub fn run_regression_check(
     features: &[Feature],
-    category_filter: Option<&str>,
-    sample_size: Option<usize>,
-    verbose: bool,
-    security_config: Option<&SecurityConfig>,
+    options: &CheckOptions,
 ) -> Result<RegressionSummary> {
     let total_features = features.len();
     let mut passing_features: Vec<_> = features
         .iter()
         .filter(|f| f.passes)
         .filter(|f| {
-            category_filter
+            options
+                .category_filter
                 .map(|cat| f.category.eq_ignore_ascii_case(cat))
                 .unwrap_or(true)
         })
         .collect();
 
-    if let Some(limit) = sample_size {
+    if let Some(limit) = options.sample_size {
         passing_features.truncate(limit.max(1));
     }
 
-    let mut automated_passed = 0;
-    let mut automated_failed = 0;
-    let mut manual_required = 0;
-    let mut results = Vec::new();
-
-    for feature in &passing_features {
-        if verbose {
-            println!("Checking: {}", feature.description);
-        }
-
-        if let Some(ref cmd) = feature.verification_command {
-            // Use security-validated command runner if config provided, otherwise default security
-            let default_security = SecurityConfig::default();
-            let sec_cfg = security_config.unwrap_or(&default_security);
+    let security = options.security;
 
-            let output = match security::run_verified_command(cmd, sec_cfg, None) {
-                Ok(out) => out,
-                Err(e) => {
-                    if verbose {
-                        println!("  🚫 BLOCKED: {}", e);
-                    }
-                    automated_failed += 1;
-                    results.push(CheckResult {
-                        description: feature.description.clone(),
-                        passed: false,
-                        error_message: Some(format!("Security blocked: {}", e)),
-                        was_automated: true,
-                    });
-                    continue;
-                }
-            };
-
-            if output.status.success() {
-                automated_passed += 1;
-                results.push(CheckResult {
-                    description: feature.description.clone(),
-                    passed: true,
-                    error_message: None,
-                    was_automated: true,
-                });
-
-                if verbose {
-                    println!("  ✓ PASS");
+    // Features that already passed against this exact tree aren't run again
+    let mut slots = vec![None; passing_features.len()];
+    if let Some(cache) = options.cache {
+        for (index, feature) in passing_features.iter().enumerate() {
+            let steps = feature.verification_pipeline();
+            if steps.is_empty() {
+                continue;
+            }
+            if let Some(passed_at) = cache.lookup(&steps)? {
+                if options.verbose {
+                    println!("↺ CACHED: {} (passed {})", feature.description, passed_at);
                 }
-            } else {
-                automated_failed += 1;
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-                results.push(CheckResult {
-                    description: feature.description.clone(),
-                    passed: false,
-                    error_message: Some(stderr),
+                slots[index] = Some(CheckResult {
                     was_automated: true,
+                    cached: true,
+                    ..unchecked(feature)
                 });
+            }
+        }
+    }
+    let pending: Vec<usize> = (0..slots.len()).filter(|&i| slots[i].is_none()).collect();
 
-                if verbose {
-                    println!("  ✗ FAIL");
+    let jobs = if pending.is_empty() {
+        Vec::new()
+    } else {
+        prepare_jobs(options, pending.len())?
+    };
+    let next = AtomicUsize::new(0);
+    let slots = Mutex::new(slots);
+    std::thread::scope(|scope| {
+        for job in &jobs {
+            scope.spawn(|| {
+                while let Some(&index) = pending.get(next.fetch_add(1, Ordering::SeqCst)) {
+                    let feature = passing_features[index];
+                    let result = check_feature(feature, job, security, options);
+                    slots.lock().unwrap()[index] = Some(result);
                 }
-            }
-        } else {
-            // No automated verification available
-            manual_required += 1;
-            results.push(CheckResult {
-                description: feature.description.clone(),
-                passed: true, // Assume manual-only features still pass
-                error_message: None,
-                was_automated: false,
             });
+        }
+    });
+    drop(jobs);
 
-            if verbose {
-                println!("  ○ MANUAL (no verification_command)");
+    let results: Vec<CheckResult> = slots.into_inner().unwrap().into_iter().flatten().collect();
+    if let Some(cache) = options.cache {
+        for (feature, result) in passing_features.iter().zip(&results) {
+            if let (Some(id), Some(verdict)) = (feature.id, &result.verdict) {
+                if verdict.success() {
+                    cache.store(&feature.verification_pipeline(), id)?;
+                }
             }
         }
     }
-
+    let count = |pred: fn(&CheckResult) -> bool| results.iter().filter(|r| pred(r)).count();
     Ok(RegressionSummary {
         total_features,
         passing_features: passing_features.len(),
-        automated_passed,
-        automated_failed,
-        manual_required,
+        automated_passed: count(|r| r.was_automated && r.passed),
+        automated_failed: count(|r| r.was_automated && !r.passed),
+        flaky: count(|r| r.flaky),
+        cached: count(|r| r.cached),
+        manual_required: count(|r| !r.was_automated),
         results,
     })
 }
 
+/// Set up the working directory and environment of each job
+fn prepare_jobs(options: &CheckOptions, features: usize) -> Result<Vec<Job>> {
+    let root = verification::project_root();
+    let count = options.jobs.clamp(1, features.max(1));
+    if count == 1 && !options.isolate {
+        return Ok(vec![Job {
+            root,
+            env: Vec::new(),
+            worktree: None,
+        }]);
+    }
+
+    let isolate = options.isolate
+        && {
+            let clean = git::is_clean().unwrap_or(false);
+            if !clean {
+                println!("⚠️  Working tree has uncommitted changes or is not a git repository; jobs share the project directory");
+            }
+            clean
+        };
+
+    let run_id = std::process::id();
+    let mut jobs = Vec::with_capacity(count);
+    for id in 0..count {
+        let slot = WorkerSlot::new(id, options.parallel)?;
+        let worktree = if isolate {
+            let path = std::env::temp_dir().join(format!("forger-check-{}-{}", run_id, id));
+            worktree::create_detached_worktree(&path)
+                .with_context(|| format!("Failed to create worktree for check job {}", id))?;
+            Some(path)
+        } else {
+            None
+        };
+        jobs.push(Job {
+            root: worktree.clone().unwrap_or_else(|| root.clone()),
+            env: slot.env(),
+            worktree,
+        });
+    }
+    Ok(jobs)
+}
+
+/// Verify one feature under its category's quorum
+fn check_feature(
+    feature: &Feature,
+    job: &Job,
+    security: &SecurityConfig,
+    options: &CheckOptions,
+) -> CheckResult {
+    let mut result = unchecked(feature);
+
+    let mut steps = feature.verification_pipeline();
+    if steps.is_empty() {
+        // Manual features are tracked by their own state, not re-verified here
+        if options.verbose {
+            println!(
+                "○ MANUAL: {} (no verification_command)",
+                feature.description
+            );
+        }
+        return result;
+    }
+    result.was_automated = true;
+    for step in &mut steps {
+        for (key, value) in &job.env {
+            step.env
+                .entry(key.to_string())
+                .or_insert_with(|| value.clone());
+        }
+    }
+
+    if let Err(e) = verification::ensure_pipeline_allowed(&steps, security) {
+        if options.verbose {
+            println!("🚫 BLOCKED: {}: {}", feature.description, e);
+        }
+        result.passed = false;
+        result.error_message = Some(format!("Security blocked: {}", e));
+        return result;
+    }
+
+    let quorum = options.flaky.quorum_for(&feature.category);
+    let verdict = match verification::run_quorum(&steps, security, &job.root, quorum) {
+        Ok(verdict) => verdict,
+        Err(e) => {
+            // A broken step definition, not a blocked command: the fix is to rewrite it
+            if options.verbose {
+                println!("✗ ERROR: {}: {}", feature.description, e);
+            }
+            result.passed = false;
+            result.error_message = Some(format!("Verification error: {}", e));
+            return result;
+        }
+    };
+
+    result.passed = verdict.success();
+    result.flaky = verdict.flaky();
+    result.runs = verdict.runs.len();
+    result.duration_ms = verdict.duration().as_millis() as u64;
+    if !result.passed {
+        result.error_message = Some(verdict.error_message());
+    }
+    if options.verbose {
+        let mark = if result.passed {
+            "✓ PASS"
+        } else {
+            "✗ FAIL"
+        };
+        if result.flaky {
+            println!(
+                "{}: {} (flaky, passed {} of {} runs)",
+                mark,
+                feature.description,
+                verdict.passes(),
+                verdict.runs.len()
+            );
+        } else {
+            println!("{}: {}", mark, feature.description);
+        }
+    }
+    result.verdict = Some(verdict);
+    result
+}
+
+/// A passing result for a feature whose verification wasn't run
+fn unchecked(feature: &Feature) -> CheckResult {
+    CheckResult {
+        feature_id: feature.id,
+        category: feature.category.clone(),
+        description: feature.description.clone(),
+        passed: true,
+        error_message: None,
+        was_automated: false,
+        runs: 0,
+        flaky: false,
+        cached: false,
+        duration_ms: 0,
+        verdict: None,
+    }
+}
+
+/// Keep the features whose history touches files changed since `commit`
+///
+/// Features with no known files are left out.
+pub fn features_changed_since(
+    db: &Database,
+    features: Vec<Feature>,
+    commit: &str,
+) -> Result<Vec<Feature>> {
+    let changed: BTreeSet<String> = git::files_changed_since(commit)?.into_iter().collect();
+    let touched = touched_features(db, &features, &changed)?;
+    Ok(features
+        .into_iter()
+        .zip(touched)
+        .filter_map(|(feature, touched)| touched.then_some(feature))
+        .collect())
+}
+
+/// For each feature, whether one of its commits changed any of `files`
+///
+/// A feature's commits are those attached to its status history and its
+/// `commit_completed_feature` commits. A single `git log` over `files` finds
+/// every candidate commit, however many features there are.
+fn touched_features(
+    db: &Database,
+    features: &[Feature],
+    files: &BTreeSet<String>,
+) -> Result<Vec<bool>> {
+    let paths: Vec<String> = files.iter().cloned().collect();
+    let commits = git::commits_touching(&paths).unwrap_or_default();
+    if commits.is_empty() {
+        return Ok(vec![false; features.len()]);
+    }
+    let hashes: HashSet<&str> = commits.iter().map(|c| c.hash.as_str()).collect();
+
+    let mut touched = Vec::with_capacity(features.len());
+    for feature in features {
+        let in_history = match feature.id {
+            Some(id) => db
+                .history()
+                .list_for_feature(id)?
+                .iter()
+                .filter_map(|entry| entry.commit_hash.as_deref())
+                .any(|hash| hashes.contains(hash)),
+            None => false,
+        };
+        touched.push(
+            in_history
+                || commits
+                    .iter()
+                    .any(|c| c.is_for_feature(&feature.key, &feature.description)),
+        );
+    }
+    Ok(touched)
+}
+
+/// Staleness, in hours, assumed for features that were never verified
+const NEVER_VERIFIED_HOURS: f64 = 24.0 * 365.0;
+
+/// Priority, in hours of staleness, added for features the last commit touched
+const TOUCHED_WEIGHT_HOURS: f64 = 48.0;
+
+/// Priority, in hours of staleness, added for a feature that flakes on every check
+const FLAKY_WEIGHT_HOURS: f64 = 24.0;
+
+/// Why a feature is worth re-verifying
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegressionRisk {
+    /// Hours since the feature was last verified, `None` if never
+    pub hours_since_verified: Option<f64>,
+    /// The last commit changed files the feature's history touched
+    pub touched_by_last_commit: bool,
+    /// Share of the feature's checks that flaked
+    pub flake_rate: f64,
+}
+
+impl RegressionRisk {
+    /// Sampling priority: staleness in hours plus weights for the other risks
+    pub fn score(&self) -> f64 {
+        let mut score = self.hours_since_verified.unwrap_or(NEVER_VERIFIED_HOURS);
+        if self.touched_by_last_commit {
+            score += TOUCHED_WEIGHT_HOURS;
+        }
+        score + self.flake_rate * FLAKY_WEIGHT_HOURS
+    }
+
+    /// Short explanation for logs, e.g. "never verified, touched by last commit"
+    pub fn describe(&self) -> String {
+        let mut reasons = vec![match self.hours_since_verified {
+            Some(hours) => format!("verified {:.1}h ago", hours),
+            None => "never verified".to_string(),
+        }];
+        if self.touched_by_last_commit {
+            reasons.push("touched by last commit".to_string());
+        }
+        if self.flake_rate > 0.0 {
+            reasons.push(format!("{:.0}% flaky", self.flake_rate * 100.0));
+        }
+        reasons.join(", ")
+    }
+}
+
+/// Pick up to `size` passing features to re-verify, riskiest first
+///
+/// Features not verified recently come first, so repeated samples rotate
+/// through the whole suite; features the last commit touched and features
+/// with a flaky history are moved up.
+pub fn sample_features(
+    db: &Database,
+    features: &[Feature],
+    size: usize,
+) -> Result<Vec<(Feature, RegressionRisk)>> {
+    let ages = db.verification().hours_since_verified()?;
+    let flake_rates: HashMap<i64, f64> = db
+        .verification()
+        .flake_stats()?
+        .into_iter()
+        .map(|stats| (stats.feature_id, stats.rate()))
+        .collect();
+    let last_commit: BTreeSet<String> = git::files_in_commits(&["HEAD".to_string()])
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+
+    // Manual features are never re-verified, so they would always look stale
+    let eligible: Vec<Feature> = features
+        .iter()
+        .filter(|f| f.passes && f.has_verification())
+        .cloned()
+        .collect();
+    let touched = touched_features(db, &eligible, &last_commit)?;
+
+    let mut candidates = Vec::new();
+    for (feature, touched_by_last_commit) in eligible.into_iter().zip(touched) {
+        let id = feature.id.unwrap_or(0);
+        let risk = RegressionRisk {
+            hours_since_verified: ages.get(&id).copied(),
+            touched_by_last_commit,
+            flake_rate: flake_rates.get(&id).copied().unwrap_or(0.0),
+        };
+        candidates.push((feature, risk));
+    }
+    candidates.sort_by(|(a, a_risk), (b, b_risk)| {
+        b_risk
+            .score()
+            .total_cmp(&a_risk.score())
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    candidates.truncate(size.max(1));
+    Ok(candidates)
+}
+
+/// Store every verification step run by a check in the `verification_runs`
+/// table and count each check towards its feature's flake rate
+///
+/// Cached results only stamp the feature's `last_verified_at`.
+pub fn record_runs(db: &Database, summary: &RegressionSummary) -> Result<()> {
+    for result in &summary.results {
+        let Some(feature_id) = result.feature_id else {
+            continue;
+        };
+        if let Some(verdict) = &result.verdict {
+            verdict.record(db, feature_id)?;
+        } else if result.cached {
+            db.verification().mark_verified(feature_id)?;
+        }
+    }
+    Ok(())
+}
+
+/// Regression results as JSON, for CI
+pub fn json_report(summary: &RegressionSummary) -> Result<String> {
+    Ok(serde_json::to_string_pretty(summary)?)
+}
+
+/// Regression results as a JUnit XML report, one test case per feature
+///
+/// Manual features are reported as skipped.
+pub fn junit_report(summary: &RegressionSummary) -> String {
+    let seconds = |ms: u64| format!("{:.3}", ms as f64 / 1000.0);
+    let total_ms: u64 = summary.results.iter().map(|r| r.duration_ms).sum();
+    let counts = format!(
+        r#"tests="{}" failures="{}" skipped="{}" time="{}""#,
+        summary.results.len(),
+        summary.automated_failed,
+        summary.manual_required,
+        seconds(total_ms)
+    );
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!("<testsuites name=\"regression\" {}>\n", counts));
+    xml.push_str(&format!("  <testsuite name=\"regression\" {}>\n", counts));
+    for result in &summary.results {
+        let name = match result.feature_id {
+            Some(id) => format!("#{} {}", id, result.description),
+            None => result.description.clone(),
+        };
+        xml.push_str(&format!(
+            "    <testcase classname=\"{}\" name=\"{}\" time=\"{}\">\n",
+            escape(&result.category),
+            escape(&name),
+            seconds(result.duration_ms)
+        ));
+        if !result.was_automated {
+            xml.push_str("      <skipped message=\"no verification command\"/>\n");
+        } else if !result.passed {
+            let error = result.error_message.as_deref().unwrap_or_default();
+            xml.push_str(&format!(
+                "      <failure message=\"{}\">{}</failure>\n",
+                escape(error.lines().next().unwrap_or_default()),
+                escape(error)
+            ));
+        }
+        if result.cached {
+            xml.push_str("      <system-out>cached: passed against the same tree</system-out>\n");
+        } else if result.flaky {
+            xml.push_str(&format!(
+                "      <system-out>flaky: runs disagreed over {} runs</system-out>\n",
+                result.runs
+            ));
+        }
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// Escape text for XML, dropping what XML 1.0 can't hold
+///
+/// Test output often carries ANSI colour codes, so their escape sequences are
+/// removed along with any other character outside the XML `Char` range.
+fn escape(text: &str) -> String {
+    let mut clean = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            // Control sequence: parameter and intermediate bytes up to a final byte in @..~
+            chars.next();
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        } else if is_xml_char(c) {
+            clean.push(c);
+        }
+    }
+    quick_xml::escape::escape(&clean).into_owned()
+}
+
+/// Whether `c` is allowed in an XML 1.0 document
+fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
 /// Print a formatted report of the regression check results
 pub fn report_results(summary: &RegressionSummary) {
     println!();
@@ -149,6 +607,8 @@ pub fn report_results(summary: &RegressionSummary) {
     println!("Automated tests:");
     println!("  ✓ Passed:           {}", summary.automated_passed);
     println!("  ✗ Failed:           {}", summary.automated_failed);
+    println!("  ~ Flaky:            {}", summary.flaky);
+    println!("  ↺ Cached:           {}", summary.cached);
     println!("  ○ Manual required:  {}", summary.manual_required);
     println!();
 
@@ -182,26 +642,25 @@ pub fn report_results(summary: &RegressionSummary) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::autonomous::verification::{classify_verification_failure, VerificationFailure};
+    use crate::db::features::{FeatureStatus, VerificationStep};
 
     fn create_test_features() -> Vec<Feature> {
         vec![
             Feature {
                 id: Some(1),
-                category: "functional".to_string(),
-                description: "Test feature 1".to_string(),
+                key: format!("feature-{}", 1),
                 steps: vec!["Step 1".to_string(), "Step 2".to_string()],
                 passes: true,
+                status: FeatureStatus::Passing,
                 verification_command: Some("echo test".to_string()),
-                last_error: None,
+                ..Feature::new("functional", "Test feature 1")
             },
             Feature {
                 id: Some(2),
-                category: "functional".to_string(),
-                description: "Test feature 2".to_string(),
+                key: format!("feature-{}", 2),
                 steps: vec!["Step 1".to_string()],
-                passes: false,
-                verification_command: None,
-                last_error: None,
+                ..Feature::new("functional", "Test feature 2")
             },
         ]
     }
@@ -209,11 +668,182 @@ mod tests {
     #[test]
     fn test_run_regression_check() {
         let features = create_test_features();
-        let summary = run_regression_check(&features, None, None, false, None).unwrap();
+        let config = Config::default();
+        let summary = run_regression_check(&features, &CheckOptions::new(&config)).unwrap();
 
         assert_eq!(summary.total_features, 2);
         assert_eq!(summary.passing_features, 1);
         assert_eq!(summary.automated_passed, 1);
         assert_eq!(summary.automated_failed, 0);
+        assert_eq!(summary.flaky, 0);
+    }
+
+    #[test]
+    fn test_concurrent_check_keeps_order_and_reports_junit() {
+        let mut features = create_test_features();
+        features[1].passes = true;
+        features[1].verification_command = Some("echo 'port '$PORT; exit 1".to_string());
+        features.push(Feature {
+            id: Some(3),
+            description: "Manual <check> & more".to_string(),
+            verification_command: None,
+            ..features[0].clone()
+        });
+
+        let config = Config::default();
+        let options = CheckOptions {
+            jobs: 3,
+            ..CheckOptions::new(&config)
+        };
+        let summary = run_regression_check(&features, &options).unwrap();
+        let ids: Vec<_> = summary.results.iter().map(|r| r.feature_id).collect();
+        assert_eq!(ids, vec![Some(1), Some(2), Some(3)]);
+        assert_eq!(
+            (
+                summary.automated_passed,
+                summary.automated_failed,
+                summary.manual_required
+            ),
+            (1, 1, 1)
+        );
+        // Each job has its own port range
+        assert!(summary.results[1]
+            .error_message
+            .as_deref()
+            .unwrap()
+            .contains("port 81"));
+
+        let xml = junit_report(&summary);
+        assert!(xml.contains(r#"<testsuite name="regression" tests="3" failures="1" skipped="1""#));
+        assert!(xml.contains(r##"name="#3 Manual &lt;check&gt; &amp; more""##));
+        assert!(xml.contains("<failure message=\"Passed 0 of 2 runs:\">"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&json_report(&summary).unwrap()).unwrap();
+        assert_eq!(json["results"][1]["runs"], 2);
+    }
+
+    #[test]
+    fn test_junit_report_drops_terminal_control_characters() {
+        let mut features = create_test_features();
+        features.truncate(1);
+        features[0].verification_command =
+            Some("printf '\\033[31mFAILED\\033[0m login\\007\\n'; exit 1".to_string());
+
+        let config = Config::default();
+        let summary = run_regression_check(&features, &CheckOptions::new(&config)).unwrap();
+        assert!(summary.results[0]
+            .error_message
+            .as_deref()
+            .unwrap()
+            .contains('\u{1b}'));
+
+        let xml = junit_report(&summary);
+        assert!(xml.contains("FAILED login"));
+        assert!(!xml.chars().any(|c| !is_xml_char(c)));
+        assert_eq!(escape("a\u{1b}[1;31mb\u{0}<"), "ab&lt;");
+    }
+
+    #[test]
+    fn test_sample_rotates_to_least_recently_verified() {
+        let (_dir, db) = crate::db::test_utils::tests::setup_test_db();
+        let mut features = Vec::new();
+        for feature in create_test_features()
+            .into_iter()
+            .chain(create_test_features())
+        {
+            let id = db
+                .features()
+                .insert(&Feature {
+                    id: None,
+                    key: String::new(),
+                    passes: true,
+                    status: FeatureStatus::Passing,
+                    description: format!("Sampled feature {}", features.len()),
+                    verification_command: Some("true".to_string()),
+                    ..feature
+                })
+                .unwrap();
+            features.push(db.features().get(id).unwrap().unwrap());
+        }
+        let ids: Vec<i64> = features.iter().map(|f| f.id.unwrap()).collect();
+        let sample = |size| {
+            sample_features(&db, &features, size)
+                .unwrap()
+                .into_iter()
+                .map(|(feature, _)| feature.id.unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        // Never-verified features come first, in order
+        assert_eq!(sample(2), ids[..2]);
+        for id in &ids[..2] {
+            db.verification().record_check(*id, false).unwrap();
+        }
+        assert_eq!(sample(2), ids[2..]);
+
+        // Once everything was checked, a flaky history moves a feature up
+        db.verification().record_check(ids[2], false).unwrap();
+        db.verification().record_check(ids[3], true).unwrap();
+        assert_eq!(sample(1), vec![ids[3]]);
+
+        let risk = RegressionRisk {
+            hours_since_verified: Some(2.0),
+            touched_by_last_commit: true,
+            flake_rate: 0.5,
+        };
+        assert_eq!(
+            risk.score(),
+            2.0 + TOUCHED_WEIGHT_HOURS + 0.5 * FLAKY_WEIGHT_HOURS
+        );
+        assert_eq!(
+            risk.describe(),
+            "verified 2.0h ago, touched by last commit, 50% flaky"
+        );
+    }
+
+    #[test]
+    fn test_dropped_job_removes_its_worktree() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("forger-check-job");
+        std::fs::create_dir(&path).unwrap();
+        std::fs::write(path.join("left-over.txt"), "x").unwrap();
+
+        let job = Job {
+            root: path.clone(),
+            env: Vec::new(),
+            worktree: Some(path.clone()),
+        };
+        drop(job);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_check_honours_configured_blocked_patterns() {
+        let mut features = create_test_features();
+        features[0].verification_command = Some("echo deploy-prod".to_string());
+
+        let mut config = Config::default();
+        config
+            .security
+            .blocked_patterns
+            .push("deploy-prod".to_string());
+        let summary = run_regression_check(&features, &CheckOptions::new(&config)).unwrap();
+
+        assert_eq!(summary.automated_failed, 1);
+        let error = summary.results[0].error_message.as_deref().unwrap();
+        assert!(error.starts_with("Security blocked"), "{}", error);
+
+        // A step that can't run is a broken definition, not a blocked command
+        let mut step = VerificationStep::new("unit", "true");
+        step.workdir = Some("does-not-exist".to_string());
+        features[0].verification_steps = vec![step];
+        let summary = run_regression_check(&features, &CheckOptions::new(&config)).unwrap();
+        let error = summary.results[0].error_message.as_deref().unwrap();
+        assert!(error.starts_with("Verification error"), "{}", error);
+        assert_eq!(
+            classify_verification_failure(error),
+            VerificationFailure::CommandError
+        );
     }
 }
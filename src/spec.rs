@@ -1,5 +1,8 @@
 //! App specification schema
 
+use anyhow::{Context, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 
 /// Application specification - technology agnostic
@@ -137,7 +140,8 @@ pub struct Feature {
     pub sub_features: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+/// Feature priority, most important first (the derived `Ord` follows this order)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum Priority {
     Critical,
@@ -158,6 +162,153 @@ impl Priority {
     }
 }
 
+impl std::str::FromStr for Priority {
+    type Err = anyhow::Error;
+
+    /// Accepts the priority names and the common `P0`-`P3` labels
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "critical" | "p0" => Ok(Priority::Critical),
+            "high" | "p1" => Ok(Priority::High),
+            "medium" | "p2" => Ok(Priority::Medium),
+            "low" | "p3" => Ok(Priority::Low),
+            _ => anyhow::bail!(
+                "Unknown priority '{}' (expected critical, high, medium, low or P0-P3)",
+                value
+            ),
+        }
+    }
+}
+
+/// Features listed in the `<core_features>` block of an app spec
+///
+/// Understands the generator's `<feature priority="...">` entries with
+/// `<name>`, `<description>` and a `<sub_features>` list, as well as the
+/// project templates' shorthand of one element per feature holding a
+/// `- item` list (the element name becomes the feature name).
+pub fn parse_core_features(spec_text: &str) -> Result<Vec<Feature>> {
+    let start = spec_text
+        .find("<core_features>")
+        .context("Spec has no <core_features> block")?;
+    let end = spec_text[start..]
+        .find("</core_features>")
+        .map(|i| start + i + "</core_features>".len())
+        .context("Spec has no closing </core_features> tag")?;
+
+    let mut reader = Reader::from_str(&spec_text[start..end]);
+
+    let mut features = Vec::new();
+    let mut current: Option<Feature> = None;
+    let mut child = String::new();
+    // Entity references arrive as separate events, so text is collected until the next tag
+    let mut text = String::new();
+    // 0 = inside <core_features>, 1 = inside a feature, 2+ = inside its children
+    let mut depth = 0usize;
+
+    loop {
+        let event = reader.read_event();
+        if matches!(event, Ok(Event::Start(_)) | Ok(Event::End(_))) {
+            if let Some(feature) = current.as_mut() {
+                match (depth, child.as_str()) {
+                    (1, _) => feature.sub_features.extend(list_items(&text)),
+                    (2, "name") => feature.name = text.trim().to_string(),
+                    (2, "description") => feature.description = text.trim().to_string(),
+                    (2, "sub_features") => feature.sub_features.extend(list_items(&text)),
+                    _ => {}
+                }
+            }
+            text.clear();
+        }
+
+        match event {
+            Ok(Event::Start(e)) => {
+                let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                if tag == "core_features" {
+                    continue;
+                }
+                depth += 1;
+                match depth {
+                    1 => {
+                        let priority = e
+                            .try_get_attribute("priority")
+                            .ok()
+                            .flatten()
+                            .and_then(|a| a.unescape_value().ok()?.parse().ok())
+                            .unwrap_or_default();
+                        let name = if tag == "feature" {
+                            String::new()
+                        } else {
+                            humanize_tag(&tag)
+                        };
+                        current = Some(Feature {
+                            name,
+                            description: String::new(),
+                            priority,
+                            sub_features: Vec::new(),
+                        });
+                    }
+                    2 => child = tag,
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(e)) => text.push_str(&e.decode().unwrap_or_default()),
+            Ok(Event::CData(e)) => text.push_str(&e.decode().unwrap_or_default()),
+            Ok(Event::GeneralRef(e)) => {
+                let name = e.decode().unwrap_or_default();
+                if let Ok(Some(c)) = e.resolve_char_ref() {
+                    text.push(c);
+                } else if let Some(entity) = quick_xml::escape::resolve_predefined_entity(&name) {
+                    text.push_str(entity);
+                }
+            }
+            Ok(Event::End(e)) => {
+                if e.name().as_ref() == b"core_features" {
+                    break;
+                }
+                if depth == 2 {
+                    child.clear();
+                }
+                if depth == 1 {
+                    if let Some(feature) = current.take() {
+                        if !feature.name.is_empty() {
+                            features.push(feature);
+                        }
+                    }
+                }
+                depth = depth.saturating_sub(1);
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => anyhow::bail!(
+                "Invalid <core_features> block at position {}: {}",
+                reader.buffer_position(),
+                e
+            ),
+            _ => {}
+        }
+    }
+
+    Ok(features)
+}
+
+/// `user_authentication` -> `User authentication`
+fn humanize_tag(tag: &str) -> String {
+    let words = tag.replace(['_', '-'], " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Items of a `- item` list, one per line
+fn list_items(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix('-'))
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TechStack {
     pub languages: Vec<String>,
@@ -208,4 +359,52 @@ mod tests {
         assert!(text.contains("<project_name>Test Project</project_name>"));
         assert!(text.contains("Feature 1"));
     }
+
+    #[test]
+    fn test_parse_core_features_round_trip() {
+        let spec = AppSpec {
+            project_name: "Shop".to_string(),
+            features: vec![
+                Feature {
+                    name: "Checkout".to_string(),
+                    description: "Pay in < 3 clicks".to_string(),
+                    priority: Priority::Critical,
+                    sub_features: vec!["Card payment".to_string(), "Receipt email".to_string()],
+                },
+                Feature {
+                    name: "Wishlist".to_string(),
+                    description: String::new(),
+                    priority: Priority::Low,
+                    sub_features: vec![],
+                },
+            ],
+            ..Default::default()
+        };
+        let text = spec.to_spec_text().replace("< 3", "&lt; 3");
+
+        let features = parse_core_features(&text).unwrap();
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0].name, "Checkout");
+        assert_eq!(features[0].description, "Pay in < 3 clicks");
+        assert_eq!(features[0].priority, Priority::Critical);
+        assert_eq!(
+            features[0].sub_features,
+            vec!["Card payment", "Receipt email"]
+        );
+        assert_eq!(features[1].priority, Priority::Low);
+    }
+
+    #[test]
+    fn test_parse_core_features_template_shorthand() {
+        let text = "<core_features>\n<user_authentication>\n- Login with JWT tokens\n- Password hashing\n  </user_authentication>\n</core_features>";
+
+        let features = parse_core_features(text).unwrap();
+        assert_eq!(features.len(), 1);
+        assert_eq!(features[0].name, "User authentication");
+        assert_eq!(features[0].priority, Priority::Medium);
+        assert_eq!(
+            features[0].sub_features,
+            vec!["Login with JWT tokens", "Password hashing"]
+        );
+    }
 }
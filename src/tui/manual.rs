@@ -100,7 +100,7 @@ fn select_priority(config: &Config) -> Result<Priority> {
 
     Ok(options
         .get(idx)
-        .map(|opt| opt.value.clone())
+        .map(|opt| opt.value)
         .unwrap_or(Priority::Medium))
 }
 
@@ -108,23 +108,24 @@ fn run_vibe(cwd: &Path, args: &[&str]) -> std::process::ExitStatus {
         .expect("Failed to run vibe")
 }
 
-/// Add a mock feature to the database
-fn add_feature(cwd: &Path, id: i32, desc: &str) {
+/// Add a mock feature to the database (ids are assigned in insertion order)
+fn add_feature(cwd: &Path, desc: &str) {
     let bin_path = get_bin_path();
     let mut cmd = Command::new(bin_path);
 
-    let sql = format!(
-        "INSERT INTO features (id, description, category, passes) VALUES ({}, '{}', 'Test', 0)",
-        id, desc
-    );
-
-    cmd.arg("db")
-        .arg("exec")
-        .arg(sql)
-        .current_dir(cwd)
-        .stdout(Stdio::null())
-        .status()
-        .expect("Failed to add feature");
+    cmd.args([
+        "db",
+        "feature",
+        "add",
+        "--category",
+        "Test",
+        "--description",
+        desc,
+    ])
+    .current_dir(cwd)
+    .stdout(Stdio::null())
+    .status()
+    .expect("Failed to add feature");
 }
 
 /// Check if opencode CLI is available in PATH
@@ -145,7 +146,7 @@ fn test_verification_failure_prevents_merge() {
     let (_temp, project_path) = setup_project();
 
     // 1. Add feature
-    add_feature(&project_path, 1, "Feature A");
+    add_feature(&project_path, "Feature A");
 
     // Placeholder for now
 }
@@ -153,8 +154,8 @@ fn test_verification_failure_prevents_merge() {
 #[test]
 fn test_parallel_execution_cleanup() {
     let (_temp, project_path) = setup_project();
-    add_feature(&project_path, 1, "Feature Clean");
-    add_feature(&project_path, 2, "Feature Clean 2");
+    add_feature(&project_path, "Feature Clean");
+    add_feature(&project_path, "Feature Clean 2");
 
     let _status = run_vibe(&project_path, &["--parallel", "2", "--limit", "1"]);
 
@@ -177,7 +178,7 @@ fn test_crash_recovery_zombie_worktree() {
     fs::create_dir_all(&zombie_path).unwrap();
     // Lock it?
 
-    add_feature(&project_path, 1, "Zombie Feat");
+    add_feature(&project_path, "Zombie Feat");
 
     // 2. Run vibe (starts worker 1, which targets feature 1)
     // It should force-remove the zombie folder.
@@ -213,7 +214,7 @@ fn test_git_index_lock_recovery() {
     // but a *graceful* one (timeout), not a hang.
 
     // 2. Add pending feature to force worktree creation
-    add_feature(&project_path, 999, "Lock Test Feature");
+    add_feature(&project_path, "Lock Test Feature");
 
     // 3. Run vibe
     let handle =
@@ -230,7 +231,7 @@ fn test_database_concurrency_stress() {
     let db_path = project_path.join(".forger/progress.db");
 
     // Initialize DB with one feature using the CLI first to ensure schema exists
-    add_feature(&project_path, 1, "Initial Feature");
+    add_feature(&project_path, "Initial Feature");
 
     // Spawn 10 threads to hammer the DB
     let mut handles = vec![];
@@ -280,7 +281,7 @@ fn test_feature_isolation() {
     // This requires git commands.
 
     // 1. Add feature A (cli)
-    add_feature(&project_path, 1, "Feat A");
+    add_feature(&project_path, "Feat A");
 
     // 2. Run vibe parallel 2 limit 1 (creates worktrees)
     //    We rely on the previous test_parallel_execution_cleanup logic which proves worktrees run.